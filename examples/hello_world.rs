@@ -1,14 +1,17 @@
-use panoramix::elements::{Button, Label};
+use panoramix::elements::{Button, ComponentOutput, Label};
 use panoramix::{component, Column, CompCtx, Element, NoEvent, RootHandler};
 
 #[component]
 fn HelloBox(ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
     let md = ctx.use_metadata::<NoEvent, ()>();
-    Column!(
-        Label::new("Hello world!"),
-        Button::new("Say hello").on_click(md, |_, _| {
-            println!("Hello world");
-        })
+    ComponentOutput::new(
+        md,
+        Column!(
+            Label::new("Hello world!"),
+            Button::new("Say hello").on_click(md, |_, _| {
+                println!("Hello world");
+            })
+        ),
     )
 }
 
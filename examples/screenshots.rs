@@ -0,0 +1,82 @@
+//! Regenerate the screenshots of the other examples.
+//!
+//! ```sh
+//! cargo run --example=screenshots -- [--size 640x480]... [--out-dir DIR] [EXAMPLE]...
+//! ```
+//!
+//! Each example is run with the `png` feature and the `PANORAMIX_SCREENSHOT` environment variable,
+//! which makes `RootHandler::launch` save a screenshot instead of opening a window. Screenshots are
+//! written to `examples/screenshots/` by default.
+
+use panoramix::screenshot::{parse_window_size, SCREENSHOT_SIZE_VAR, SCREENSHOT_VAR};
+use std::path::PathBuf;
+use std::process::Command;
+
+const EXAMPLES: &[&str] = &["hello_world", "list", "todo_list"];
+
+fn main() {
+    let mut sizes = Vec::new();
+    let mut out_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("examples")
+        .join("screenshots");
+    let mut examples = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--size" => {
+                let size = args
+                    .next()
+                    .expect("error: --size expects a value, eg 640x480");
+                if parse_window_size(&size).is_none() {
+                    panic!("error: invalid window size '{}', expected eg 640x480", size);
+                }
+                sizes.push(size);
+            }
+            "--out-dir" => {
+                out_dir = args
+                    .next()
+                    .expect("error: --out-dir expects a value")
+                    .into();
+            }
+            example => examples.push(example.to_string()),
+        }
+    }
+    if examples.is_empty() {
+        examples = EXAMPLES.iter().map(|example| example.to_string()).collect();
+    }
+
+    std::fs::create_dir_all(&out_dir).unwrap();
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+
+    for example in &examples {
+        // With no --size argument, the default window size of the harness is used.
+        let runs: Vec<Option<&String>> = if sizes.is_empty() {
+            vec![None]
+        } else {
+            sizes.iter().map(Some).collect()
+        };
+
+        for size in runs {
+            let file_name = match size {
+                Some(size) if sizes.len() > 1 => format!("{}-{}.png", example, size),
+                _ => format!("{}.png", example),
+            };
+            let path = out_dir.join(file_name);
+
+            let mut command = Command::new(&cargo);
+            command
+                .args(&["run", "--quiet", "--features", "png", "--example", example])
+                .env(SCREENSHOT_VAR, &path);
+            if let Some(size) = size {
+                command.env(SCREENSHOT_SIZE_VAR, size);
+            }
+
+            let status = command.status().unwrap();
+            if !status.success() {
+                panic!("error: example '{}' failed with {}", example, status);
+            }
+            println!("{}", path.display());
+        }
+    }
+}
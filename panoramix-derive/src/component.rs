@@ -1,5 +1,5 @@
-use proc_macro2::TokenStream;
-use quote::{quote, ToTokens};
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::{quote, quote_spanned, ToTokens};
 use std::fmt::Display;
 use syn::Error;
 
@@ -135,6 +135,16 @@ pub fn component(attr: TokenStream, fn_item: syn::ItemFn) -> Result<TokenStream,
         );
     };
 
+    // Components that call use_metadata (or use_persisted_metadata) or use_state must return a
+    // ComponentOutput; otherwise local state and event queues can't be found at runtime. We check
+    // this by adding a marker bound to the return type of render(), spanned on the method call,
+    // so the compiler error points to it.
+    let render_output = if let Some(span) = find_state_method_call(fn_block.to_token_stream()) {
+        add_component_output_bound(fn_output, span)
+    } else {
+        fn_output
+    };
+
     // TODO
     // - Error message if user tries to do MyComponent(props) instead of MyComponent::new(props)
 
@@ -158,7 +168,7 @@ pub fn component(attr: TokenStream, fn_item: syn::ItemFn) -> Result<TokenStream,
             #vis fn render(
                 #ctx_arg,
                 #props_arg,
-            ) -> #fn_output {
+            ) -> #render_output {
                 #fn_block
             }
         }
@@ -241,3 +251,49 @@ fn parse_return_ty(return_ty: syn::Type) -> Result<Option<syn::Type>, Error> {
 
     Ok(local_event_ty)
 }
+
+fn find_state_method_call(tokens: TokenStream) -> Option<Span> {
+    let mut prev_token_is_dot = false;
+    for token in tokens {
+        match token {
+            TokenTree::Group(group) => {
+                if let Some(span) = find_state_method_call(group.stream()) {
+                    return Some(span);
+                }
+                prev_token_is_dot = false;
+            }
+            TokenTree::Ident(ident)
+                if prev_token_is_dot
+                    && (ident == "use_metadata"
+                        || ident == "use_persisted_metadata"
+                        || ident == "use_state") =>
+            {
+                return Some(ident.span());
+            }
+            TokenTree::Punct(punct) => {
+                prev_token_is_dot = punct.as_char() == '.';
+            }
+            _ => {
+                prev_token_is_dot = false;
+            }
+        }
+    }
+    None
+}
+
+fn add_component_output_bound(return_ty: syn::Type, span: Span) -> syn::Type {
+    let mut impl_trait = if let syn::Type::ImplTrait(impl_trait) = return_ty {
+        impl_trait
+    } else {
+        // parse_return_ty already checked that the return type is `impl Element`
+        unreachable!()
+    };
+
+    let bound: syn::TypeParamBound = syn::parse2(quote_spanned! {span=>
+        panoramix::elements::internals::IsComponentOutput
+    })
+    .unwrap();
+    impl_trait.bounds.push(bound);
+
+    syn::Type::ImplTrait(impl_trait)
+}
@@ -43,6 +43,25 @@ use tracing::trace;
 /// MyComponent::new(my_props)
 /// # ;
 /// ```
+///
+/// ## Metadata
+///
+/// Components which call [`CompCtx::use_metadata`](panoramix::CompCtx::use_metadata) or
+/// [`CompCtx::use_state`](panoramix::CompCtx::use_state) must wrap their return value in a
+/// [`ComponentOutput`](panoramix::elements::ComponentOutput). This is checked at compile time,
+/// and the error points to the method call:
+///
+/// ```compile_fail
+/// # use panoramix::{component, CompCtx, Element, NoEvent};
+/// # use panoramix::elements::EmptyElement;
+/// #[component]
+/// fn MyComponent(ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
+///     let md = ctx.use_metadata::<NoEvent, i32>();
+///     let _local_state = ctx.get_local_state(md);
+///     // Error: should be ComponentOutput::new(md, EmptyElement::new())
+///     EmptyElement::new()
+/// }
+/// ```
 #[allow(rustdoc::broken_intra_doc_links)]
 #[proc_macro_attribute]
 pub fn component(attr: TokenStream, item: TokenStream) -> TokenStream {
@@ -1,13 +1,23 @@
+use crate::elements::internals::AnyStateBox;
 use crate::glue::DruidAppData;
-use crate::metadata::{Metadata, NoState};
+use crate::metadata::{Metadata, NoState, StateHandle};
 use druid::{Env, EventCtx};
 use std::any::{type_name, Any, TypeId};
+use std::cell::OnceCell;
+use std::fmt::Debug;
 
 /// Context type passed to all components when building them.
 pub struct CompCtx<'a> {
     // Yeah, we're using a cell, sorry. It's very local, though.
     pub(crate) called_use_metadata: std::cell::Cell<bool>,
     pub(crate) local_state: &'a dyn Any,
+    pub(crate) hook_states: &'a [AnyStateBox],
+    pub(crate) hook_count: std::cell::Cell<usize>,
+    /// Slots created (or re-created) during this render, as `(index, value)` pairs.
+    pub(crate) new_hook_states: std::cell::RefCell<Vec<(usize, AnyStateBox)>>,
+    /// Local state saved to disk, set by [`use_persisted_metadata`](Self::use_persisted_metadata).
+    /// If set, it replaces `local_state`.
+    pub(crate) restored_state: &'a OnceCell<Box<dyn Any>>,
 }
 
 impl<'a> CompCtx<'a> {
@@ -30,6 +40,39 @@ impl<'a> CompCtx<'a> {
         Default::default()
     }
 
+    /// Same as [`use_metadata`](Self::use_metadata), except the local state of the component
+    /// instance is saved to disk.
+    ///
+    /// The local state is saved when it changes, and restored when the app starts, if the app
+    /// is created with [`RootHandler::with_component_persistence`](crate::RootHandler::with_component_persistence).
+    /// Otherwise, this method is the same as `use_metadata`.
+    ///
+    /// Instances are identified by their path in the inspector tree (eg `App/Column/Form`), so
+    /// a state is restored if the instance is at the same place when the app starts again.
+    ///
+    /// Requires the `serde` feature.
+    ///
+    /// ## Panic
+    ///
+    /// Same as `use_metadata`. Also panics if the root element of the component isn't
+    /// [`ComponentOutput`](crate::elements::ComponentOutput).
+    #[cfg(feature = "serde")]
+    pub fn use_persisted_metadata<ComponentEvent: 'static, ComponentState>(
+        &self,
+    ) -> Metadata<ComponentEvent, ComponentState>
+    where
+        ComponentState: serde_crate::Serialize + serde_crate::de::DeserializeOwned + 'static,
+    {
+        let md = self.use_metadata();
+        if let Some(path) = crate::element_path::current() {
+            let local_state = self.get_local_state(md);
+            if let Some(state) = crate::persistence::persist_state(&path, local_state) {
+                let _ = self.restored_state.set(Box::new(state));
+            }
+        }
+        md
+    }
+
     /// Returns the local state of the current component instance.
     ///
     /// The local state of a component is initialized to a default value when a component
@@ -43,19 +86,91 @@ impl<'a> CompCtx<'a> {
         md: Metadata<ComponentEvent, ComponentState>,
     ) -> &'a ComponentState {
         #![allow(unused_variables)]
-        if (*self.local_state).type_id() == TypeId::of::<NoState>() {
+        let local_state = match self.restored_state.get() {
+            Some(restored_state) => &**restored_state,
+            None => self.local_state,
+        };
+        if (*local_state).type_id() == TypeId::of::<NoState>() {
             panic!("error: 'get_local_state' cannot be called for a component whose root element isn't ComponentOutput")
         }
-        self.local_state
+        local_state
             .downcast_ref::<ComponentState>()
             .expect(&format!(
             "internal type error: get_local_state expected {:?} ({}), parent component gave {:?}",
             TypeId::of::<ComponentState>(),
             type_name::<ComponentState>(),
-            (*self.local_state).type_id(),
+            (*local_state).type_id(),
         ))
     }
 
+    /// Returns the value of a state slot, and a handle used to modify it in event callbacks.
+    ///
+    /// Unlike [`get_local_state`](Self::get_local_state), this method can be called several
+    /// times per component; each call gets its own slot, initialized with `init()` when the
+    /// component instance is first created. To modify the slot, pass the handle to
+    /// [`ElementExt::on_state`](crate::ElementExt::on_state).
+    ///
+    /// Slots are identified by the order of calls, so this method must be called the same
+    /// number of times and in the same order every time the component is called. In other
+    /// words, don't call it in a branch or a loop.
+    ///
+    /// ## Panic
+    ///
+    /// Panics if the root element of the component isn't [`ComponentOutput`](crate::elements::ComponentOutput).
+    ///
+    /// In debug builds, panics if the order of calls changed since the previous call of the component.
+    pub fn use_state<T: Clone + Debug + PartialEq + 'static>(
+        &self,
+        init: impl FnOnce() -> T,
+    ) -> (T, StateHandle<T>) {
+        if (*self.local_state).type_id() == TypeId::of::<NoState>() {
+            panic!("error: 'use_state' cannot be called for a component whose root element isn't ComponentOutput")
+        }
+
+        let index = self.hook_count.get();
+        self.hook_count.set(index + 1);
+
+        if let Some(prev_value) = self.hook_states.get(index) {
+            if let Some(prev_value) = prev_value.downcast_ref::<T>() {
+                return (prev_value.clone(), StateHandle::new(index));
+            }
+            if cfg!(debug_assertions) {
+                panic!(
+                    "error: hook order changed between renders: 'use_state' call #{} expected {}, found {:?}",
+                    index,
+                    type_name::<T>(),
+                    prev_value,
+                )
+            }
+        }
+
+        let value = init();
+        self.new_hook_states
+            .borrow_mut()
+            .push((index, AnyStateBox::new(value.clone())));
+        (value, StateHandle::new(index))
+    }
+
+    /// Returns a slice of the app-wide store.
+    ///
+    /// `selector` is called with the store set by [`RootHandler::with_store`](crate::RootHandler::with_store).
+    /// To modify the store, use [`ElementExt::on_store`](crate::ElementExt::on_store).
+    ///
+    /// After the store is modified, only the components whose selected slices changed
+    /// (compared with `PartialEq`) are called again, along with their descendants. The other
+    /// components reuse the elements they returned, unless their props or local state changed
+    /// too.
+    ///
+    /// ## Panic
+    ///
+    /// Panics if the app doesn't have a store, or if the store isn't of type `S`.
+    pub fn use_selector<S: 'static, R: Clone + PartialEq + 'static>(
+        &self,
+        selector: impl Fn(&S) -> R + 'static,
+    ) -> R {
+        crate::store::select(selector)
+    }
+
     // TODO - add methods
     // use_lifecycle
     // get_vdom_context
@@ -71,6 +186,7 @@ pub struct ReconcileCtx<'a, 'b, 'c, 'd, 'e> {
 pub struct ProcessEventCtx<'e, 's> {
     pub event_queue: &'e mut dyn Any,
     pub state: &'s mut dyn Any,
+    pub hook_states: &'s mut Vec<AnyStateBox>,
 }
 
 impl<'e, 's> ProcessEventCtx<'e, 's> {
@@ -103,6 +219,19 @@ impl<'e, 's> ProcessEventCtx<'e, 's> {
             type_id,
         ))
     }
+
+    pub fn hook_state<T: 'static>(&mut self, handle: StateHandle<T>) -> &mut T {
+        let hook_count = self.hook_states.len();
+        self.hook_states
+            .get_mut(handle.index)
+            .and_then(AnyStateBox::downcast_mut::<T>)
+            .expect(&format!(
+                "internal type error: event handler expected state slot #{} of type {}, component has {} slots",
+                handle.index,
+                type_name::<T>(),
+                hook_count,
+            ))
+    }
 }
 
 #[cfg(test)]
@@ -122,6 +251,7 @@ mod tests {
         let mut ctx = ProcessEventCtx {
             event_queue: &mut event_queue,
             state: &mut NoState,
+            hook_states: &mut Vec::new(),
         };
         ctx.event_queue(md).push(MyEvent(42));
 
@@ -136,6 +266,7 @@ mod tests {
         let mut ctx = ProcessEventCtx {
             event_queue: &mut Vec::<NoEvent>::new(),
             state: &mut state,
+            hook_states: &mut Vec::new(),
         };
 
         assert_eq!(*ctx.state(md), 12345_i64,);
@@ -143,4 +274,31 @@ mod tests {
         *ctx.state(md) = 123_i64;
         assert_eq!(state, 123_i64,);
     }
+
+    #[test]
+    fn hook_state() {
+        let handle_a: StateHandle<i64> = StateHandle::new(0);
+        let handle_b: StateHandle<String> = StateHandle::new(1);
+
+        let mut hook_states = vec![
+            AnyStateBox::new(12345_i64),
+            AnyStateBox::new(String::from("Hello")),
+        ];
+        let mut ctx = ProcessEventCtx {
+            event_queue: &mut Vec::<NoEvent>::new(),
+            state: &mut NoState,
+            hook_states: &mut hook_states,
+        };
+
+        assert_eq!(*ctx.hook_state(handle_a), 12345_i64);
+        *ctx.hook_state(handle_b) = String::from("World");
+
+        assert_eq!(
+            hook_states,
+            vec![
+                AnyStateBox::new(12345_i64),
+                AnyStateBox::new(String::from("World")),
+            ]
+        );
+    }
 }
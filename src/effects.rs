@@ -0,0 +1,209 @@
+use druid::Command;
+use std::any::Any;
+use std::fmt::Debug;
+use std::rc::Rc;
+use std::time::Duration;
+
+/// A side effect requested by an event callback.
+///
+/// See [`Effects`].
+#[derive(Debug, Clone)]
+pub enum Effect {
+    /// Submit a druid command.
+    Command(Command),
+    /// Give keyboard focus to the first widget of the element with the given path.
+    Focus(String),
+    /// Scroll the window so that the element with the given path is visible.
+    ScrollTo(String),
+    /// Send a message to [`ExternalMessages`](crate::elements::ExternalMessages) elements
+    /// once the delay has elapsed.
+    StartTimer {
+        delay: Duration,
+        message: TimerMessage,
+    },
+    /// Close the window the component is in.
+    CloseWindow,
+    /// Restore the app state from before the last change.
+    Undo,
+    /// Cancel the last undo.
+    Redo,
+}
+
+/// Message sent when the timer of an [`Effect::StartTimer`] fires.
+#[derive(Clone)]
+pub struct TimerMessage {
+    make_message: Rc<dyn Fn() -> Box<dyn Any + Send>>,
+    debug: String,
+}
+
+/// Queue of side effects requested by event callbacks.
+///
+/// Callbacks passed to [`ElementExt::on_with_effects`](crate::ElementExt::on_with_effects)
+/// get a mutable reference to this queue. Effects are executed in the order they were
+/// requested, once the GUI has been updated with the new local state.
+#[derive(Debug, Clone, Default)]
+pub struct Effects {
+    effects: Vec<Effect>,
+}
+
+impl Effects {
+    /// Submit a druid [`Command`].
+    pub fn submit_command(&mut self, command: impl Into<Command>) {
+        self.effects.push(Effect::Command(command.into()));
+    }
+
+    /// Give keyboard focus to the element with the given path.
+    ///
+    /// Paths are the ones of the inspector tree (see [`InspectNode::path`](crate::InspectNode)),
+    /// eg `"Form/Column/TextBox#1"`. If the element has several widgets, the first one gets
+    /// the focus.
+    pub fn request_focus(&mut self, path: impl Into<String>) {
+        self.effects.push(Effect::Focus(path.into()));
+    }
+
+    /// Scroll the window so that the element with the given path is visible.
+    ///
+    /// See [`request_focus`](Self::request_focus) for the format of paths.
+    pub fn scroll_to(&mut self, path: impl Into<String>) {
+        self.effects.push(Effect::ScrollTo(path.into()));
+    }
+
+    /// Send `message` to [`ExternalMessages`](crate::elements::ExternalMessages) elements once
+    /// `delay` has elapsed, as if it had been sent with an
+    /// [`ExternalHandle`](crate::ExternalHandle).
+    pub fn start_timer<Message: Clone + Debug + Send + 'static>(
+        &mut self,
+        delay: Duration,
+        message: Message,
+    ) {
+        let message = TimerMessage {
+            debug: format!("{:?}", message),
+            make_message: Rc::new(move || Box::new(message.clone())),
+        };
+        self.effects.push(Effect::StartTimer { delay, message });
+    }
+
+    /// Close the window the component is in.
+    pub fn close_window(&mut self) {
+        self.effects.push(Effect::CloseWindow);
+    }
+
+    /// Restore the app state from before the last change.
+    ///
+    /// Only has an effect if the app keeps a history (see
+    /// [`RootHandler::with_history`](crate::RootHandler::with_history)). Unlike other effects,
+    /// undo and redo are applied before the GUI is updated.
+    pub fn undo(&mut self) {
+        self.effects.push(Effect::Undo);
+    }
+
+    /// Cancel the last undo.
+    ///
+    /// See [`undo`](Self::undo).
+    pub fn redo(&mut self) {
+        self.effects.push(Effect::Redo);
+    }
+
+    /// Returns true if no effect was requested.
+    pub fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+
+    /// Iterate over requested effects, in the order they were requested.
+    pub fn iter(&self) -> impl Iterator<Item = &Effect> {
+        self.effects.iter()
+    }
+
+    /// Run by the root widget, which has access to the widget tree.
+    pub(crate) fn into_vec(self) -> Vec<Effect> {
+        self.effects
+    }
+}
+
+impl TimerMessage {
+    pub(crate) fn make_message(&self) -> Box<dyn Any + Send> {
+        (self.make_message)()
+    }
+}
+
+impl Debug for TimerMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.debug)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ctx::ProcessEventCtx;
+    use crate::element_tree::{Element, ElementExt, VirtualDom};
+    use crate::elements::{Button, ButtonClick};
+    use crate::glue::{Action, DruidAppData, GlobalEventCx};
+    use crate::metadata::{Metadata, NoEvent};
+    use druid::Selector;
+    use test_env_log::test;
+
+    const MY_SELECTOR: Selector<i32> = Selector::new("panoramix.test.my_selector");
+
+    #[test]
+    fn queue_effects() {
+        let mut effects = Effects::default();
+        assert!(effects.is_empty());
+
+        effects.submit_command(MY_SELECTOR.with(42));
+        effects.request_focus("Form/Column/TextBox");
+        effects.scroll_to("Form/Column/Button");
+        effects.start_timer(Duration::from_secs(1), 42_u32);
+        effects.close_window();
+        effects.undo();
+        effects.redo();
+
+        let effects: Vec<_> = effects.iter().collect();
+        assert!(matches!(effects[0], Effect::Command(command) if command.is(MY_SELECTOR)));
+        assert!(matches!(effects[1], Effect::Focus(path) if path == "Form/Column/TextBox"));
+        assert!(matches!(effects[2], Effect::ScrollTo(path) if path == "Form/Column/Button"));
+        match effects[3] {
+            Effect::StartTimer { delay, message } => {
+                assert_eq!(*delay, Duration::from_secs(1));
+                assert_eq!(format!("{:?}", message), "42");
+                assert_eq!(message.make_message().downcast_ref::<u32>(), Some(&42));
+            }
+            effect => panic!("unexpected effect {:?}", effect),
+        }
+        assert!(matches!(effects[4], Effect::CloseWindow));
+        assert!(matches!(effects[5], Effect::Undo));
+        assert!(matches!(effects[6], Effect::Redo));
+    }
+
+    #[test]
+    fn callback_effects() {
+        let md: Metadata<NoEvent, u32> = Default::default();
+        let button = Button::new("Submit").on_with_effects(
+            md,
+            |clicks: &mut u32, _: ButtonClick, effects: &mut Effects| {
+                *clicks += 1;
+                effects.submit_command(MY_SELECTOR.with(42));
+            },
+        );
+
+        let (vdom, mut state) = button.build(Default::default());
+        let mut widget_seq = vdom.init_tree();
+
+        let mut data = DruidAppData::default();
+        data.queue_action(widget_seq.id(), Action::Clicked);
+        let mut cx = GlobalEventCx::new(&mut data);
+
+        let mut clicks = 0_u32;
+        let mut ctx = ProcessEventCtx {
+            event_queue: &mut Vec::<NoEvent>::new(),
+            state: &mut clicks,
+            hook_states: &mut Vec::new(),
+        };
+        vdom.process_event(&mut ctx, &mut state, &mut widget_seq, &mut cx);
+
+        assert_eq!(clicks, 1);
+        let effects: Vec<_> = cx.effects.iter().collect();
+        assert_eq!(effects.len(), 1);
+        assert!(matches!(effects[0], Effect::Command(command) if command.is(MY_SELECTOR)));
+    }
+}
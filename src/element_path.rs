@@ -0,0 +1,174 @@
+//! Paths of the elements being built, in the format of [`InspectNode::path`](crate::InspectNode).
+//!
+//! Element::build has no context parameter, so components and container elements push their
+//! name here while their children are built. This lets component instances be identified by
+//! the same path during a build and in the inspector tree, eg `TodoList/List/TodoItem[42]`.
+//!
+//! Only components and elements with children (Flex, Clickable and ElementList) push a name.
+//! The other elements don't have descendants, so they don't change the path of any component.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+struct PathFrame {
+    path: Rc<str>,
+    /// Number of children with each name built so far, to number siblings.
+    name_counts: HashMap<&'static str, usize>,
+    /// Key of the list item being built, if this is the frame of a list.
+    key: Option<String>,
+}
+
+thread_local! {
+    static PATH_STACK: RefCell<Option<Vec<PathFrame>>> = RefCell::new(None);
+}
+
+//
+// --- IMPLS
+
+impl PathFrame {
+    fn new(path: Rc<str>) -> Self {
+        PathFrame {
+            path,
+            name_counts: HashMap::new(),
+            key: None,
+        }
+    }
+}
+
+/// Run `build` with the paths of the elements it builds available to [`enter`].
+pub(crate) fn with_paths<R>(build: impl FnOnce() -> R) -> R {
+    let prev_stack = PATH_STACK.with(|stack| stack.replace(Some(vec![PathFrame::new("".into())])));
+    let output = build();
+    PATH_STACK.with(|stack| stack.replace(prev_stack));
+    output
+}
+
+/// Start building the children of an element, and return the path of the element.
+///
+/// Siblings with the same name are numbered like in the inspector tree (`Name`, `Name#1`,
+/// etc), unless they're items of a list, in which case they get the key of the item
+/// (`Name[key]`).
+///
+/// Returns `None` outside of [`with_paths`].
+pub(crate) fn enter(name: &'static str) -> Option<Rc<str>> {
+    PATH_STACK.with(|stack| {
+        let mut stack = stack.borrow_mut();
+        let stack = stack.as_mut()?;
+        let parent = stack.last_mut().unwrap();
+
+        let segment = match &parent.key {
+            Some(key) => format!("{}[{}]", name, key),
+            None => {
+                let count = parent.name_counts.entry(name).or_insert(0);
+                let segment = match *count {
+                    0 => name.to_string(),
+                    count => format!("{}#{}", name, count),
+                };
+                *count += 1;
+                segment
+            }
+        };
+        let path: Rc<str> = if parent.path.is_empty() {
+            segment.into()
+        } else {
+            format!("{}/{}", parent.path, segment).into()
+        };
+
+        stack.push(PathFrame::new(path.clone()));
+        Some(path)
+    })
+}
+
+/// Stop building the children of the element passed to [`enter`].
+pub(crate) fn exit() {
+    PATH_STACK.with(|stack| {
+        if let Some(stack) = stack.borrow_mut().as_mut() {
+            stack.pop();
+        }
+    });
+}
+
+/// Run `build` between [`enter`] and [`exit`].
+pub(crate) fn in_element<R>(name: &'static str, build: impl FnOnce() -> R) -> R {
+    enter(name);
+    let output = build();
+    exit();
+    output
+}
+
+/// Returns the path of the element whose children are being built.
+///
+/// Returns `None` outside of [`with_paths`].
+#[cfg_attr(not(feature = "serde"), allow(dead_code))]
+pub(crate) fn current() -> Option<Rc<str>> {
+    PATH_STACK.with(|stack| {
+        let stack = stack.borrow();
+        Some(stack.as_ref()?.last()?.path.clone())
+    })
+}
+
+/// Set the key of the list item being built, in the frame of the list.
+pub(crate) fn set_key(key: &str) {
+    PATH_STACK.with(|stack| {
+        if let Some(stack) = stack.borrow_mut().as_mut() {
+            stack.last_mut().unwrap().key = Some(key.to_string());
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_env_log::test;
+
+    #[test]
+    fn number_siblings() {
+        let paths = with_paths(|| {
+            let root = enter("App");
+            let column = enter("Column");
+            let first = enter("Counter");
+            exit();
+            let second = enter("Counter");
+            assert_eq!(current(), second);
+            exit();
+            exit();
+            exit();
+            vec![root, column, first, second]
+        });
+        let paths: Vec<_> = paths.iter().map(|path| path.as_deref().unwrap()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "App",
+                "App/Column",
+                "App/Column/Counter",
+                "App/Column/Counter#1"
+            ]
+        );
+    }
+
+    #[test]
+    fn keyed_items() {
+        let paths = with_paths(|| {
+            in_element("List", || {
+                ["a", "b"]
+                    .iter()
+                    .map(|key| {
+                        set_key(key);
+                        let path = enter("Item");
+                        exit();
+                        path.unwrap().to_string()
+                    })
+                    .collect::<Vec<_>>()
+            })
+        });
+        assert_eq!(paths, vec!["List/Item[a]", "List/Item[b]"]);
+    }
+
+    #[test]
+    fn no_paths() {
+        assert_eq!(enter("App"), None);
+        exit();
+    }
+}
@@ -1,6 +1,10 @@
 use crate::ctx::{ProcessEventCtx, ReconcileCtx};
-use crate::glue::GlobalEventCx;
-use crate::metadata::Metadata;
+use crate::effects::Effects;
+use crate::elements::internals::AnyStateBox;
+use crate::glue::{DruidAppData, GlobalEventCx};
+use crate::inspector::{self, InspectNode};
+use crate::metadata::{Metadata, StateHandle};
+use crate::reducer::Reducer;
 use crate::widget_sequence::WidgetSequence;
 
 use std::fmt::Debug;
@@ -40,6 +44,22 @@ pub trait Element: Debug + Clone + 'static {
     fn get_component_state(_state: &Self::AggregateChildrenState) -> Option<&Self::ComponentState> {
         None
     }
+
+    fn get_component_state_mut(
+        _state: &mut Self::AggregateChildrenState,
+    ) -> Option<&mut Self::ComponentState> {
+        None
+    }
+
+    fn get_hook_states(_state: &Self::AggregateChildrenState) -> Option<&Vec<AnyStateBox>> {
+        None
+    }
+
+    fn get_hook_states_mut(
+        _state: &mut Self::AggregateChildrenState,
+    ) -> Option<&mut Vec<AnyStateBox>> {
+        None
+    }
 }
 
 // TODO - Include documentation about what a Virtual DOM is and where the name comes from.
@@ -89,9 +109,30 @@ pub trait VirtualDom: Debug {
         #![allow(unused_variables)]
         None
     }
+
+    /// Describe this element, its children and their widgets.
+    ///
+    /// See [`RootWidget::inspect`](crate::RootWidget::inspect). The default implementation
+    /// describes an element without children, and the widgets it created; elements with
+    /// children must override it.
+    fn inspect(
+        &self,
+        children_state: &Self::AggregateChildrenState,
+        widget_seq: &Self::TargetWidgetSeq,
+        data: &DruidAppData,
+    ) -> Vec<InspectNode> {
+        #![allow(unused_variables)]
+        vec![InspectNode::element(
+            &inspector::element_name::<Self>(),
+            inspector::inspect_widgets(widget_seq, data),
+        )]
+    }
 }
 
-use crate::elements::internals::{ParentEvent, WithBubbleEvent, WithCallbackEvent, WithMapEvent};
+use crate::elements::internals::{
+    BubbleEvent, DispatchEvent, MapEvent, OnEvent, OnEventWithEffects, OnStateEvent, OnStoreEvent,
+    ParentEvent, WithEvent,
+};
 
 /// Helper methods that can be called on all elements.
 pub trait ElementExt: Element + Sized {
@@ -100,6 +141,9 @@ pub trait ElementExt: Element + Sized {
     /// When an event is emitted that matches the EventParam type (TODO - see [`ParentEvent`] for
     /// details), call the given callback, with a mutable reference to the component's local state
     /// (see [`CompCtx::get_local_state`](crate::CompCtx::get_local_state)) and the event value.
+    ///
+    /// Handlers can be chained: the event is then passed on to handlers chained after this
+    /// one.
     fn on<
         EventParam,
         Cb: Fn(&mut ComponentState, EventParam) + Clone,
@@ -109,14 +153,117 @@ pub trait ElementExt: Element + Sized {
         self,
         md: Metadata<ComponentEvent, ComponentState>,
         callback: Cb,
-    ) -> WithCallbackEvent<ComponentEvent, ComponentState, EventParam, Self, Cb>
+    ) -> WithEvent<EventParam, Self, OnEvent<ComponentEvent, ComponentState, Cb>>
+    where
+        Self::Event: ParentEvent<EventParam> + Clone,
+    {
+        WithEvent {
+            element: self,
+            handler: OnEvent {
+                callback,
+                _metadata: md,
+            },
+            _marker: Default::default(),
+        }
+    }
+
+    /// Bind callback to an event, with a queue of side effects.
+    ///
+    /// Same as [`on`](Self::on), except the callback is also given an [`Effects`] queue, which
+    /// it can use to submit druid commands, request focus, close the window, etc. Effects are
+    /// executed after the GUI has been updated with the new local state.
+    fn on_with_effects<
+        EventParam,
+        Cb: Fn(&mut ComponentState, EventParam, &mut Effects) + Clone,
+        ComponentEvent,
+        ComponentState,
+    >(
+        self,
+        md: Metadata<ComponentEvent, ComponentState>,
+        callback: Cb,
+    ) -> WithEvent<EventParam, Self, OnEventWithEffects<ComponentEvent, ComponentState, Cb>>
+    where
+        Self::Event: ParentEvent<EventParam> + Clone,
+    {
+        WithEvent {
+            element: self,
+            handler: OnEventWithEffects {
+                callback,
+                _metadata: md,
+            },
+            _marker: Default::default(),
+        }
+    }
+
+    /// Bind callback to an event, with a state slot.
+    ///
+    /// Same as [`on`](Self::on), except the callback is given a mutable reference to the state
+    /// slot created by [`CompCtx::use_state`](crate::CompCtx::use_state) instead of the
+    /// component's local state.
+    fn on_state<EventParam, Cb: Fn(&mut State, EventParam) + Clone, State: 'static>(
+        self,
+        handle: StateHandle<State>,
+        callback: Cb,
+    ) -> WithEvent<EventParam, Self, OnStateEvent<State, Cb>>
+    where
+        Self::Event: ParentEvent<EventParam> + Clone,
+    {
+        WithEvent {
+            element: self,
+            handler: OnStateEvent { callback, handle },
+            _marker: Default::default(),
+        }
+    }
+
+    /// Bind callback to an event, with the app-wide store.
+    ///
+    /// Same as [`on`](Self::on), except the callback is given a mutable reference to the store
+    /// set by [`RootHandler::with_store`](crate::RootHandler::with_store) instead of the
+    /// component's local state. Components read the store with
+    /// [`CompCtx::use_selector`](crate::CompCtx::use_selector).
+    ///
+    /// The callback panics if the app doesn't have a store, or if the store isn't of type `Store`.
+    fn on_store<EventParam, Cb: Fn(&mut Store, EventParam) + Clone, Store: 'static>(
+        self,
+        callback: Cb,
+    ) -> WithEvent<EventParam, Self, OnStoreEvent<Store, Cb>>
+    where
+        Self::Event: ParentEvent<EventParam> + Clone,
+    {
+        WithEvent {
+            element: self,
+            handler: OnStoreEvent {
+                callback,
+                _marker: Default::default(),
+            },
+            _marker: Default::default(),
+        }
+    }
+
+    /// Turn events into actions applied to the component's local state.
+    ///
+    /// When an event is emitted that matches the EventParam type (TODO - see [`ParentEvent`] for
+    /// details), call the given function with the event value, and pass the returned action to
+    /// the [`reduce`](Reducer::reduce) method of the component's local state.
+    fn dispatch<
+        EventParam,
+        Cb: Fn(EventParam) -> ComponentState::Action + Clone,
+        ComponentEvent,
+        ComponentState: Reducer,
+    >(
+        self,
+        md: Metadata<ComponentEvent, ComponentState>,
+        callback: Cb,
+    ) -> WithEvent<EventParam, Self, DispatchEvent<ComponentEvent, ComponentState, Cb>>
     where
-        Self::Event: ParentEvent<EventParam>,
+        Self::Event: ParentEvent<EventParam> + Clone,
     {
-        WithCallbackEvent {
+        WithEvent {
             element: self,
-            callback,
-            _metadata: md,
+            handler: DispatchEvent {
+                callback,
+                _metadata: md,
+            },
             _marker: Default::default(),
         }
     }
@@ -138,15 +285,18 @@ pub trait ElementExt: Element + Sized {
         self,
         md: Metadata<ComponentEvent, ComponentState>,
         callback: Cb,
-    ) -> WithMapEvent<ComponentEvent, ComponentState, EventParam, EventReturn, Self, Cb>
+    ) -> WithEvent<EventParam, Self, MapEvent<ComponentEvent, ComponentState, EventReturn, Cb>>
     where
-        Self::Event: ParentEvent<EventParam>,
+        Self::Event: ParentEvent<EventParam> + Clone,
         ComponentEvent: ParentEvent<EventReturn>,
     {
-        WithMapEvent {
+        WithEvent {
             element: self,
-            callback,
-            _metadata: md,
+            handler: MapEvent {
+                callback,
+                _metadata: md,
+                _marker: Default::default(),
+            },
             _marker: Default::default(),
         }
     }
@@ -161,14 +311,14 @@ pub trait ElementExt: Element + Sized {
     fn bubble_up<Event, ComponentEvent, ComponentState>(
         self,
         md: Metadata<ComponentEvent, ComponentState>,
-    ) -> WithBubbleEvent<ComponentEvent, ComponentState, Event, Self>
+    ) -> WithEvent<Event, Self, BubbleEvent<ComponentEvent, ComponentState, Event>>
     where
-        Self::Event: ParentEvent<Event>,
+        Self::Event: ParentEvent<Event> + Clone,
         ComponentEvent: ParentEvent<Event>,
     {
-        WithBubbleEvent {
+        WithEvent {
             element: self,
-            _metadata: md,
+            handler: BubbleEvent::<ComponentEvent, ComponentState, Event>::new(md),
             _marker: Default::default(),
         }
     }
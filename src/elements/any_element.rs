@@ -1,6 +1,7 @@
 use crate::ctx::{ProcessEventCtx, ReconcileCtx};
 use crate::element_tree::{Element, VirtualDom};
-use crate::glue::GlobalEventCx;
+use crate::glue::{DruidAppData, GlobalEventCx};
+use crate::inspector::InspectNode;
 use crate::metadata::NoState;
 use crate::widgets::WidgetSeqBox;
 
@@ -23,7 +24,7 @@ pub trait AnyState: Any + Debug {
 
 impl<T> AnyState for T
 where
-    T: Clone + Default + Debug + PartialEq + 'static,
+    T: Clone + Debug + PartialEq + 'static,
 {
     fn as_any(&self) -> &dyn Any {
         self
@@ -56,6 +57,14 @@ impl AnyStateBox {
             value: Box::new(state),
         }
     }
+
+    pub fn downcast_ref<T: 'static>(&self) -> Option<&T> {
+        self.value.as_any().downcast_ref::<T>()
+    }
+
+    pub fn downcast_mut<T: 'static>(&mut self) -> Option<&mut T> {
+        self.value.as_mut_any().downcast_mut::<T>()
+    }
 }
 
 impl Clone for AnyStateBox {
@@ -255,6 +264,13 @@ pub trait AnyVirtualDom: Any + Debug {
         widget_seq: &mut WidgetSeqBox,
         cx: &mut GlobalEventCx,
     ) -> Option<Self::Event>;
+
+    fn inspect(
+        &self,
+        children_state: &Option<AnyStateBox>,
+        widget_seq: &WidgetSeqBox,
+        data: &DruidAppData,
+    ) -> Vec<InspectNode>;
 }
 
 impl<Child: VirtualDom + 'static> AnyVirtualDom for ErasedVirtualDom<Child> {
@@ -329,6 +345,27 @@ impl<Child: VirtualDom + 'static> AnyVirtualDom for ErasedVirtualDom<Child> {
         self.child
             .process_local_event(children_state, widget_seq, cx)
     }
+
+    fn inspect(
+        &self,
+        children_state: &Option<AnyStateBox>,
+        widget_seq: &WidgetSeqBox,
+        data: &DruidAppData,
+    ) -> Vec<InspectNode> {
+        let children_state = children_state
+            .as_ref()
+            .unwrap()
+            .value
+            .as_any()
+            .downcast_ref::<Child::AggregateChildrenState>()
+            .unwrap();
+        let widget_seq = widget_seq
+            .value
+            .as_any()
+            .downcast_ref::<Child::TargetWidgetSeq>()
+            .unwrap();
+        self.child.inspect(children_state, widget_seq, data)
+    }
 }
 
 // -
@@ -389,6 +426,15 @@ impl<Event: Debug> VirtualDom for VirtualDomBox<Event> {
         self.child
             .process_local_event(children_state, widget_seq, cx)
     }
+
+    fn inspect(
+        &self,
+        children_state: &Option<AnyStateBox>,
+        widget_seq: &WidgetSeqBox,
+        data: &DruidAppData,
+    ) -> Vec<InspectNode> {
+        self.child.inspect(children_state, widget_seq, data)
+    }
 }
 
 // --- TESTS ---
@@ -439,19 +485,15 @@ mod tests {
     #[test]
     fn boxed_button_press() {
         use crate::elements::event_logger::EventLogger;
-        use crate::glue::WidgetId;
         use std::sync::mpsc::channel;
 
         let (event_sender, event_receiver) = channel();
-        let button_id = WidgetId::reserved(1);
-        let button = EventLogger::new(
-            event_sender,
-            ElementBox::new(Button::new("Hello").with_reserved_id(button_id)),
-        );
+        let button = EventLogger::new(event_sender, ElementBox::new(Button::new("Hello")));
 
         Harness::run_test_window(button, |harness| {
             assert_debug_snapshot!(harness.get_root_debug_state());
 
+            let button_id = harness.widget_id_at("Button");
             harness.mouse_click_on(button_id);
 
             let click_event = event_receiver.try_recv();
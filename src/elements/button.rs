@@ -16,14 +16,12 @@ use tracing::{instrument, trace};
 pub struct Button {
     pub text: String,
     pub flex: FlexParams,
-    pub reserved_widget_id: Option<WidgetId>,
 }
 
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct ButtonData {
     pub text: String,
     pub flex: FlexParams,
-    pub reserved_widget_id: Option<WidgetId>,
 }
 
 /// Event emitted when a [Button] is clicked.
@@ -46,7 +44,6 @@ impl Button {
                 flex: None,
                 alignment: None,
             },
-            reserved_widget_id: None,
         }
     }
 
@@ -58,18 +55,6 @@ impl Button {
         }
     }
 
-    /// For unit tests only.
-    ///
-    /// The button created by this element always has the same id. If two widgets are created
-    /// with the same id (for instance, because the same button is returned twice because of
-    /// a copy-paste error), impredictable behavior may ensue.
-    pub fn with_reserved_id(self, widget_id: WidgetId) -> Self {
-        Button {
-            reserved_widget_id: Some(widget_id),
-            ..self
-        }
-    }
-
     /// Provide a closure to be called when this button is clicked.
     pub fn on_click<ComponentEvent: 'static, ComponentState: 'static>(
         self,
@@ -93,7 +78,6 @@ impl Element for Button {
             ButtonData {
                 text: self.text,
                 flex: self.flex,
-                reserved_widget_id: self.reserved_widget_id,
             },
             (),
         )
@@ -107,8 +91,7 @@ impl VirtualDom for ButtonData {
 
     #[instrument(name = "Button", skip(self))]
     fn init_tree(&self) -> ButtonWidget {
-        let id = self.reserved_widget_id.unwrap_or_else(WidgetId::next);
-        ButtonWidget::new(self.text.clone(), self.flex, id)
+        ButtonWidget::new(self.text.clone(), self.flex, WidgetId::next())
     }
 
     #[instrument(name = "Button", skip(self, _prev_value, _widget, _ctx))]
@@ -157,7 +140,6 @@ mod tests {
                     flex: None,
                     alignment: None,
                 },
-                ..Default::default()
             }
         );
     }
@@ -180,15 +162,12 @@ mod tests {
         use std::sync::mpsc::channel;
 
         let (event_sender, event_receiver) = channel();
-        let button_id = WidgetId::reserved(1);
-        let button = EventLogger::new(
-            event_sender,
-            Button::new("Hello").with_reserved_id(button_id),
-        );
+        let button = EventLogger::new(event_sender, Button::new("Hello"));
 
         Harness::run_test_window(button, |harness| {
             assert_debug_snapshot!(harness.get_root_debug_state());
 
+            let button_id = harness.widget_id_at("Button");
             harness.mouse_click_on(button_id);
 
             let click_event = event_receiver.try_recv();
@@ -21,7 +21,6 @@ pub struct Checkbox {
     pub text: String,
     pub value: bool,
     pub flex: FlexParams,
-    pub reserved_widget_id: Option<WidgetId>,
 }
 
 #[derive(Clone, Debug, Default, PartialEq)]
@@ -29,7 +28,6 @@ pub struct CheckboxData {
     pub text: String,
     pub value: bool,
     pub flex: FlexParams,
-    pub reserved_widget_id: Option<WidgetId>,
 }
 
 /// Event emitted when a [Checkbox] is clicked.
@@ -55,7 +53,6 @@ impl Checkbox {
                 flex: None,
                 alignment: None,
             },
-            reserved_widget_id: None,
         }
     }
 
@@ -67,18 +64,6 @@ impl Checkbox {
         }
     }
 
-    /// For unit tests only.
-    ///
-    /// The checkbox created by this element always has the same id. If two widgets are created
-    /// with the same id (for instance, because the same checkbox is returned twice because of
-    /// a copy-paste error), impredictable behavior may ensue.
-    pub fn with_reserved_id(self, widget_id: WidgetId) -> Self {
-        Checkbox {
-            reserved_widget_id: Some(widget_id),
-            ..self
-        }
-    }
-
     /// Provide a closure to be called when this checkbox is toggled.
     pub fn on_toggled<ComponentEvent: 'static, ComponentState: 'static>(
         self,
@@ -103,7 +88,6 @@ impl Element for Checkbox {
                 text: self.text,
                 value: self.value,
                 flex: self.flex,
-                reserved_widget_id: self.reserved_widget_id,
             },
             (),
         )
@@ -118,9 +102,8 @@ impl VirtualDom for CheckboxData {
 
     #[instrument(name = "Checkbox", skip(self))]
     fn init_tree(&self) -> SingleCheckboxWidget {
-        let id = self.reserved_widget_id.unwrap_or_else(WidgetId::next);
         SingleCheckboxWidget::new(
-            CheckboxWidget::new(self.text.clone(), self.value, id),
+            CheckboxWidget::new(self.text.clone(), self.value, WidgetId::next()),
             self.flex,
         )
     }
@@ -188,7 +171,6 @@ mod tests {
                     flex: None,
                     alignment: None,
                 },
-                ..Default::default()
             }
         );
     }
@@ -218,15 +200,12 @@ mod tests {
         use std::sync::mpsc::channel;
 
         let (event_sender, event_receiver) = channel();
-        let checkbox_id = WidgetId::reserved(1);
-        let checkbox = EventLogger::new(
-            event_sender,
-            Checkbox::new("Hello", false).with_reserved_id(checkbox_id),
-        );
+        let checkbox = EventLogger::new(event_sender, Checkbox::new("Hello", false));
 
         Harness::run_test_window(checkbox, |harness| {
             assert_debug_snapshot!(harness.get_root_debug_state());
 
+            let checkbox_id = harness.widget_id_at("Checkbox");
             harness.mouse_click_on(checkbox_id);
 
             let click_event = event_receiver.try_recv();
@@ -1,6 +1,8 @@
 use crate::ctx::ReconcileCtx;
+use crate::element_path;
 use crate::element_tree::{Element, ElementExt, VirtualDom};
-use crate::glue::{Action, GlobalEventCx, WidgetId};
+use crate::glue::{Action, DruidAppData, GlobalEventCx, WidgetId};
+use crate::inspector::InspectNode;
 use crate::metadata::{Metadata, NoState};
 use crate::widgets::ClickableWidget;
 
@@ -10,13 +12,11 @@ use tracing::{instrument, trace};
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct Clickable<Child: Element> {
     pub child: Child,
-    pub reserved_widget_id: Option<WidgetId>,
 }
 
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct ClickableData<Child: VirtualDom> {
     pub child: Child,
-    pub reserved_widget_id: Option<WidgetId>,
 }
 
 /// Event emitted when a [Clickable] is clicked.
@@ -30,22 +30,7 @@ pub struct ClickEvent;
 
 impl<Child: Element> Clickable<Child> {
     pub fn new(child: Child) -> Self {
-        Clickable {
-            child,
-            reserved_widget_id: None,
-        }
-    }
-
-    /// For unit tests only.
-    ///
-    /// The widget created by this element always has the same id. If two widgets are created
-    /// with the same id (for instance, because the same widget is returned twice because of
-    /// a copy-paste error), impredictable behavior may ensue.
-    pub fn with_reserved_id(self, widget_id: WidgetId) -> Self {
-        Clickable {
-            reserved_widget_id: Some(widget_id),
-            ..self
-        }
+        Clickable { child }
     }
 
     /// Provide a closure to be called when the child widget is clicked.
@@ -73,14 +58,9 @@ impl<Child: Element> Element for Clickable<Child> {
         ClickableData<Child::BuildOutput>,
         Self::AggregateChildrenState,
     ) {
-        let (element, child_state) = self.child.build(prev_state);
-        (
-            ClickableData {
-                child: element,
-                reserved_widget_id: self.reserved_widget_id,
-            },
-            child_state,
-        )
+        let (element, child_state) =
+            element_path::in_element("Clickable", || self.child.build(prev_state));
+        (ClickableData { child: element }, child_state)
     }
 }
 
@@ -91,8 +71,7 @@ impl<Child: VirtualDom> VirtualDom for ClickableData<Child> {
 
     #[instrument(name = "Clickable", skip(self))]
     fn init_tree(&self) -> Self::TargetWidgetSeq {
-        let id = self.reserved_widget_id.unwrap_or_else(WidgetId::next);
-        ClickableWidget::new(self.child.init_tree(), id)
+        ClickableWidget::new(self.child.init_tree(), WidgetId::next())
     }
 
     #[instrument(name = "Clickable", skip(self, prev_value, widget_seq, ctx))]
@@ -122,6 +101,21 @@ impl<Child: VirtualDom> VirtualDom for ClickableData<Child> {
             None
         }
     }
+
+    fn inspect(
+        &self,
+        children_state: &Self::AggregateChildrenState,
+        widget_seq: &Self::TargetWidgetSeq,
+        data: &DruidAppData,
+    ) -> Vec<InspectNode> {
+        let children = self
+            .child
+            .inspect(children_state, widget_seq.children(), data);
+        vec![InspectNode::element(
+            "Clickable",
+            vec![InspectNode::widget(widget_seq, data, Some(children))],
+        )]
+    }
 }
 
 #[cfg(test)]
@@ -161,16 +155,13 @@ mod tests {
         use std::sync::mpsc::channel;
 
         let (event_sender, event_receiver) = channel();
-        let clickable_id = WidgetId::reserved(1);
-        let clickable = EventLogger::new(
-            event_sender,
-            Clickable::new(Label::new("Hello")).with_reserved_id(clickable_id),
-        );
+        let clickable = EventLogger::new(event_sender, Clickable::new(Label::new("Hello")));
 
         Harness::run_test_window(clickable, |harness| {
             // FIXME
             // assert_debug_snapshot!(harness.get_root_debug_state());
 
+            let clickable_id = harness.widget_id_at("Clickable");
             harness.mouse_click_on(clickable_id);
 
             let click_event = event_receiver.try_recv();
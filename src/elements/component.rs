@@ -1,11 +1,21 @@
 use crate::ctx::{CompCtx, ProcessEventCtx, ReconcileCtx};
+use crate::element_path;
 use crate::element_tree::{Element, VirtualDom};
+use crate::elements::internals::AnyStateBox;
 use crate::elements::ElementBox;
-use crate::glue::GlobalEventCx;
+use crate::glue::{DruidAppData, GlobalEventCx};
+use crate::inspector::InspectNode;
 use crate::metadata::{Metadata, NoState};
+use crate::profiler;
+use crate::state_diff::{self, InstanceState};
+use crate::store;
 
 use derivative::Derivative;
+use std::any::Any;
+use std::cell::OnceCell;
 use std::fmt::Debug;
+use std::rc::Rc;
+use tracing::instrument;
 
 /// Trait that all components implement.
 ///
@@ -31,6 +41,28 @@ pub struct ComponentHolder<
     _marker: std::marker::PhantomData<Comp>,
 }
 
+/// Output of [`ComponentHolder::call_component`].
+type ComponentCall<ReturnedTree> = (
+    ReturnedTree,
+    usize,
+    Vec<(usize, AnyStateBox)>,
+    Option<Box<dyn Any>>,
+);
+
+/// The virtual DOM of a component.
+///
+/// Used to profile and inspect components; otherwise, it's a transparent wrapper around the
+/// virtual DOM of the element returned by the component.
+pub struct ComponentHolderData<Comp: Component, ReturnedTree: Element> {
+    pub child: ReturnedTree::BuildOutput,
+    pub props: Comp::Props,
+    /// Path of the component instance, if the app is being profiled.
+    profiled_instance: Option<Rc<str>>,
+    /// State of the component instance after the build, if state changes are logged.
+    logged_state: Option<InstanceState<ReturnedTree::ComponentState>>,
+    _marker: std::marker::PhantomData<Comp>,
+}
+
 /// The root of the element hierarchy returned by a component.
 ///
 /// Any component which uses metadata (so components with local state, or components that emit
@@ -57,6 +89,15 @@ pub struct ComponentOutput<
     pub _metadata: Metadata<ComponentEvent, ComponentState>,
 }
 
+/// Marker trait implemented by [`ComponentOutput`].
+///
+/// The [`#[component]`](crate::component) macro requires the return type of components which
+/// call [`CompCtx::use_metadata`](crate::CompCtx::use_metadata) or
+/// [`CompCtx::use_state`](crate::CompCtx::use_state) to implement this trait. That way,
+/// forgetting to wrap the returned element in a `ComponentOutput` is a compile error instead of
+/// a runtime panic.
+pub trait IsComponentOutput {}
+
 #[derive(Derivative, Hash)]
 #[derivative(
     Debug(bound = ""),
@@ -105,6 +146,69 @@ impl<
             _marker: Default::default(),
         }
     }
+
+    /// Call the component function with the local state and state slots in `prev_state`.
+    ///
+    /// Returns the element tree, the number of state slots used, the slots created, and the
+    /// local state restored from disk, if any.
+    fn call_component(
+        &self,
+        prev_state: &ReturnedTree::AggregateChildrenState,
+        props: Comp::Props,
+    ) -> ComponentCall<ReturnedTree> {
+        let default_state = Default::default();
+        let local_state = ReturnedTree::get_component_state(prev_state).unwrap_or(&default_state);
+        let hook_states = ReturnedTree::get_hook_states(prev_state)
+            .map(Vec::as_slice)
+            .unwrap_or(&[]);
+        let restored_state = OnceCell::new();
+
+        let ctx = CompCtx {
+            called_use_metadata: std::cell::Cell::new(false),
+            local_state,
+            hook_states,
+            hook_count: std::cell::Cell::new(0),
+            new_hook_states: Default::default(),
+            restored_state: &restored_state,
+        };
+        let element_tree = (self.component_fn)(&ctx, props);
+
+        // An empty list of hooks means this is the first time the component is called.
+        let hook_count = ctx.hook_count.get();
+        if cfg!(debug_assertions) && !hook_states.is_empty() && hook_count != hook_states.len() {
+            panic!(
+                "error: hook order changed between renders: component {} called 'use_state' {} times, previously called {} times",
+                Comp::name(),
+                hook_count,
+                hook_states.len(),
+            )
+        }
+
+        let new_hook_states = ctx.new_hook_states.into_inner();
+        (
+            element_tree,
+            hook_count,
+            new_hook_states,
+            restored_state.into_inner(),
+        )
+    }
+
+    /// Record the state of the instance at `path`, if state changes are logged.
+    fn logged_state(
+        path: Option<Rc<str>>,
+        state: &ReturnedTree::AggregateChildrenState,
+    ) -> Option<InstanceState<ReturnedTree::ComponentState>> {
+        if !state_diff::is_enabled() {
+            return None;
+        }
+        Some(InstanceState::new(
+            path?,
+            ReturnedTree::get_component_state(state).cloned(),
+            ReturnedTree::get_hook_states(state)
+                .cloned()
+                .unwrap_or_default(),
+        ))
+    }
 }
 
 impl<
@@ -132,26 +236,197 @@ impl<
     type Event = Comp::LocalEvent;
     type ComponentState = NoState;
     type AggregateChildrenState = ReturnedTree::AggregateChildrenState;
-    type BuildOutput = ReturnedTree::BuildOutput;
+    type BuildOutput = ComponentHolderData<Comp, ReturnedTree>;
 
-    // TODO - add spans
+    #[instrument(name = "Component", skip(self, prev_state), fields(name = Comp::name()))]
     fn build(
         self,
         prev_state: Self::AggregateChildrenState,
     ) -> (Self::BuildOutput, Self::AggregateChildrenState) {
-        let default_state = Default::default();
-        let local_state = ReturnedTree::get_component_state(&prev_state).unwrap_or(&default_state);
+        let path = element_path::enter(Comp::name());
+
+        // If only the store changed, and not the slices this instance selected, the component
+        // would return the same element tree.
+        let reused_tree = path.as_ref().and_then(|path| {
+            store::reuse_output(path, |(props, _): &(Comp::Props, ReturnedTree)| {
+                *props == self.props
+            })
+        });
+        if let Some((props, element_tree)) = reused_tree {
+            let (child, state) = element_tree.build(prev_state);
+            let logged_state = Self::logged_state(path, &state);
+            element_path::exit();
+            return (
+                ComponentHolderData {
+                    child,
+                    props,
+                    profiled_instance: None,
+                    logged_state,
+                    _marker: Default::default(),
+                },
+                state,
+            );
+        }
 
-        let ctx = CompCtx {
-            called_use_metadata: std::cell::Cell::new(false),
-            local_state: local_state,
+        let mut prev_state = prev_state;
+        let props = self.props.clone();
+
+        let profiled_instance = path.as_ref().and_then(|path| {
+            let default_state = Default::default();
+            let local_state =
+                ReturnedTree::get_component_state(&prev_state).unwrap_or(&default_state);
+            let hook_states = ReturnedTree::get_hook_states(&prev_state)
+                .map(Vec::as_slice)
+                .unwrap_or(&[]);
+            profiler::enter_build(Comp::name(), path, &self.props, &(local_state, hook_states))
+        });
+
+        let (element_tree, hook_count, new_hook_states, restored_state) =
+            self.call_component(&prev_state, props.clone());
+
+        // The component was called with the local state saved to disk, which replaces the
+        // previous one.
+        if let Some(restored_state) = restored_state {
+            let local_state = ReturnedTree::get_component_state_mut(&mut prev_state);
+            let restored_state = restored_state.downcast::<ReturnedTree::ComponentState>();
+            if let (Some(local_state), Ok(restored_state)) = (local_state, restored_state) {
+                *local_state = *restored_state;
+            }
+        }
+        store::record_output(path.as_ref(), || (props.clone(), element_tree.clone()));
+
+        if let Some(hook_states) = ReturnedTree::get_hook_states_mut(&mut prev_state) {
+            hook_states.truncate(hook_count);
+            for (index, state) in new_hook_states {
+                if index < hook_states.len() {
+                    hook_states[index] = state;
+                } else {
+                    hook_states.push(state);
+                }
+            }
+        }
+
+        let (child, state) = store::build_called_output(|| element_tree.build(prev_state));
+
+        if let Some(instance) = &profiled_instance {
+            profiler::exit_build(instance);
+        }
+        if let Some(path) = &path {
+            profiler::count_build(path);
+        }
+        let logged_state = Self::logged_state(path, &state);
+        element_path::exit();
+
+        (
+            ComponentHolderData {
+                child,
+                props,
+                profiled_instance,
+                logged_state,
+                _marker: Default::default(),
+            },
+            state,
+        )
+    }
+}
+
+impl<Comp: Component, ReturnedTree: Element> Debug for ComponentHolderData<Comp, ReturnedTree> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.child.fmt(f)
+    }
+}
+
+impl<Comp: Component, ReturnedTree: Element> VirtualDom
+    for ComponentHolderData<Comp, ReturnedTree>
+{
+    type Event = ReturnedTree::Event;
+    type AggregateChildrenState = ReturnedTree::AggregateChildrenState;
+    type TargetWidgetSeq = <ReturnedTree::BuildOutput as VirtualDom>::TargetWidgetSeq;
+
+    #[instrument(name = "Component", skip(self), fields(name = Comp::name()))]
+    fn init_tree(&self) -> Self::TargetWidgetSeq {
+        self.child.init_tree()
+    }
+
+    #[instrument(
+        name = "Component",
+        skip(self, prev_value, widget_seq, ctx),
+        fields(name = Comp::name())
+    )]
+    fn reconcile(
+        &self,
+        prev_value: &Self,
+        widget_seq: &mut Self::TargetWidgetSeq,
+        ctx: &mut ReconcileCtx,
+    ) {
+        if let (Some(prev_state), Some(state)) = (&prev_value.logged_state, &self.logged_state) {
+            state_diff::log_changes(prev_state, state);
+        }
+
+        let instance = match &self.profiled_instance {
+            Some(instance) if profiler::enter_reconcile() => instance,
+            _ => return self.child.reconcile(&prev_value.child, widget_seq, ctx),
         };
-        let element_tree = (self.component_fn)(&ctx, self.props);
 
-        element_tree.build(prev_state)
+        self.child.reconcile(&prev_value.child, widget_seq, ctx);
+        profiler::exit_reconcile(instance);
+    }
+
+    fn process_event(
+        &self,
+        comp_ctx: &mut ProcessEventCtx,
+        children_state: &mut Self::AggregateChildrenState,
+        widget_seq: &mut Self::TargetWidgetSeq,
+        cx: &mut GlobalEventCx,
+    ) {
+        self.child
+            .process_event(comp_ctx, children_state, widget_seq, cx)
+    }
+
+    fn process_local_event(
+        &self,
+        children_state: &mut Self::AggregateChildrenState,
+        widget_seq: &mut Self::TargetWidgetSeq,
+        cx: &mut GlobalEventCx,
+    ) -> Option<Self::Event> {
+        self.child
+            .process_local_event(children_state, widget_seq, cx)
+    }
+
+    fn inspect(
+        &self,
+        children_state: &Self::AggregateChildrenState,
+        widget_seq: &Self::TargetWidgetSeq,
+        data: &DruidAppData,
+    ) -> Vec<InspectNode> {
+        let local_state = ReturnedTree::get_component_state(children_state);
+        let hook_states = ReturnedTree::get_hook_states(children_state)
+            .map(|hook_states| {
+                hook_states
+                    .iter()
+                    .map(|hook_state| format!("{:?}", hook_state))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        vec![InspectNode::component(
+            Comp::name(),
+            &self.props,
+            local_state.map(|state| state as &dyn Debug),
+            hook_states,
+            self.child.inspect(children_state, widget_seq, data),
+        )]
     }
 }
 
+impl<
+        ComponentEvent: Clone + Debug + PartialEq,
+        ComponentState: Clone + Default + Debug + PartialEq,
+        Child: Element,
+    > IsComponentOutput for ComponentOutput<ComponentEvent, ComponentState, Child>
+{
+}
+
 /// ---
 
 impl<
@@ -168,6 +443,7 @@ impl<
         Vec<ComponentEvent>,
         ComponentState,
         Child::AggregateChildrenState,
+        Vec<AnyStateBox>,
     );
     type BuildOutput = ComponentOutputData<ComponentEvent, ComponentState, Child::BuildOutput>;
 
@@ -175,20 +451,36 @@ impl<
         self,
         prev_state: Self::AggregateChildrenState,
     ) -> (Self::BuildOutput, Self::AggregateChildrenState) {
-        let (_, prev_local_state, children_prev_state) = prev_state;
+        let (_, prev_local_state, children_prev_state, hook_states) = prev_state;
         let (child, children_state) = self.child.build(children_prev_state);
         (
             ComponentOutputData {
                 child,
                 _metadata: Default::default(),
             },
-            (vec![], prev_local_state, children_state),
+            (vec![], prev_local_state, children_state, hook_states),
         )
     }
 
     fn get_component_state(state: &Self::AggregateChildrenState) -> Option<&Self::ComponentState> {
         Some(&state.1)
     }
+
+    fn get_component_state_mut(
+        state: &mut Self::AggregateChildrenState,
+    ) -> Option<&mut Self::ComponentState> {
+        Some(&mut state.1)
+    }
+
+    fn get_hook_states(state: &Self::AggregateChildrenState) -> Option<&Vec<AnyStateBox>> {
+        Some(&state.3)
+    }
+
+    fn get_hook_states_mut(
+        state: &mut Self::AggregateChildrenState,
+    ) -> Option<&mut Vec<AnyStateBox>> {
+        Some(&mut state.3)
+    }
 }
 
 impl<
@@ -202,10 +494,10 @@ impl<
         Vec<ComponentEvent>,
         ComponentState,
         Child::AggregateChildrenState,
+        Vec<AnyStateBox>,
     );
     type TargetWidgetSeq = Child::TargetWidgetSeq;
 
-    // TODO - add spans
     fn init_tree(&self) -> Child::TargetWidgetSeq {
         self.child.init_tree()
     }
@@ -240,10 +532,20 @@ impl<
         let mut ctx = ProcessEventCtx {
             event_queue: &mut children_state.0,
             state: &mut children_state.1,
+            hook_states: &mut children_state.3,
         };
         self.child
             .process_event(&mut ctx, &mut children_state.2, widget_seq, cx)
     }
+
+    fn inspect(
+        &self,
+        children_state: &Self::AggregateChildrenState,
+        widget_seq: &Self::TargetWidgetSeq,
+        data: &DruidAppData,
+    ) -> Vec<InspectNode> {
+        self.child.inspect(&children_state.2, widget_seq, data)
+    }
 }
 
 #[cfg(test)]
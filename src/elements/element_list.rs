@@ -1,14 +1,17 @@
 use crate::ctx::{ProcessEventCtx, ReconcileCtx};
+use crate::element_path;
 use crate::element_tree::{Element, VirtualDom};
 use crate::elements::compute_diff::compute_diff;
-use crate::glue::GlobalEventCx;
+use crate::glue::{DruidAppData, GlobalEventCx};
+use crate::inspector::InspectNode;
 use crate::metadata::{NoEvent, NoState};
+use crate::widget_sequence::WidgetSequence;
 use crate::widgets::WidgetList;
 
 use derivative::Derivative;
 use either::{Left, Right};
 use std::collections::VecDeque;
-use tracing::{debug_span, info, instrument};
+use tracing::{debug_span, error, info, instrument};
 
 // TODO - Add arbitrary index types
 
@@ -118,15 +121,18 @@ impl<Child: Element> Element for ElementList<Child> {
             index_diff -= mutation_item.removed_count as isize;
         }
 
+        element_path::enter("List");
         let (children, new_state): (Vec<_>, Vec<_>) = self
             .children
             .into_iter()
             .zip(prev_state_or_default)
             .map(|((key, item), (_key, item_prev_state))| {
+                element_path::set_key(&key);
                 let (new_item, new_state) = item.build(item_prev_state);
                 ((key.clone(), new_item), (key, new_state))
             })
             .unzip();
+        element_path::exit();
 
         (
             ElementListData {
@@ -254,6 +260,54 @@ impl<Child: VirtualDom> VirtualDom for ElementListData<Child> {
             child.process_event(comp_ctx, &mut child_comp_state.1, child_widget_seq, cx);
         }
     }
+
+    fn inspect(
+        &self,
+        children_state: &Self::AggregateChildrenState,
+        widget_seq: &Self::TargetWidgetSeq,
+        data: &DruidAppData,
+    ) -> Vec<InspectNode> {
+        let mut items = Vec::new();
+        for child_data in self
+            .children
+            .iter()
+            .zip(children_state)
+            .zip(widget_seq.children.iter())
+        {
+            let (key, child) = child_data.0 .0;
+            let child_comp_state = child_data.0 .1;
+            let child_widget_seq = child_data.1;
+            items.extend(
+                child
+                    .inspect(&child_comp_state.1, child_widget_seq, data)
+                    .into_iter()
+                    .map(|node| node.with_key(key)),
+            );
+        }
+
+        // Items are zipped above, so a desync would otherwise be silently ignored.
+        if self.children.len() != children_state.len()
+            || self.children.len() != widget_seq.children.len()
+        {
+            let message = format!(
+                "list has {} items, {} states and {} widgets",
+                self.children.len(),
+                children_state.len(),
+                widget_seq.children.len()
+            );
+            error!("Cannot inspect list: {}", message);
+
+            let orphaned_widgets = widget_seq
+                .children
+                .iter()
+                .skip(self.children.len().min(children_state.len()))
+                .flat_map(|child_widget_seq| child_widget_seq.widgets())
+                .map(|widget| InspectNode::widget(widget, data, None))
+                .collect();
+            items.push(InspectNode::error(message, orphaned_widgets));
+        }
+        vec![InspectNode::element("List", items)]
+    }
 }
 
 #[cfg(test)]
@@ -1,6 +1,7 @@
 use crate::ctx::{ProcessEventCtx, ReconcileCtx};
 use crate::element_tree::{Element, VirtualDom};
-use crate::glue::GlobalEventCx;
+use crate::glue::{DruidAppData, GlobalEventCx};
+use crate::inspector::InspectNode;
 use crate::metadata::{NoEvent, NoState};
 
 use either::{Either, Left, Right};
@@ -83,6 +84,22 @@ impl<Child: VirtualDom> VirtualDom for Option<Child> {
             );
         }
     }
+
+    fn inspect(
+        &self,
+        children_state: &Self::AggregateChildrenState,
+        widget_seq: &Self::TargetWidgetSeq,
+        data: &DruidAppData,
+    ) -> Vec<InspectNode> {
+        match self {
+            Some(child) => child.inspect(
+                children_state.as_ref().unwrap_or_log(),
+                widget_seq.as_ref().unwrap_or_log(),
+                data,
+            ),
+            None => Vec::new(),
+        }
+    }
 }
 
 // ----
@@ -199,6 +216,27 @@ impl<ChildLeft: VirtualDom, ChildRight: VirtualDom> VirtualDom for Either<ChildL
             ),
         }
     }
+
+    fn inspect(
+        &self,
+        children_state: &Self::AggregateChildrenState,
+        widget_seq: &Self::TargetWidgetSeq,
+        data: &DruidAppData,
+    ) -> Vec<InspectNode> {
+        let children_state = children_state.as_ref().unwrap_or_log();
+        match self {
+            Left(child) => child.inspect(
+                children_state.as_ref().left().unwrap_or_log(),
+                widget_seq.as_ref().left().unwrap_or_log(),
+                data,
+            ),
+            Right(child) => child.inspect(
+                children_state.as_ref().right().unwrap_or_log(),
+                widget_seq.as_ref().right().unwrap_or_log(),
+                data,
+            ),
+        }
+    }
 }
 
 #[cfg(test)]
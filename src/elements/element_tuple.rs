@@ -8,7 +8,8 @@
 use crate::ctx::{ProcessEventCtx, ReconcileCtx};
 use crate::element_tree::{Element, VirtualDom};
 use crate::elements::internals::EmptyElementData;
-use crate::glue::GlobalEventCx;
+use crate::glue::{DruidAppData, GlobalEventCx};
+use crate::inspector::InspectNode;
 use crate::metadata::{NoEvent, NoState};
 use crate::widgets::WidgetTuple;
 
@@ -369,6 +370,30 @@ impl<
         self.10.process_event(comp_ctx, &mut children_state.10, &mut widget_seq.10, cx);
         self.11.process_event(comp_ctx, &mut children_state.11, &mut widget_seq.11, cx);
     }
+
+    // Tuples are transparent; their children are described as children of the parent.
+    #[rustfmt::skip]
+    fn inspect(
+        &self,
+        children_state: &Self::AggregateChildrenState,
+        widget_seq: &Self::TargetWidgetSeq,
+        data: &DruidAppData,
+    ) -> Vec<InspectNode> {
+        let mut nodes = Vec::new();
+        nodes.append(&mut self.0.inspect(&children_state.0, &widget_seq.0, data));
+        nodes.append(&mut self.1.inspect(&children_state.1, &widget_seq.1, data));
+        nodes.append(&mut self.2.inspect(&children_state.2, &widget_seq.2, data));
+        nodes.append(&mut self.3.inspect(&children_state.3, &widget_seq.3, data));
+        nodes.append(&mut self.4.inspect(&children_state.4, &widget_seq.4, data));
+        nodes.append(&mut self.5.inspect(&children_state.5, &widget_seq.5, data));
+        nodes.append(&mut self.6.inspect(&children_state.6, &widget_seq.6, data));
+        nodes.append(&mut self.7.inspect(&children_state.7, &widget_seq.7, data));
+        nodes.append(&mut self.8.inspect(&children_state.8, &widget_seq.8, data));
+        nodes.append(&mut self.9.inspect(&children_state.9, &widget_seq.9, data));
+        nodes.append(&mut self.10.inspect(&children_state.10, &widget_seq.10, data));
+        nodes.append(&mut self.11.inspect(&children_state.11, &widget_seq.11, data));
+        nodes
+    }
 }
 
 #[cfg(test)]
@@ -1,5 +1,7 @@
 use crate::ctx::ReconcileCtx;
 use crate::element_tree::{Element, VirtualDom};
+use crate::glue::DruidAppData;
+use crate::inspector::InspectNode;
 use crate::metadata::{NoEvent, NoState};
 use crate::widgets::EmptySequence;
 
@@ -66,6 +68,15 @@ impl VirtualDom for EmptyElementData {
         _ctx: &mut ReconcileCtx,
     ) {
     }
+
+    fn inspect(
+        &self,
+        _state: &(),
+        _widget_seq: &EmptySequence,
+        _data: &DruidAppData,
+    ) -> Vec<InspectNode> {
+        Vec::new()
+    }
 }
 
 #[cfg(test)]
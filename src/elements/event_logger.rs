@@ -2,7 +2,8 @@ use crate::ctx::{ProcessEventCtx, ReconcileCtx};
 use crate::element_tree::{Element, VirtualDom};
 use crate::metadata::{NoEvent, NoState};
 
-use crate::glue::GlobalEventCx;
+use crate::glue::{DruidAppData, GlobalEventCx};
+use crate::inspector::InspectNode;
 
 use derivative::Derivative;
 use std::sync::mpsc::Sender;
@@ -99,4 +100,13 @@ impl<Child: VirtualDom> VirtualDom for EventLoggerData<Child> {
             let _ = self.event_queue.send(local_event);
         }
     }
+
+    fn inspect(
+        &self,
+        children_state: &Child::AggregateChildrenState,
+        widget_seq: &Self::TargetWidgetSeq,
+        data: &DruidAppData,
+    ) -> Vec<InspectNode> {
+        self.child.inspect(children_state, widget_seq, data)
+    }
 }
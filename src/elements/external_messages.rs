@@ -0,0 +1,118 @@
+use crate::ctx::ReconcileCtx;
+use crate::element_tree::{Element, VirtualDom};
+use crate::glue::GlobalEventCx;
+use crate::metadata::NoState;
+use crate::widgets::EmptySequence;
+
+use derivative::Derivative;
+use std::fmt::Debug;
+use tracing::{instrument, trace};
+
+/// Receives messages sent from other threads.
+///
+/// Messages are sent with an [`ExternalHandle`](crate::ExternalHandle), which you get from
+/// [`RootHandler::external_handle`](crate::RootHandler::external_handle). Like
+/// [`EmptyElement`](crate::elements::EmptyElement), this element doesn't create any widget.
+///
+/// If several ExternalMessages elements receive the same message type, only the first one
+/// (in tree order) gets each message.
+///
+/// ## Events
+///
+/// Emits the received messages.
+///
+/// ## Example
+///
+/// ```rust
+/// # use panoramix::{component, CompCtx, Element, ElementExt, NoEvent};
+/// # use panoramix::elements::{ComponentOutput, ExternalMessages, Label};
+/// # use panoramix::Column;
+/// #[derive(Debug, Clone, PartialEq)]
+/// struct JobDone(u32);
+///
+/// #[component]
+/// fn JobCounter(ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
+///     let md = ctx.use_metadata::<NoEvent, u32>();
+///     let done_jobs = ctx.get_local_state(md);
+///     ComponentOutput::new(
+///         md,
+///         Column!(
+///             Label::new(format!("Done jobs: {}", done_jobs)),
+///             ExternalMessages::<JobDone>::new()
+///                 .on(md, |done_jobs, _: JobDone| *done_jobs += 1),
+///         ),
+///     )
+/// }
+/// ```
+#[derive(Derivative)]
+#[derivative(Clone(bound = ""), Debug(bound = ""), Default(bound = ""))]
+pub struct ExternalMessages<Message> {
+    #[derivative(Debug = "ignore")]
+    _marker: std::marker::PhantomData<fn() -> Message>,
+}
+
+#[derive(Derivative)]
+#[derivative(Clone(bound = ""), Debug(bound = ""), Default(bound = ""))]
+pub struct ExternalMessagesData<Message> {
+    #[derivative(Debug = "ignore")]
+    _marker: std::marker::PhantomData<fn() -> Message>,
+}
+
+//
+// --- IMPLS
+
+impl<Message> ExternalMessages<Message> {
+    /// Build an element receiving messages of type `Message`.
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+impl<Message: Clone + Debug + Send + 'static> Element for ExternalMessages<Message> {
+    type Event = Message;
+    type ComponentState = NoState;
+    type AggregateChildrenState = ();
+    type BuildOutput = ExternalMessagesData<Message>;
+
+    #[instrument(name = "ExternalMessages", skip(self, _prev_state))]
+    fn build(self, _prev_state: ()) -> (ExternalMessagesData<Message>, ()) {
+        (Default::default(), ())
+    }
+}
+
+impl<Message: Clone + Debug + Send + 'static> VirtualDom for ExternalMessagesData<Message> {
+    type Event = Message;
+    type AggregateChildrenState = ();
+    type TargetWidgetSeq = EmptySequence;
+
+    fn init_tree(&self) -> EmptySequence {
+        EmptySequence
+    }
+
+    fn reconcile(
+        &self,
+        _prev_value: &Self,
+        _widget_seq: &mut EmptySequence,
+        _ctx: &mut ReconcileCtx,
+    ) {
+    }
+
+    #[instrument(name = "ExternalMessages", skip(self, _children_state, _widget_seq, cx))]
+    fn process_local_event(
+        &self,
+        _children_state: &mut (),
+        _widget_seq: &mut EmptySequence,
+        cx: &mut GlobalEventCx,
+    ) -> Option<Message> {
+        if !cx
+            .external_message
+            .as_ref()
+            .map_or(false, |message| message.is::<Message>())
+        {
+            return None;
+        }
+        let message = cx.external_message.take()?.downcast::<Message>().ok()?;
+        trace!("Received external message");
+        Some(*message)
+    }
+}
@@ -1,9 +1,11 @@
 use crate::ctx::{ProcessEventCtx, ReconcileCtx};
+use crate::element_path;
 use crate::element_tree::{Element, VirtualDom};
 use crate::flex::{
     Axis, ContainerStyle, CrossAxisAlignment, FlexContainerParams, FlexParams, MainAxisAlignment,
 };
-use crate::glue::GlobalEventCx;
+use crate::glue::{DruidAppData, GlobalEventCx};
+use crate::inspector::InspectNode;
 use crate::metadata::{NoEvent, NoState};
 use crate::widgets::{Container, FlexWidget, SingleWidget};
 
@@ -107,7 +109,12 @@ impl<Child: Element> Element for Flex<Child> {
         self,
         prev_state: Self::AggregateChildrenState,
     ) -> (Self::BuildOutput, Self::AggregateChildrenState) {
-        let (element, children_state) = self.child.build(prev_state);
+        let name = match self.axis {
+            Axis::Horizontal => "Row",
+            Axis::Vertical => "Column",
+        };
+        let (element, children_state) =
+            element_path::in_element(name, || self.child.build(prev_state));
         (
             FlexData::new(
                 self.axis,
@@ -178,6 +185,27 @@ impl<Child: VirtualDom> VirtualDom for FlexData<Child> {
             cx,
         )
     }
+
+    fn inspect(
+        &self,
+        children_state: &Child::AggregateChildrenState,
+        widget_seq: &Self::TargetWidgetSeq,
+        data: &DruidAppData,
+    ) -> Vec<InspectNode> {
+        let name = match self.axis {
+            Axis::Horizontal => "Row",
+            Axis::Vertical => "Column",
+        };
+        let children = self.child.inspect(
+            children_state,
+            &widget_seq.pod.widget().child().children_seq,
+            data,
+        );
+        vec![InspectNode::element(
+            name,
+            vec![InspectNode::widget(widget_seq, data, Some(children))],
+        )]
+    }
 }
 
 // ----
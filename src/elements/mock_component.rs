@@ -10,13 +10,10 @@ use tracing::trace;
 ///
 /// Used to test element identity, and local state stability.
 #[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
-pub struct MockComponent {
-    reserved_widget_id: Option<WidgetId>,
-}
+pub struct MockComponent;
 
 #[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct MockComponentData {
-    reserved_widget_id: Option<WidgetId>,
     clicks: u32,
 }
 
@@ -32,28 +29,13 @@ pub struct MockState {
 
 impl MockComponent {
     pub fn new() -> Self {
-        MockComponent {
-            reserved_widget_id: None,
-        }
-    }
-
-    /// The button created by this element always has the same id. If two widgets are created
-    /// with the same id (for instance, because the same button is returned twice because of
-    /// a copy-paste error), impredictable behavior may ensue.
-    pub fn with_reserved_id(self, widget_id: WidgetId) -> Self {
-        MockComponent {
-            reserved_widget_id: Some(widget_id),
-            ..self
-        }
+        MockComponent
     }
 }
 
 impl MockComponentData {
     pub fn new(clicks: u32) -> Self {
-        MockComponentData {
-            reserved_widget_id: None,
-            clicks,
-        }
+        MockComponentData { clicks }
     }
 }
 
@@ -85,7 +67,6 @@ impl Element for MockComponent {
         (
             MockComponentData {
                 clicks: prev_state.clicks,
-                reserved_widget_id: self.reserved_widget_id,
             },
             prev_state,
         )
@@ -98,8 +79,11 @@ impl VirtualDom for MockComponentData {
     type TargetWidgetSeq = ButtonWidget;
 
     fn init_tree(&self) -> ButtonWidget {
-        let id = self.reserved_widget_id.unwrap_or_else(WidgetId::next);
-        ButtonWidget::new(String::from("clickme"), Default::default(), id)
+        ButtonWidget::new(
+            String::from("clickme"),
+            Default::default(),
+            WidgetId::next(),
+        )
     }
 
     fn reconcile(&self, _prev_value: &Self, _widget: &mut ButtonWidget, _ctx: &mut ReconcileCtx) {}
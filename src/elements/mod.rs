@@ -8,8 +8,10 @@ mod component;
 mod element_list;
 mod element_option;
 mod empty;
+mod external_messages;
 mod flex_element;
 mod label;
+mod subscribe;
 mod textbox;
 
 pub mod element_tuple;
@@ -28,25 +30,34 @@ pub use component::{Component, ComponentOutput};
 pub use element_list::ElementList;
 pub use element_option::*;
 pub use empty::EmptyElement;
+pub use external_messages::ExternalMessages;
 pub use flex_element::Flex;
 pub use label::Label;
+pub use subscribe::{Subscribe, Subscription, SubscriptionSender};
+pub(crate) use subscribe::SubscriptionHandle;
 pub use textbox::{TextBox, TextChanged};
 
 // TODO - doc
 pub mod internals {
-    pub use super::any_element::VirtualDomBox;
+    pub use super::any_element::{AnyStateBox, VirtualDomBox};
     pub use super::button::ButtonData;
     pub use super::checkbox::CheckboxData;
     pub use super::clickable::ClickableData;
-    pub use super::component::{ComponentHolder, ComponentOutputData};
+    pub use super::component::{
+        ComponentHolder, ComponentHolderData, ComponentOutputData, IsComponentOutput,
+    };
     pub use super::element_list::ElementListData;
     pub use super::element_tuple::ElementTupleData;
     pub use super::empty::EmptyElementData;
+    pub use super::external_messages::ExternalMessagesData;
     pub use super::flex_element::FlexData;
     pub use super::label::LabelData;
+    pub use super::subscribe::SubscribeData;
     pub use super::textbox::TextBoxData;
-    pub use super::with_event::WithEventTarget;
-    pub use super::with_event::{ParentEvent, WithBubbleEvent, WithCallbackEvent, WithMapEvent};
+    pub use super::with_event::{
+        BubbleEvent, DispatchEvent, EventHandler, MapEvent, OnEvent, OnEventWithEffects,
+        OnStateEvent, OnStoreEvent, ParentEvent, WithEvent, WithEventTarget,
+    };
 
     // TODO - move to test_harness?
     pub use super::event_logger::{EventLogger, EventLoggerData};
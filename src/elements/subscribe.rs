@@ -0,0 +1,367 @@
+use crate::ctx::ReconcileCtx;
+use crate::element_tree::{Element, VirtualDom};
+use crate::external::PROCESS_EXTERNAL_MESSAGES;
+use crate::flex::FlexParams;
+use crate::glue::GlobalEventCx;
+use crate::metadata::NoState;
+use crate::widgets::{SingleWidget, SubscriptionWidget};
+
+use derivative::Derivative;
+use druid::{ExtEventError, ExtEventSink, Target};
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::sync::mpsc::Receiver;
+use std::sync::{Arc, Mutex, MutexGuard};
+use tracing::{instrument, trace};
+
+/// The receiving end of a channel, in a form that can be passed as props.
+///
+/// Create one with [`Subscription::channel`], or from a [`std::sync::mpsc::Receiver`].
+///
+/// Only messages sent through a [`SubscriptionSender`] wake up the application. Messages sent
+/// to a wrapped `Receiver` are emitted the next time the application processes events.
+///
+/// Two subscriptions are equal if they're clones of each other.
+#[derive(Derivative)]
+#[derivative(Clone(bound = ""))]
+pub struct Subscription<T> {
+    queue: Arc<SubscriptionQueue<T>>,
+}
+
+/// The sending end of a [`Subscription`].
+///
+/// Sending a message wakes up the application, so that the [`Subscribe`] elements using the
+/// subscription emit it right away.
+#[derive(Derivative)]
+#[derivative(Clone(bound = ""))]
+pub struct SubscriptionSender<T> {
+    queue: Arc<SubscriptionQueue<T>>,
+}
+
+/// Emits messages received from a channel.
+///
+/// While the element is in the tree, every message sent through a [`SubscriptionSender`] wakes
+/// up the application, and the element emits it. Messages sent while the element isn't in the
+/// tree are kept in the channel until an element subscribes again.
+///
+/// The element doesn't take any space in the layout.
+///
+/// ## Events
+///
+/// Emits the received messages.
+///
+/// ## Example
+///
+/// ```rust
+/// # use panoramix::{component, CompCtx, Element, ElementExt, NoEvent};
+/// # use panoramix::elements::{ComponentOutput, Label, Subscribe, Subscription};
+/// # use panoramix::Column;
+/// #[component]
+/// fn LastValue(ctx: &CompCtx, feed: Subscription<f64>) -> impl Element<Event = NoEvent> {
+///     let md = ctx.use_metadata::<NoEvent, Option<f64>>();
+///     let last_value = ctx.get_local_state(md);
+///     ComponentOutput::new(
+///         md,
+///         Column!(
+///             Label::new(format!("Last value: {:?}", last_value)),
+///             Subscribe::new(feed)
+///                 .on(md, |last_value, value: f64| *last_value = Some(value)),
+///         ),
+///     )
+/// }
+/// ```
+#[derive(Derivative)]
+#[derivative(Clone(bound = ""), Debug(bound = ""), PartialEq(bound = ""))]
+pub struct Subscribe<T> {
+    pub subscription: Subscription<T>,
+}
+
+#[derive(Derivative)]
+#[derivative(Clone(bound = ""), Debug(bound = ""), PartialEq(bound = ""))]
+pub struct SubscribeData<T> {
+    pub subscription: Subscription<T>,
+}
+
+/// Messages waiting to be emitted, and the sink used to wake up the application.
+#[derive(Derivative)]
+#[derivative(Default(bound = ""))]
+struct SubscriptionQueue<T> {
+    messages: Mutex<VecDeque<T>>,
+    receiver: Option<Mutex<Receiver<T>>>,
+    waker: Mutex<Waker>,
+}
+
+/// Wakes up the application while [`SubscriptionWidget`]s are in the widget tree.
+#[derive(Default)]
+struct Waker {
+    sink: Option<ExtEventSink>,
+    subscribers: usize,
+}
+
+/// The part of a [`Subscription`] used by [`SubscriptionWidget`], which doesn't depend on the
+/// type of messages.
+pub(crate) trait SubscriptionHandle {
+    /// Called when a widget using the subscription is added to the widget tree.
+    ///
+    /// Returns true if messages are already waiting.
+    fn subscribe(&self, sink: ExtEventSink) -> bool;
+
+    /// Called when a widget using the subscription is removed from the widget tree.
+    fn unsubscribe(&self);
+}
+
+//
+// --- IMPLS
+
+impl<T> Subscription<T> {
+    /// Create a channel whose sender wakes up the application.
+    pub fn channel() -> (SubscriptionSender<T>, Subscription<T>) {
+        let queue = Arc::new(SubscriptionQueue::default());
+        (
+            SubscriptionSender {
+                queue: queue.clone(),
+            },
+            Subscription { queue },
+        )
+    }
+
+    /// Take the next message, and whether more messages are waiting.
+    fn try_recv(&self) -> Option<(T, bool)> {
+        let mut messages = self.queue.lock_messages();
+        let message = messages.pop_front()?;
+        Some((message, !messages.is_empty()))
+    }
+
+    pub(crate) fn handle(&self) -> Arc<dyn SubscriptionHandle + Send + Sync>
+    where
+        T: Send + 'static,
+    {
+        self.queue.clone()
+    }
+}
+
+impl<T: Send + 'static> Subscription<T> {
+    /// Wrap the receiving end of a [`std::sync::mpsc`] channel.
+    ///
+    /// A `Receiver` can't wake up the application, so its messages are only emitted the next
+    /// time the application processes events. Use [`Subscription::channel`] to emit messages
+    /// as soon as they're sent.
+    pub fn new(receiver: Receiver<T>) -> Self {
+        Subscription {
+            queue: Arc::new(SubscriptionQueue {
+                receiver: Some(Mutex::new(receiver)),
+                ..Default::default()
+            }),
+        }
+    }
+}
+
+impl<T: Send + 'static> From<Receiver<T>> for Subscription<T> {
+    fn from(receiver: Receiver<T>) -> Self {
+        Subscription::new(receiver)
+    }
+}
+
+impl<T> PartialEq for Subscription<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.queue, &other.queue)
+    }
+}
+
+impl<T> Debug for Subscription<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Subscription")
+            .field(&std::any::type_name::<T>())
+            .finish()
+    }
+}
+
+impl<T> SubscriptionSender<T> {
+    /// Send a message to the subscription.
+    ///
+    /// Returns an error if the application has been closed.
+    pub fn send(&self, message: T) -> Result<(), ExtEventError> {
+        self.queue.push(message)
+    }
+}
+
+impl<T> Debug for SubscriptionSender<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("SubscriptionSender")
+            .field(&std::any::type_name::<T>())
+            .finish()
+    }
+}
+
+impl<T> SubscriptionQueue<T> {
+    /// Lock the queue, after moving the messages waiting in the wrapped receiver to it.
+    fn lock_messages(&self) -> MutexGuard<'_, VecDeque<T>> {
+        let mut messages = self.messages.lock().unwrap();
+        if let Some(receiver) = &self.receiver {
+            messages.extend(receiver.lock().unwrap().try_iter());
+        }
+        messages
+    }
+
+    fn push(&self, message: T) -> Result<(), ExtEventError> {
+        self.messages.lock().unwrap().push_back(message);
+        if let Some(sink) = &self.waker.lock().unwrap().sink {
+            // The root widget processes events after every druid event, including this one.
+            sink.submit_command(PROCESS_EXTERNAL_MESSAGES, (), Target::Auto)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> SubscriptionHandle for SubscriptionQueue<T> {
+    fn subscribe(&self, sink: ExtEventSink) -> bool {
+        let mut waker = self.waker.lock().unwrap();
+        waker.sink = Some(sink);
+        waker.subscribers += 1;
+        !self.lock_messages().is_empty()
+    }
+
+    fn unsubscribe(&self) {
+        let mut waker = self.waker.lock().unwrap();
+        waker.subscribers -= 1;
+        if waker.subscribers == 0 {
+            waker.sink = None;
+        }
+    }
+}
+
+impl<T> Subscribe<T> {
+    /// Build an element emitting the messages of the given subscription.
+    pub fn new(subscription: impl Into<Subscription<T>>) -> Self {
+        Subscribe {
+            subscription: subscription.into(),
+        }
+    }
+}
+
+impl<T: Clone + Debug + Send + 'static> Element for Subscribe<T> {
+    type Event = T;
+    type ComponentState = NoState;
+    type AggregateChildrenState = ();
+    type BuildOutput = SubscribeData<T>;
+
+    #[instrument(name = "Subscribe", skip(self, _prev_state))]
+    fn build(self, _prev_state: ()) -> (SubscribeData<T>, ()) {
+        (
+            SubscribeData {
+                subscription: self.subscription,
+            },
+            (),
+        )
+    }
+}
+
+impl<T: Clone + Debug + Send + 'static> VirtualDom for SubscribeData<T> {
+    type Event = T;
+    type AggregateChildrenState = ();
+    type TargetWidgetSeq = SingleWidget<SubscriptionWidget>;
+
+    #[instrument(name = "Subscribe", skip(self))]
+    fn init_tree(&self) -> Self::TargetWidgetSeq {
+        SingleWidget::new(
+            SubscriptionWidget::new(self.subscription.handle()),
+            FlexParams::default(),
+        )
+    }
+
+    #[instrument(name = "Subscribe", skip(self, prev_value, widget, ctx))]
+    fn reconcile(
+        &self,
+        prev_value: &Self,
+        widget: &mut Self::TargetWidgetSeq,
+        ctx: &mut ReconcileCtx,
+    ) {
+        if self.subscription != prev_value.subscription {
+            let sink = ctx.event_ctx.get_external_handle();
+            let widget = widget.widget_mut();
+            if widget.set_subscription(self.subscription.handle(), sink) {
+                ctx.event_ctx.submit_command(PROCESS_EXTERNAL_MESSAGES);
+            }
+        }
+    }
+
+    #[instrument(name = "Subscribe", skip(self, _children_state, _widget, cx))]
+    fn process_local_event(
+        &self,
+        _children_state: &mut (),
+        _widget: &mut Self::TargetWidgetSeq,
+        cx: &mut GlobalEventCx,
+    ) -> Option<T> {
+        let (message, has_more) = self.subscription.try_recv()?;
+        trace!("Received message {:?}", message);
+        if has_more {
+            cx.has_pending_events = true;
+        }
+        Some(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::glue::DruidAppData;
+    use std::sync::mpsc::channel;
+    use test_env_log::test;
+
+    #[test]
+    fn subscription_eq() {
+        let (_sender, subscription) = Subscription::<i32>::channel();
+        let (_sender_2, subscription_2) = Subscription::<i32>::channel();
+
+        assert_eq!(subscription, subscription.clone());
+        assert_ne!(subscription, subscription_2);
+    }
+
+    #[test]
+    fn drain_messages() {
+        let (sender, subscription) = Subscription::<i32>::channel();
+        let (subscribe_data, mut state) = Subscribe::new(subscription).build(());
+        let mut widget = subscribe_data.init_tree();
+
+        let mut data = DruidAppData::default();
+        let mut cx = GlobalEventCx::new(&mut data);
+
+        assert_eq!(
+            subscribe_data.process_local_event(&mut state, &mut widget, &mut cx),
+            None
+        );
+        assert!(!cx.has_pending_events);
+
+        sender.send(1).unwrap();
+        sender.send(2).unwrap();
+
+        assert_eq!(
+            subscribe_data.process_local_event(&mut state, &mut widget, &mut cx),
+            Some(1)
+        );
+        assert!(cx.has_pending_events);
+        cx.has_pending_events = false;
+        assert_eq!(
+            subscribe_data.process_local_event(&mut state, &mut widget, &mut cx),
+            Some(2)
+        );
+        assert!(!cx.has_pending_events);
+        assert_eq!(
+            subscribe_data.process_local_event(&mut state, &mut widget, &mut cx),
+            None
+        );
+    }
+
+    #[test]
+    fn poll_receiver() {
+        let (sender, receiver) = channel::<i32>();
+        let subscription = Subscription::new(receiver);
+        assert_eq!(subscription.try_recv(), None);
+
+        sender.send(1).unwrap();
+        sender.send(2).unwrap();
+        assert_eq!(subscription.try_recv(), Some((1, true)));
+        assert_eq!(subscription.try_recv(), Some((2, false)));
+        assert_eq!(subscription.try_recv(), None);
+    }
+}
@@ -22,14 +22,12 @@ use tracing::{instrument, trace};
 pub struct TextBox {
     pub text: String,
     pub flex: FlexParams,
-    pub reserved_widget_id: Option<WidgetId>,
 }
 
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct TextBoxData {
     pub text: String,
     pub flex: FlexParams,
-    pub reserved_widget_id: Option<WidgetId>,
 }
 
 /// Event emitted when text is entered or edited in a [TextBox].
@@ -54,7 +52,6 @@ impl TextBox {
                 flex: None,
                 alignment: None,
             },
-            reserved_widget_id: None,
         }
     }
 
@@ -66,18 +63,6 @@ impl TextBox {
         }
     }
 
-    /// For unit tests only.
-    ///
-    /// The textbox created by this element always has the same id. If two widgets are created
-    /// with the same id (for instance, because the same textbox is returned twice because of
-    /// a copy-paste error), impredictable behavior may ensue.
-    pub fn with_reserved_id(self, widget_id: WidgetId) -> Self {
-        TextBox {
-            reserved_widget_id: Some(widget_id),
-            ..self
-        }
-    }
-
     /// Provide a closure to be called when this box is edited.
     pub fn on_text_changed<ComponentEvent: 'static, ComponentState: 'static>(
         self,
@@ -101,7 +86,6 @@ impl Element for TextBox {
             TextBoxData {
                 text: self.text,
                 flex: self.flex,
-                reserved_widget_id: self.reserved_widget_id,
             },
             (),
         )
@@ -116,8 +100,7 @@ impl VirtualDom for TextBoxData {
 
     #[instrument(name = "TextBox", skip(self))]
     fn init_tree(&self) -> TextBoxWidget {
-        let id = self.reserved_widget_id.unwrap_or_else(WidgetId::next);
-        TextBoxWidget::new(self.text.clone(), self.flex, id)
+        TextBoxWidget::new(self.text.clone(), self.flex, WidgetId::next())
     }
 
     #[instrument(name = "TextBox", skip(self, _prev_value, widget, ctx))]
@@ -170,7 +153,6 @@ mod tests {
                     flex: None,
                     alignment: None,
                 },
-                ..Default::default()
             }
         );
     }
@@ -197,15 +179,12 @@ mod tests {
         use std::sync::mpsc::channel;
 
         let (event_sender, event_receiver) = channel();
-        let textbox_id = WidgetId::reserved(42);
-        let textbox = EventLogger::new(
-            event_sender,
-            TextBox::new("Hello").with_reserved_id(textbox_id),
-        );
+        let textbox = EventLogger::new(event_sender, TextBox::new("Hello"));
 
         Harness::run_test_window(textbox, |harness| {
             assert_debug_snapshot!(harness.get_root_debug_state());
 
+            let textbox_id = harness.widget_id_at("TextBox");
             harness.mouse_click_on(textbox_id);
             harness.keyboard_key("a");
 
@@ -218,4 +197,33 @@ mod tests {
             // TODO - test data persistence, somehow?
         });
     }
+
+    #[test]
+    fn textbox_type_text() {
+        use crate::elements::event_logger::EventLogger;
+        use std::sync::mpsc::channel;
+
+        let (event_sender, event_receiver) = channel();
+        let textbox = EventLogger::new(event_sender, TextBox::new("Hello"));
+
+        Harness::run_test_window(textbox, |harness| {
+            let textbox_id = harness.widget_id_at("TextBox");
+            // Every key release emits the text of the box, changed or not
+            let last_text = || {
+                event_receiver
+                    .try_iter()
+                    .last()
+                    .map(|event| event.new_content)
+            };
+
+            harness.type_text(textbox_id, "a");
+            assert_eq!(last_text(), Some("Helloa".to_string()));
+
+            harness.select_all(textbox_id);
+            assert_eq!(last_text(), Some("Helloa".to_string()));
+
+            harness.paste(textbox_id, "World");
+            assert_eq!(last_text(), Some("World".to_string()));
+        });
+    }
 }
@@ -1,29 +1,18 @@
 use crate::ctx::{ProcessEventCtx, ReconcileCtx};
+use crate::effects::Effects;
 use crate::element_tree::{Element, VirtualDom};
-use crate::glue::GlobalEventCx;
-use crate::metadata::{Metadata, NoState};
+use crate::glue::{DruidAppData, GlobalEventCx};
+use crate::inspector::InspectNode;
+use crate::metadata::{Metadata, NoState, StateHandle};
+use crate::reducer::Reducer;
 
 use derivative::Derivative;
-use tracing::{instrument, trace};
+use std::cell::RefCell;
+use std::fmt::Debug;
+use tracing::{debug, instrument, trace};
 
 // UTILITY TRAITS ---
 
-pub trait OptionOrUnit<T> {
-    fn to_option(self) -> Option<T>;
-}
-
-impl<T> OptionOrUnit<T> for Option<T> {
-    fn to_option(self) -> Option<T> {
-        self
-    }
-}
-
-impl<T> OptionOrUnit<T> for () {
-    fn to_option(self) -> Option<T> {
-        None
-    }
-}
-
 /// TODO - Document this
 pub trait ParentEvent<Child> {
     fn from_child_event(child: Child) -> Self;
@@ -54,92 +43,126 @@ fn bubble_event_up<State, Event>(_state: &mut State, event: Event) -> Option<Eve
 
 // ---
 
-/// Applies callback to events of child element
+/// Something done with the events of the element wrapped in [`WithEvent`], eg calling a
+/// callback with the component's local state.
 ///
 /// For internal use only. Library users should use [ElementExt](crate::ElementExt) instead.
+pub trait EventHandler<EventParam>: Clone + Debug + 'static {
+    fn handle_event(
+        &self,
+        event: EventParam,
+        comp_ctx: &mut ProcessEventCtx,
+        cx: &mut GlobalEventCx,
+    );
+}
+
+/// Applies callback to events of child element.
 #[derive(Derivative)]
-#[derivative(Clone(bound = ""), Debug(bound = ""))]
-pub struct WithCallbackEvent<
-    ComponentEvent: 'static,
-    ComponentState: 'static,
-    EventParam,
-    Child: Element,
-    Cb: Clone + Fn(&mut ComponentState, EventParam),
-> where
-    Child::Event: ParentEvent<EventParam>,
-{
-    pub element: Child,
+#[derivative(Clone(bound = "Cb: Clone"), Debug(bound = ""))]
+pub struct OnEvent<ComponentEvent, ComponentState, Cb> {
+    #[derivative(Debug(format_with = "format_typename"))]
+    pub callback: Cb,
+    #[derivative(Debug = "ignore")]
+    pub _metadata: Metadata<ComponentEvent, ComponentState>,
+}
+
+/// Applies callback to events of child element, with a queue of side effects.
+#[derive(Derivative)]
+#[derivative(Clone(bound = "Cb: Clone"), Debug(bound = ""))]
+pub struct OnEventWithEffects<ComponentEvent, ComponentState, Cb> {
     #[derivative(Debug(format_with = "format_typename"))]
     pub callback: Cb,
     #[derivative(Debug = "ignore")]
     pub _metadata: Metadata<ComponentEvent, ComponentState>,
+}
+
+/// Applies callback to events of child element, with a state slot.
+#[derive(Derivative)]
+#[derivative(Clone(bound = "Cb: Clone"), Debug(bound = ""))]
+pub struct OnStateEvent<State, Cb> {
+    #[derivative(Debug(format_with = "format_typename"))]
+    pub callback: Cb,
+    pub handle: StateHandle<State>,
+}
+
+/// Applies callback to events of child element, with the app-wide store.
+#[derive(Derivative)]
+#[derivative(Clone(bound = "Cb: Clone"), Debug(bound = ""))]
+pub struct OnStoreEvent<Store, Cb> {
+    #[derivative(Debug(format_with = "format_typename"))]
+    pub callback: Cb,
     #[derivative(Debug = "ignore")]
-    pub _marker: std::marker::PhantomData<EventParam>,
+    pub _marker: std::marker::PhantomData<Store>,
+}
+
+/// Turns events of child element into actions, and applies them to the component's local state.
+#[derive(Derivative)]
+#[derivative(Clone(bound = "Cb: Clone"), Debug(bound = ""))]
+pub struct DispatchEvent<ComponentEvent, ComponentState, Cb> {
+    #[derivative(Debug(format_with = "format_typename"))]
+    pub callback: Cb,
+    #[derivative(Debug = "ignore")]
+    pub _metadata: Metadata<ComponentEvent, ComponentState>,
 }
 
 /// Maps events of child element into events of parent component, using provided map function.
-///
-/// For internal use only. Library users should use [ElementExt](crate::ElementExt) instead.
 #[derive(Derivative)]
-#[derivative(Clone(bound = ""), Debug(bound = ""))]
-pub struct WithMapEvent<
-    ComponentEvent: 'static,
-    ComponentState: 'static,
-    EventParam,
-    EventReturn,
-    Child: Element,
-    Cb: Clone + Fn(&mut ComponentState, EventParam) -> Option<EventReturn>,
-> where
-    Child::Event: ParentEvent<EventParam>,
-    ComponentEvent: ParentEvent<EventReturn>,
-{
-    pub element: Child,
+#[derivative(Clone(bound = "Cb: Clone"), Debug(bound = ""))]
+pub struct MapEvent<ComponentEvent, ComponentState, EventReturn, Cb> {
     #[derivative(Debug(format_with = "format_typename"))]
     pub callback: Cb,
     #[derivative(Debug = "ignore")]
     pub _metadata: Metadata<ComponentEvent, ComponentState>,
     #[derivative(Debug = "ignore")]
-    pub _marker: std::marker::PhantomData<EventParam>,
+    pub _marker: std::marker::PhantomData<EventReturn>,
 }
 
 /// Transfers events of child element to parent component.
+pub type BubbleEvent<ComponentEvent, ComponentState, Event> = MapEvent<
+    ComponentEvent,
+    ComponentState,
+    Event,
+    fn(&mut ComponentState, Event) -> Option<Event>,
+>;
+
+impl<ComponentEvent, ComponentState, Event> BubbleEvent<ComponentEvent, ComponentState, Event> {
+    pub fn new(md: Metadata<ComponentEvent, ComponentState>) -> Self {
+        MapEvent {
+            callback: bubble_event_up,
+            _metadata: md,
+            _marker: Default::default(),
+        }
+    }
+}
+
+/// Passes events of child element to an [`EventHandler`].
+///
+/// Handlers can be chained: after the handler is called, the event is passed on to the
+/// element wrapping this one.
 ///
 /// For internal use only. Library users should use [ElementExt](crate::ElementExt) instead.
 #[derive(Derivative)]
-#[derivative(Clone(bound = ""), Debug(bound = ""))]
-pub struct WithBubbleEvent<ComponentEvent, ComponentState, Event, Child: Element>
-where
-    Child::Event: ParentEvent<Event>,
-    ComponentEvent: ParentEvent<Event>,
-{
+#[derivative(Clone(bound = "Handler: Clone"), Debug(bound = "Handler: Debug"))]
+pub struct WithEvent<EventParam, Child: Element, Handler> {
     pub element: Child,
+    pub handler: Handler,
     #[derivative(Debug = "ignore")]
-    pub _metadata: Metadata<ComponentEvent, ComponentState>,
-    #[derivative(Debug = "ignore")]
-    pub _marker: std::marker::PhantomData<Event>,
+    pub _marker: std::marker::PhantomData<EventParam>,
 }
 
 #[derive(Derivative)]
-#[derivative(Clone(bound = "Child: Clone"), Debug(bound = ""))]
-pub struct WithEventTarget<
-    ComponentEvent: 'static,
-    ComponentState: 'static,
-    EventParam,
-    EventReturn,
-    CbReturn: OptionOrUnit<EventReturn>,
-    Child: VirtualDom,
-    Cb: Clone + Fn(&mut ComponentState, EventParam) -> CbReturn,
-> where
-    Child::Event: ParentEvent<EventParam>,
-    ComponentEvent: ParentEvent<EventReturn>,
-{
+#[derivative(
+    Clone(bound = "Child: Clone, Child::Event: Clone, Handler: Clone"),
+    Debug(bound = "Handler: Debug")
+)]
+pub struct WithEventTarget<EventParam, Child: VirtualDom, Handler> {
     element: Child,
-    #[derivative(Debug(format_with = "format_typename"))]
-    callback: Cb,
+    handler: Handler,
     #[derivative(Debug = "ignore")]
-    _metadata: Metadata<ComponentEvent, ComponentState>,
+    _marker: std::marker::PhantomData<EventParam>,
+    /// Event of child element, kept for the next handler in the chain.
     #[derivative(Debug = "ignore")]
-    _marker: std::marker::PhantomData<(EventParam, EventReturn)>,
+    pending_event: RefCell<Option<Child::Event>>,
 }
 
 // ---
@@ -147,106 +170,131 @@ pub struct WithEventTarget<
 impl<
         ComponentEvent: 'static,
         ComponentState: 'static,
-        EventParam: 'static,
-        Child: Element,
+        EventParam,
         Cb: Clone + Fn(&mut ComponentState, EventParam) + 'static,
-    > Element for WithCallbackEvent<ComponentEvent, ComponentState, EventParam, Child, Cb>
-where
-    Child::Event: ParentEvent<EventParam>,
+    > EventHandler<EventParam> for OnEvent<ComponentEvent, ComponentState, Cb>
 {
-    type Event = Child::Event;
-    type ComponentState = NoState;
-    type AggregateChildrenState = Child::AggregateChildrenState;
-    type BuildOutput = WithEventTarget<
-        ComponentEvent,
-        ComponentState,
+    fn handle_event(
+        &self,
+        event: EventParam,
+        comp_ctx: &mut ProcessEventCtx,
+        _cx: &mut GlobalEventCx,
+    ) {
+        trace!("Processing callback for local event");
+        (self.callback)(comp_ctx.state(self._metadata), event);
+    }
+}
+
+impl<
+        ComponentEvent: 'static,
+        ComponentState: 'static,
         EventParam,
-        ComponentEvent,
-        (),
-        Child::BuildOutput,
-        Cb,
-    >;
+        Cb: Clone + Fn(&mut ComponentState, EventParam, &mut Effects) + 'static,
+    > EventHandler<EventParam> for OnEventWithEffects<ComponentEvent, ComponentState, Cb>
+{
+    fn handle_event(
+        &self,
+        event: EventParam,
+        comp_ctx: &mut ProcessEventCtx,
+        cx: &mut GlobalEventCx,
+    ) {
+        trace!("Processing effects callback for local event");
+        (self.callback)(comp_ctx.state(self._metadata), event, &mut cx.effects);
+    }
+}
 
-    #[instrument(name = "WithEvent", skip(self, prev_state))]
-    fn build(
-        self,
-        prev_state: Self::AggregateChildrenState,
-    ) -> (Self::BuildOutput, Self::AggregateChildrenState) {
-        let (element, state) = self.element.build(prev_state);
-        (
-            WithEventTarget {
-                element,
-                callback: self.callback,
-                _metadata: Default::default(),
-                _marker: Default::default(),
-            },
-            state,
-        )
+impl<State: 'static, EventParam, Cb: Clone + Fn(&mut State, EventParam) + 'static>
+    EventHandler<EventParam> for OnStateEvent<State, Cb>
+{
+    fn handle_event(
+        &self,
+        event: EventParam,
+        comp_ctx: &mut ProcessEventCtx,
+        _cx: &mut GlobalEventCx,
+    ) {
+        trace!("Processing state callback for local event");
+        (self.callback)(comp_ctx.hook_state(self.handle), event);
+    }
+}
+
+impl<Store: 'static, EventParam, Cb: Clone + Fn(&mut Store, EventParam) + 'static>
+    EventHandler<EventParam> for OnStoreEvent<Store, Cb>
+{
+    fn handle_event(
+        &self,
+        event: EventParam,
+        _comp_ctx: &mut ProcessEventCtx,
+        cx: &mut GlobalEventCx,
+    ) {
+        trace!("Processing store callback for local event");
+        let store = cx
+            .store
+            .as_deref_mut()
+            .expect("error: 'on_store' can only be used in apps with a store (see RootHandler::with_store)")
+            .downcast_mut::<Store>()
+            .expect(&format!(
+                "error: 'on_store' expected store of type {}",
+                std::any::type_name::<Store>(),
+            ));
+        (self.callback)(store, event);
+        cx.store_modified = true;
     }
 }
 
 impl<
         ComponentEvent: 'static,
+        ComponentState: Reducer + 'static,
+        EventParam,
+        Cb: Clone + Fn(EventParam) -> ComponentState::Action + 'static,
+    > EventHandler<EventParam> for DispatchEvent<ComponentEvent, ComponentState, Cb>
+{
+    fn handle_event(
+        &self,
+        event: EventParam,
+        comp_ctx: &mut ProcessEventCtx,
+        _cx: &mut GlobalEventCx,
+    ) {
+        let action = (self.callback)(event);
+        debug!("Dispatching action: {:?}", action);
+        comp_ctx.state(self._metadata).reduce(action);
+    }
+}
+
+impl<
+        ComponentEvent: ParentEvent<EventReturn> + 'static,
         ComponentState: 'static,
-        EventParam: 'static,
+        EventParam,
         EventReturn: 'static,
-        Child: Element,
         Cb: Clone + Fn(&mut ComponentState, EventParam) -> Option<EventReturn> + 'static,
-    > Element for WithMapEvent<ComponentEvent, ComponentState, EventParam, EventReturn, Child, Cb>
-where
-    Child::Event: ParentEvent<EventParam>,
-    ComponentEvent: ParentEvent<EventReturn>,
+    > EventHandler<EventParam> for MapEvent<ComponentEvent, ComponentState, EventReturn, Cb>
 {
-    type Event = Child::Event;
-    type ComponentState = NoState;
-    type AggregateChildrenState = Child::AggregateChildrenState;
-    type BuildOutput = WithEventTarget<
-        ComponentEvent,
-        ComponentState,
-        EventParam,
-        EventReturn,
-        Option<EventReturn>,
-        Child::BuildOutput,
-        Cb,
-    >;
-
-    #[instrument(name = "WithEvent", skip(self, prev_state))]
-    fn build(
-        self,
-        prev_state: Self::AggregateChildrenState,
-    ) -> (Self::BuildOutput, Self::AggregateChildrenState) {
-        let (element, state) = self.element.build(prev_state);
-        (
-            WithEventTarget {
-                element,
-                callback: self.callback,
-                _metadata: Default::default(),
-                _marker: Default::default(),
-            },
-            state,
-        )
+    fn handle_event(
+        &self,
+        event: EventParam,
+        comp_ctx: &mut ProcessEventCtx,
+        _cx: &mut GlobalEventCx,
+    ) {
+        trace!("Processing callback for local event");
+        let md = self._metadata;
+        let event =
+            (self.callback)(comp_ctx.state(md), event).map(ComponentEvent::from_child_event);
+        if let Some(event) = event {
+            // TODO - Log event
+            trace!("Callback returned event");
+            comp_ctx.event_queue(md).push(event);
+        }
     }
 }
 
-impl<ComponentEvent: 'static, ComponentState: 'static, Event: 'static, Child: Element> Element
-    for WithBubbleEvent<ComponentEvent, ComponentState, Event, Child>
+impl<EventParam: 'static, Child: Element, Handler: EventHandler<EventParam>> Element
+    for WithEvent<EventParam, Child, Handler>
 where
-    Child::Event: ParentEvent<Event>,
-    ComponentEvent: ParentEvent<Event>,
+    Child::Event: ParentEvent<EventParam> + Clone,
 {
     type Event = Child::Event;
-
     type ComponentState = NoState;
     type AggregateChildrenState = Child::AggregateChildrenState;
-    type BuildOutput = WithEventTarget<
-        ComponentEvent,
-        ComponentState,
-        Event,
-        Event,
-        Option<Event>,
-        Child::BuildOutput,
-        fn(&mut ComponentState, Event) -> Option<Event>,
-    >;
+    type BuildOutput = WithEventTarget<EventParam, Child::BuildOutput, Handler>;
 
     #[instrument(name = "WithEvent", skip(self, prev_state))]
     fn build(
@@ -257,36 +305,19 @@ where
         (
             WithEventTarget {
                 element,
-                callback: bubble_event_up,
-                _metadata: Default::default(),
+                handler: self.handler,
                 _marker: Default::default(),
+                pending_event: Default::default(),
             },
             state,
         )
     }
 }
 
-impl<
-        ComponentEvent: 'static,
-        ComponentState: 'static,
-        EventParam,
-        EventReturn,
-        CbReturn: OptionOrUnit<EventReturn>,
-        Child: VirtualDom,
-        Cb: Clone + Fn(&mut ComponentState, EventParam) -> CbReturn,
-    > VirtualDom
-    for WithEventTarget<
-        ComponentEvent,
-        ComponentState,
-        EventParam,
-        EventReturn,
-        CbReturn,
-        Child,
-        Cb,
-    >
+impl<EventParam, Child: VirtualDom, Handler: EventHandler<EventParam>> VirtualDom
+    for WithEventTarget<EventParam, Child, Handler>
 where
-    Child::Event: ParentEvent<EventParam>,
-    ComponentEvent: ParentEvent<EventReturn>,
+    Child::Event: ParentEvent<EventParam> + Clone,
 {
     type Event = Child::Event;
     type AggregateChildrenState = Child::AggregateChildrenState;
@@ -322,29 +353,35 @@ where
         self.element
             .process_event(comp_ctx, children_state, widget_seq, cx);
 
-        // FIXME - Handle chains of callbacks eg
-        /*
-            Button(...)
-                .on(|Pressed| ...)
-                .on(|MouseEnter| ...)
-                .on(|MouseLeave| ...)
-        */
-
-        let md = self._metadata;
         let local_event = self
             .element
             .process_local_event(children_state, widget_seq, cx);
-        if let Some(local_event) = local_event.map(ParentEvent::into_child_event).flatten() {
-            trace!("Processing callback for local event");
-            let event = (self.callback)(comp_ctx.state(md), local_event)
-                .to_option()
-                .map(ComponentEvent::from_child_event);
-            if let Some(event) = event {
-                // TODO - Log event
-                trace!("Callback returned event");
-                comp_ctx.event_queue(md).push(event);
-            }
+        let param = local_event
+            .clone()
+            .and_then(ParentEvent::<EventParam>::into_child_event);
+        if let Some(param) = param {
+            self.handler.handle_event(param, comp_ctx, cx);
         }
+        // Let handlers chained after this one see the event too
+        self.pending_event.replace(local_event);
+    }
+
+    fn process_local_event(
+        &self,
+        _children_state: &mut Child::AggregateChildrenState,
+        _widget_seq: &mut Self::TargetWidgetSeq,
+        _cx: &mut GlobalEventCx,
+    ) -> Option<Child::Event> {
+        self.pending_event.take()
+    }
+
+    fn inspect(
+        &self,
+        children_state: &Child::AggregateChildrenState,
+        widget_seq: &Self::TargetWidgetSeq,
+        data: &DruidAppData,
+    ) -> Vec<InspectNode> {
+        self.element.inspect(children_state, widget_seq, data)
     }
 }
 
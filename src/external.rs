@@ -0,0 +1,88 @@
+use derivative::Derivative;
+use druid::{ExtEventError, ExtEventSink, Selector, Target};
+use std::any::Any;
+use std::sync::{Arc, Mutex};
+
+/// Command sent to the root widget when external messages are waiting to be processed.
+pub(crate) const PROCESS_EXTERNAL_MESSAGES: Selector =
+    Selector::new("panoramix.process_external_messages");
+
+/// Messages sent by [`ExternalHandle`]s, waiting to be processed by the root widget.
+#[derive(Default)]
+pub(crate) struct ExternalQueue {
+    messages: Vec<Box<dyn Any + Send>>,
+    sink: Option<ExtEventSink>,
+}
+
+pub(crate) type SharedExternalQueue = Arc<Mutex<ExternalQueue>>;
+
+/// Handle used to send messages to a running application from other threads.
+///
+/// Get one with [`RootHandler::external_handle`](crate::RootHandler::external_handle) before
+/// launching the application. Messages are received by
+/// [`ExternalMessages`](crate::elements::ExternalMessages) elements, which emit them as events.
+///
+/// Messages sent before the application is launched are processed once it starts.
+#[derive(Derivative)]
+#[derivative(Clone(bound = ""))]
+pub struct ExternalHandle<Message> {
+    queue: SharedExternalQueue,
+    _marker: std::marker::PhantomData<fn(Message)>,
+}
+
+// ---
+
+impl ExternalQueue {
+    /// Set the sink used to wake up the application, and process messages sent before launch.
+    pub(crate) fn set_sink(queue: &SharedExternalQueue, sink: ExtEventSink) {
+        let mut queue = queue.lock().unwrap();
+        if !queue.messages.is_empty() {
+            // If this fails, the application is already closed, so there's nothing to wake up.
+            let _ = sink.submit_command(PROCESS_EXTERNAL_MESSAGES, (), Target::Auto);
+        }
+        queue.sink = Some(sink);
+    }
+
+    pub(crate) fn has_messages(queue: &SharedExternalQueue) -> bool {
+        !queue.lock().unwrap().messages.is_empty()
+    }
+
+    /// Queue a message sent from the UI thread, eg by a timer, which will be processed by
+    /// the current event.
+    pub(crate) fn push_message(queue: &SharedExternalQueue, message: Box<dyn Any + Send>) {
+        queue.lock().unwrap().messages.push(message);
+    }
+
+    pub(crate) fn take_messages(queue: &SharedExternalQueue) -> Vec<Box<dyn Any + Send>> {
+        std::mem::take(&mut queue.lock().unwrap().messages)
+    }
+}
+
+impl<Message: Send + 'static> ExternalHandle<Message> {
+    pub(crate) fn new(queue: SharedExternalQueue) -> Self {
+        ExternalHandle {
+            queue,
+            _marker: Default::default(),
+        }
+    }
+
+    /// Send a message to the application.
+    ///
+    /// Returns an error if the application has been closed.
+    pub fn send(&self, message: Message) -> Result<(), ExtEventError> {
+        let mut queue = self.queue.lock().unwrap();
+        queue.messages.push(Box::new(message));
+        if let Some(sink) = &queue.sink {
+            sink.submit_command(PROCESS_EXTERNAL_MESSAGES, (), Target::Auto)?;
+        }
+        Ok(())
+    }
+}
+
+impl<Message> std::fmt::Debug for ExternalHandle<Message> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExternalHandle")
+            .field("message", &std::any::type_name::<Message>())
+            .finish()
+    }
+}
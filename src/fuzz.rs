@@ -0,0 +1,207 @@
+//! Random interactions with the test window, used to find bugs manual tests don't.
+
+use crate::inspector::{InspectNode, InspectNodeKind};
+
+use druid::WidgetId;
+use std::any::Any;
+use std::collections::HashSet;
+
+/// Characters used to generate text typed in textboxes.
+const FUZZ_CHARACTERS: &[char] = &['a', 'b', 'z', 'A', '0', ' ', '-', 'é', '字', '🦀'];
+
+/// An interaction chosen by the fuzzer.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum FuzzAction {
+    Click(WidgetId),
+    TypeText(WidgetId, String),
+    Paste(WidgetId, String),
+    SelectAll(WidgetId),
+    Backspace(WidgetId),
+}
+
+/// Deterministic pseudo-random number generator (SplitMix64), so that seeds can be replayed
+/// on any platform.
+pub(crate) struct FuzzRng {
+    state: u64,
+}
+
+//
+// --- IMPLS
+
+impl FuzzRng {
+    pub fn new(seed: u64) -> Self {
+        FuzzRng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `0..bound`.
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    fn text(&mut self) -> String {
+        let len = 1 + self.below(5);
+        (0..len)
+            .map(|_| FUZZ_CHARACTERS[self.below(FUZZ_CHARACTERS.len())])
+            .collect()
+    }
+}
+
+/// Returns a random interaction with one of the interactive widgets of the tree, or `None` if
+/// there are none.
+pub(crate) fn choose_action(tree: &InspectNode, rng: &mut FuzzRng) -> Option<FuzzAction> {
+    let mut targets = Vec::new();
+    collect_targets(tree, &mut targets);
+    if targets.is_empty() {
+        return None;
+    }
+
+    let (name, widget_id) = targets[rng.below(targets.len())];
+    let action = if name == "TextBox" {
+        match rng.below(4) {
+            0 => FuzzAction::TypeText(widget_id, rng.text()),
+            1 => FuzzAction::Paste(widget_id, rng.text()),
+            2 => FuzzAction::SelectAll(widget_id),
+            _ => FuzzAction::Backspace(widget_id),
+        }
+    } else {
+        FuzzAction::Click(widget_id)
+    };
+    Some(action)
+}
+
+fn collect_targets<'a>(node: &'a InspectNode, targets: &mut Vec<(&'a str, WidgetId)>) {
+    let is_interactive = matches!(
+        node.name.as_str(),
+        "Button" | "Checkbox" | "Clickable" | "TextBox"
+    );
+    if node.kind == InspectNodeKind::Element && is_interactive {
+        let widget_id = node
+            .find(|node| node.widget_id.is_some())
+            .and_then(|node| node.widget_id);
+        if let Some(widget_id) = widget_id {
+            targets.push((node.name.as_str(), widget_id));
+        }
+    }
+    for child in &node.children {
+        collect_targets(child, targets);
+    }
+}
+
+/// Check that the widgets of the virtual DOM are the widgets of the druid tree.
+///
+/// `is_in_druid_tree` returns true if druid knows a widget with the given id. Widgets of the
+/// druid tree which no element created are found by the inspector, which reports them in
+/// error nodes.
+pub(crate) fn check_widget_sync(
+    tree: &InspectNode,
+    is_in_druid_tree: &mut dyn FnMut(WidgetId) -> bool,
+) -> Result<(), String> {
+    if let Some(error) = tree.find(|node| node.kind == InspectNodeKind::Error) {
+        let mut orphaned_widgets = Vec::new();
+        visit_widgets(error, &mut |node, widget_id| {
+            orphaned_widgets.push(format!("{:?} ({})", widget_id, node.name));
+        });
+        return Err(format!(
+            "widgets [{}] are in the druid tree, but not in the virtual DOM: {}",
+            orphaned_widgets.join(", "),
+            error.value.as_deref().unwrap_or_default()
+        ));
+    }
+
+    let mut widget_ids = HashSet::new();
+    let mut result = Ok(());
+    visit_widgets(tree, &mut |node, widget_id| {
+        if result.is_err() {
+            return;
+        }
+        if !widget_ids.insert(widget_id) {
+            result = Err(format!(
+                "widget {:?} ({}) appears twice in the virtual DOM",
+                widget_id, node.name
+            ));
+        } else if !is_in_druid_tree(widget_id) {
+            result = Err(format!(
+                "widget {:?} ({}) is in the virtual DOM, but not in the druid tree",
+                widget_id, node.name
+            ));
+        }
+    });
+    result
+}
+
+fn visit_widgets(node: &InspectNode, visitor: &mut dyn FnMut(&InspectNode, WidgetId)) {
+    if let Some(widget_id) = node.widget_id {
+        visitor(node, widget_id);
+    }
+    for child in &node.children {
+        visit_widgets(child, visitor);
+    }
+}
+
+/// Returns the message of a caught panic.
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "<non-string panic payload>".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_env_log::test;
+
+    #[test]
+    fn rng_is_deterministic() {
+        let mut rng_1 = FuzzRng::new(42);
+        let mut rng_2 = FuzzRng::new(42);
+        let values_1: Vec<_> = (0..10).map(|_| rng_1.below(100)).collect();
+        let values_2: Vec<_> = (0..10).map(|_| rng_2.below(100)).collect();
+        assert_eq!(values_1, values_2);
+
+        let mut rng_3 = FuzzRng::new(43);
+        let values_3: Vec<_> = (0..10).map(|_| rng_3.below(100)).collect();
+        assert_ne!(values_1, values_3);
+    }
+
+    #[test]
+    fn orphaned_widgets() {
+        let widget_id = WidgetId::next();
+        let orphaned_widget = InspectNode {
+            widget_id: Some(widget_id),
+            ..InspectNode::new(InspectNodeKind::Widget, "Label".to_string(), Vec::new())
+        };
+        let tree = InspectNode::root(vec![InspectNode::element(
+            "List",
+            vec![InspectNode::error(
+                "list has 0 items, 0 states and 1 widgets".to_string(),
+                vec![orphaned_widget],
+            )],
+        )]);
+
+        let error = check_widget_sync(&tree, &mut |_| true).unwrap_err();
+        assert!(
+            error.contains(&format!("{:?} (Label)", widget_id)),
+            "{}",
+            error
+        );
+        assert!(error.contains("not in the virtual DOM"), "{}", error);
+    }
+
+    #[test]
+    fn no_targets() {
+        let tree = InspectNode::element("Label", Vec::new());
+        assert_eq!(choose_action(&tree, &mut FuzzRng::new(0)), None);
+    }
+}
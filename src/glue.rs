@@ -2,7 +2,10 @@
 
 #![allow(unused)]
 
+use crate::effects::Effects;
+
 use druid::Data;
+use std::any::Any;
 use std::collections::HashMap;
 use std::sync::Arc;
 
@@ -11,11 +14,30 @@ pub use druid::WidgetId;
 
 pub struct GlobalEventCx<'a> {
     pub app_data: &'a mut DruidAppData,
+    /// Side effects requested by event callbacks, run after the GUI is updated.
+    pub effects: Effects,
+    /// Message sent from another thread, waiting to be taken by an `ExternalMessages` element.
+    pub external_message: Option<Box<dyn Any + Send>>,
+    /// Set by elements which may have more events to emit, eg `Subscribe`.
+    ///
+    /// The root widget then processes events again.
+    pub has_pending_events: bool,
+    /// The app-wide store, if the app has one (see `RootHandler::with_store`).
+    pub store: Option<&'a mut (dyn Any + 'static)>,
+    /// Set by elements which may have modified the store.
+    pub store_modified: bool,
 }
 
 impl<'a> GlobalEventCx<'a> {
     pub fn new(app_data: &'a mut DruidAppData) -> Self {
-        Self { app_data }
+        Self {
+            app_data,
+            effects: Effects::default(),
+            external_message: None,
+            has_pending_events: false,
+            store: None,
+            store_modified: false,
+        }
     }
 }
 
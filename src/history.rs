@@ -0,0 +1,172 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use tracing::trace;
+
+/// Snapshots of the app state, used to undo and redo changes.
+///
+/// The root handler records the local state of components along with the app-wide store.
+///
+/// See [`RootHandler::with_history`](crate::RootHandler::with_history).
+#[derive(Debug, Clone)]
+pub(crate) struct History<State> {
+    limit: usize,
+    coalesce_interval: Option<Duration>,
+    undo_stack: VecDeque<State>,
+    redo_stack: Vec<State>,
+    last_record_time: Option<Instant>,
+}
+
+impl<State> History<State> {
+    /// Keep at most `limit` snapshots.
+    pub fn new(limit: usize) -> Self {
+        History {
+            limit,
+            coalesce_interval: None,
+            undo_stack: VecDeque::new(),
+            redo_stack: Vec::new(),
+            last_record_time: None,
+        }
+    }
+
+    /// Changes made less than `interval` after the previous change are undone together.
+    pub fn with_coalescing(self, interval: Duration) -> Self {
+        History {
+            coalesce_interval: Some(interval),
+            ..self
+        }
+    }
+
+    /// Record the state as it was before a change.
+    pub fn record(&mut self, prev_state: State, now: Instant) {
+        self.redo_stack.clear();
+
+        let coalesce = match (self.coalesce_interval, self.last_record_time) {
+            (Some(interval), Some(last_record_time)) => {
+                now.saturating_duration_since(last_record_time) < interval
+            }
+            _ => false,
+        };
+        self.last_record_time = Some(now);
+
+        if coalesce && !self.undo_stack.is_empty() {
+            trace!("Coalescing state change with previous one");
+            return;
+        }
+        if self.limit == 0 {
+            return;
+        }
+        if self.undo_stack.len() == self.limit {
+            self.undo_stack.pop_front();
+        }
+        self.undo_stack.push_back(prev_state);
+    }
+
+    /// Replace the current state with the last recorded one.
+    ///
+    /// Returns false if there was nothing to undo.
+    pub fn undo(&mut self, state: &mut State) -> bool {
+        match self.undo_stack.pop_back() {
+            Some(prev_state) => {
+                self.redo_stack.push(std::mem::replace(state, prev_state));
+                self.last_record_time = None;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Cancel the last undo.
+    ///
+    /// Returns false if there was nothing to redo.
+    pub fn redo(&mut self, state: &mut State) -> bool {
+        match self.redo_stack.pop() {
+            Some(next_state) => {
+                self.undo_stack
+                    .push_back(std::mem::replace(state, next_state));
+                self.last_record_time = None;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_env_log::test;
+
+    #[test]
+    fn undo_redo() {
+        let mut history = History::new(10);
+        let now = Instant::now();
+        let mut state = 0;
+
+        for new_state in 1..=3 {
+            history.record(state, now);
+            state = new_state;
+        }
+
+        assert!(history.undo(&mut state));
+        assert_eq!(state, 2);
+        assert!(history.undo(&mut state));
+        assert_eq!(state, 1);
+        assert!(history.redo(&mut state));
+        assert_eq!(state, 2);
+
+        // A new change discards the redo stack.
+        history.record(state, now);
+        state = 5;
+        assert!(!history.redo(&mut state));
+        assert_eq!(state, 5);
+
+        assert!(history.undo(&mut state));
+        assert_eq!(state, 2);
+        assert!(history.undo(&mut state));
+        assert_eq!(state, 1);
+        assert!(history.undo(&mut state));
+        assert_eq!(state, 0);
+        assert!(!history.undo(&mut state));
+        assert_eq!(state, 0);
+    }
+
+    #[test]
+    fn history_limit() {
+        let mut history = History::new(2);
+        let now = Instant::now();
+        let mut state = 0;
+
+        for new_state in 1..=5 {
+            history.record(state, now);
+            state = new_state;
+        }
+
+        assert!(history.undo(&mut state));
+        assert!(history.undo(&mut state));
+        assert_eq!(state, 3);
+        assert!(!history.undo(&mut state));
+    }
+
+    #[test]
+    fn coalesce_changes() {
+        let mut history = History::new(10).with_coalescing(Duration::from_millis(500));
+        let start = Instant::now();
+        let mut state = String::new();
+
+        // Typing "abc" quickly, then "d" later
+        history.record(state.clone(), start);
+        state.push('a');
+        history.record(state.clone(), start + Duration::from_millis(100));
+        state.push('b');
+        history.record(state.clone(), start + Duration::from_millis(200));
+        state.push('c');
+        history.record(state.clone(), start + Duration::from_millis(2000));
+        state.push('d');
+
+        assert!(history.undo(&mut state));
+        assert_eq!(state, "abc");
+        assert!(history.undo(&mut state));
+        assert_eq!(state, "");
+        assert!(!history.undo(&mut state));
+    }
+}
@@ -0,0 +1,313 @@
+//! Images of the test window, and snapshot tests comparing them to reference images.
+//!
+//! Reading and writing PNG files (and so snapshot tests) requires the `png` feature.
+
+#[cfg(feature = "png")]
+use std::fs::File;
+#[cfg(feature = "png")]
+use std::io::{BufReader, BufWriter, Error, ErrorKind};
+#[cfg(feature = "png")]
+use std::path::{Path, PathBuf};
+
+/// Environment variable which, if set, makes snapshot assertions overwrite reference images.
+#[cfg(feature = "png")]
+pub const UPDATE_SNAPSHOTS_VAR: &str = "PANORAMIX_UPDATE_SNAPSHOTS";
+
+/// An RGBA image of the test window.
+///
+/// See [`Harness::render_to_image`](crate::test_harness::Harness::render_to_image).
+#[derive(Clone, PartialEq, Eq)]
+pub struct RenderedImage {
+    pub width: usize,
+    pub height: usize,
+    /// Pixels row by row, 4 bytes per pixel (red, green, blue, alpha).
+    pub pixels: Vec<u8>,
+}
+
+/// Result of comparing two images.
+#[derive(Debug, Clone)]
+pub struct ImageDiff {
+    /// Number of pixels which differ by more than the tolerance.
+    pub differing_pixels: usize,
+    /// The largest difference of a color channel between the two images.
+    pub max_difference: u8,
+    /// The compared image, with differing pixels in red and the rest faded.
+    pub diff_image: RenderedImage,
+}
+
+//
+// --- IMPLS
+
+impl std::fmt::Debug for RenderedImage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RenderedImage")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .finish()
+    }
+}
+
+impl RenderedImage {
+    /// Build an image from premultiplied RGBA pixels, as rendered by piet.
+    pub(crate) fn from_premultiplied(width: usize, height: usize, raw_pixels: &[u8]) -> Self {
+        let pixels = raw_pixels
+            .chunks_exact(4)
+            .flat_map(|pixel| {
+                let alpha = pixel[3];
+                let unmultiply = |channel: u8| match alpha {
+                    0 => 0,
+                    alpha => {
+                        ((channel as u32 * 255 + alpha as u32 / 2) / alpha as u32).min(255) as u8
+                    }
+                };
+                vec![
+                    unmultiply(pixel[0]),
+                    unmultiply(pixel[1]),
+                    unmultiply(pixel[2]),
+                    alpha,
+                ]
+            })
+            .collect();
+
+        RenderedImage {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Read a PNG file.
+    ///
+    /// Only 8-bit RGBA images are supported, which is what [`save_png`](Self::save_png) writes.
+    ///
+    /// Requires the `png` feature.
+    #[cfg(feature = "png")]
+    pub fn load_png(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let decoder = png::Decoder::new(BufReader::new(File::open(path)?));
+        let (info, mut reader) = decoder
+            .read_info()
+            .map_err(|error| Error::new(ErrorKind::InvalidData, error))?;
+
+        if info.color_type != png::ColorType::RGBA || info.bit_depth != png::BitDepth::Eight {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "unsupported PNG format {:?} {:?}, expected 8-bit RGBA",
+                    info.color_type, info.bit_depth
+                ),
+            ));
+        }
+
+        let mut pixels = vec![0; info.buffer_size()];
+        reader
+            .next_frame(&mut pixels)
+            .map_err(|error| Error::new(ErrorKind::InvalidData, error))?;
+
+        Ok(RenderedImage {
+            width: info.width as usize,
+            height: info.height as usize,
+            pixels,
+        })
+    }
+
+    /// Write the image to a PNG file.
+    ///
+    /// Requires the `png` feature.
+    #[cfg(feature = "png")]
+    pub fn save_png(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let file = BufWriter::new(File::create(path)?);
+        let mut encoder = png::Encoder::new(file, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::RGBA);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut writer = encoder
+            .write_header()
+            .map_err(|error| Error::new(ErrorKind::Other, error))?;
+        writer
+            .write_image_data(&self.pixels)
+            .map_err(|error| Error::new(ErrorKind::Other, error))
+    }
+
+    /// Returns the color of the pixel at the given coordinates.
+    pub fn pixel(&self, x: usize, y: usize) -> [u8; 4] {
+        let index = (y * self.width + x) * 4;
+        let mut pixel = [0; 4];
+        pixel.copy_from_slice(&self.pixels[index..index + 4]);
+        pixel
+    }
+
+    /// Compare two images.
+    ///
+    /// A pixel differs if one of its color channels differs by more than `tolerance`.
+    /// Images of different sizes differ on every pixel.
+    pub fn compare(&self, reference: &RenderedImage, tolerance: u8) -> ImageDiff {
+        if self.width != reference.width || self.height != reference.height {
+            return ImageDiff {
+                differing_pixels: self.width * self.height,
+                max_difference: u8::MAX,
+                diff_image: self.clone(),
+            };
+        }
+
+        let mut differing_pixels = 0;
+        let mut max_difference = 0;
+        let mut diff_pixels = Vec::with_capacity(self.pixels.len());
+
+        for (pixel, reference_pixel) in self
+            .pixels
+            .chunks_exact(4)
+            .zip(reference.pixels.chunks_exact(4))
+        {
+            let difference = pixel
+                .iter()
+                .zip(reference_pixel)
+                .map(|(channel, reference_channel)| {
+                    (*channel as i16 - *reference_channel as i16).abs() as u8
+                })
+                .max()
+                .unwrap();
+            max_difference = max_difference.max(difference);
+
+            if difference > tolerance {
+                differing_pixels += 1;
+                diff_pixels.extend_from_slice(&[255, 0, 0, 255]);
+            } else {
+                // Faded copy of the pixel, so the differences stand out.
+                diff_pixels.extend(pixel[..3].iter().map(|channel| 192 + channel / 4));
+                diff_pixels.push(255);
+            }
+        }
+
+        ImageDiff {
+            differing_pixels,
+            max_difference,
+            diff_image: RenderedImage {
+                width: self.width,
+                height: self.height,
+                pixels: diff_pixels,
+            },
+        }
+    }
+}
+
+/// Returns the path of the reference image with the given name.
+///
+/// Reference images are stored in `tests/snapshots/images/` in the crate being tested.
+#[cfg(feature = "png")]
+pub fn snapshot_path(name: &str) -> PathBuf {
+    let manifest_dir = std::env::var_os("CARGO_MANIFEST_DIR").unwrap_or_default();
+    PathBuf::from(manifest_dir)
+        .join("tests")
+        .join("snapshots")
+        .join("images")
+        .join(format!("{}.png", name))
+}
+
+/// Compare an image to the reference image with the given name.
+///
+/// If the `PANORAMIX_UPDATE_SNAPSHOTS` environment variable is set, the image is saved as the
+/// new reference image instead. Reference images should be committed, so that the tests
+/// compare against them everywhere.
+///
+/// ## Panic
+///
+/// Panics if there is no reference image (unless `PANORAMIX_UPDATE_SNAPSHOTS` is set).
+///
+/// Panics if more than `max_differing_pixels` pixels differ from the reference image by more
+/// than `tolerance` (see [`RenderedImage::compare`]). In that case, the image is saved next to
+/// the reference image as `<name>.new.png`, and the diff image as `<name>.diff.png`.
+///
+/// Requires the `png` feature.
+#[cfg(feature = "png")]
+pub fn assert_image_snapshot(
+    image: &RenderedImage,
+    name: &str,
+    tolerance: u8,
+    max_differing_pixels: usize,
+) {
+    let path = snapshot_path(name);
+
+    if std::env::var_os(UPDATE_SNAPSHOTS_VAR).is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        image.save_png(&path).unwrap_or_else(|error| {
+            panic!("error: cannot write snapshot {}: {}", path.display(), error)
+        });
+        tracing::warn!("Saved image snapshot {}", path.display());
+        return;
+    }
+
+    if !path.exists() {
+        panic!(
+            "error: no reference image for snapshot '{}' at {}\nrun the test with {}=1 to create it",
+            name,
+            path.display(),
+            UPDATE_SNAPSHOTS_VAR,
+        );
+    }
+
+    let reference = RenderedImage::load_png(&path).unwrap_or_else(|error| {
+        panic!("error: cannot read snapshot {}: {}", path.display(), error)
+    });
+    let diff = image.compare(&reference, tolerance);
+
+    if diff.differing_pixels > max_differing_pixels {
+        let new_path = path.with_extension("new.png");
+        let diff_path = path.with_extension("diff.png");
+        let _ = image.save_png(&new_path);
+        let _ = diff.diff_image.save_png(&diff_path);
+        panic!(
+            "error: image snapshot '{}' doesn't match: {} pixels differ (max difference {}, tolerance {})\nreference: {}\nnew image: {}\ndiff: {}",
+            name,
+            diff.differing_pixels,
+            diff.max_difference,
+            tolerance,
+            path.display(),
+            new_path.display(),
+            diff_path.display(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_env_log::test;
+
+    fn image(pixels: &[[u8; 4]]) -> RenderedImage {
+        RenderedImage {
+            width: pixels.len(),
+            height: 1,
+            pixels: pixels.iter().flatten().copied().collect(),
+        }
+    }
+
+    #[test]
+    fn compare_with_tolerance() {
+        let reference = image(&[[0, 0, 0, 255], [100, 100, 100, 255]]);
+        let new_image = image(&[[2, 0, 0, 255], [100, 150, 100, 255]]);
+
+        let diff = new_image.compare(&reference, 5);
+        assert_eq!(diff.differing_pixels, 1);
+        assert_eq!(diff.max_difference, 50);
+        assert_eq!(diff.diff_image.pixel(1, 0), [255, 0, 0, 255]);
+
+        assert_eq!(new_image.compare(&reference, 50).differing_pixels, 0);
+    }
+
+    #[test]
+    fn unpremultiply() {
+        let image = RenderedImage::from_premultiplied(1, 1, &[64, 0, 128, 128]);
+        assert_eq!(image.pixel(0, 0), [128, 0, 255, 128]);
+    }
+
+    #[test]
+    #[cfg(feature = "png")]
+    fn png_roundtrip() {
+        let image = image(&[[1, 2, 3, 255], [4, 5, 6, 128]]);
+        let path = std::env::temp_dir().join("panoramix_png_roundtrip.png");
+
+        image.save_png(&path).unwrap();
+        assert_eq!(RenderedImage::load_png(&path).unwrap(), image);
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,438 @@
+//! Merged description of the element tree and the widget tree, used to debug apps.
+
+use crate::glue::{DebugState, DruidAppData, WidgetId};
+use crate::tree_text;
+use crate::widget_sequence::{FlexWidget, WidgetSequence};
+
+use druid::kurbo::{Rect, Vec2};
+use druid::Selector;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Debug;
+use std::rc::Rc;
+
+/// What an [`InspectNode`] describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InspectNodeKind {
+    /// An instance of a component, with its props and local state.
+    Component,
+    /// An element returned by a component (eg a `Button`, a `Row`, a list).
+    Element,
+    /// A druid widget created by an element.
+    Widget,
+    /// A mismatch between the virtual DOM and the widgets, eg a list with more widgets than
+    /// items. The message is the node's [`value`](InspectNode::value), and its children are
+    /// the widgets left out of the virtual DOM.
+    Error,
+}
+
+/// A node of the tree returned by [`RootWidget::inspect`](crate::RootWidget::inspect).
+///
+/// The tree mirrors the reconciliation: component and element nodes are the virtual DOM side,
+/// and the widget nodes under them are the druid widgets they created.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InspectNode {
+    pub kind: InspectNodeKind,
+    /// Name of the component, type of the element, or type of the widget.
+    pub name: String,
+    /// Key of the item, for children of an [`ElementList`](crate::elements::ElementList).
+    pub key: Option<String>,
+    /// Stable path of the node in the component tree, for component and element nodes.
+    ///
+    /// The path is made of the names of the components and elements leading to the node,
+    /// separated by `/`, eg `TodoList/Column/Button#1` for the second button of the column.
+    /// Items of a list are identified by their key instead of their index, eg
+    /// `TodoList/List/TodoItem[42]`. Unlike widget ids, paths are the same from one run to
+    /// the next, so tests and debugging tools can use them to address widgets.
+    pub path: Option<String>,
+    /// `Debug` output of the props, for components.
+    pub props: Option<String>,
+    /// `Debug` output of the local state, for components with local state.
+    pub state: Option<String>,
+    /// `Debug` output of each hook state, for components.
+    pub hook_states: Vec<String>,
+    /// For widget nodes created by panoramix (widgets nested inside druid widgets don't have one).
+    pub widget_id: Option<WidgetId>,
+    /// For widget nodes created by panoramix, in the coordinates of their parent. For the root
+    /// node, the scrolled content of the window, in window coordinates.
+    pub layout_rect: Option<Rect>,
+    /// For widget nodes created by panoramix, whether the mouse is over the widget (druid's
+    /// "hot" state).
+    pub is_hot: bool,
+    /// Main value of the widget's `DebugState` (eg the text of a label).
+    pub value: Option<String>,
+    /// Other values of the widget's `DebugState`.
+    pub values: BTreeMap<String, String>,
+    pub children: Vec<InspectNode>,
+}
+
+/// Command sent by the test harness to inspect the root widget.
+pub(crate) const INSPECT: Selector<SharedInspection> = Selector::new("panoramix.inspect");
+
+pub(crate) type SharedInspection = Rc<RefCell<Option<InspectNode>>>;
+
+//
+// --- IMPLS
+
+impl InspectNode {
+    pub(crate) fn new(kind: InspectNodeKind, name: String, children: Vec<InspectNode>) -> Self {
+        InspectNode {
+            kind,
+            name,
+            key: None,
+            path: None,
+            props: None,
+            state: None,
+            hook_states: Vec::new(),
+            widget_id: None,
+            layout_rect: None,
+            is_hot: false,
+            value: None,
+            values: BTreeMap::new(),
+            children,
+        }
+    }
+
+    pub(crate) fn root(children: Vec<InspectNode>) -> Self {
+        let mut children = children;
+        assign_paths(&mut children, "", &mut HashMap::new());
+        Self::new(InspectNodeKind::Element, "Root".to_string(), children)
+    }
+
+    pub(crate) fn component(
+        name: &str,
+        props: &dyn Debug,
+        state: Option<&dyn Debug>,
+        hook_states: Vec<String>,
+        children: Vec<InspectNode>,
+    ) -> Self {
+        InspectNode {
+            props: Some(format!("{:?}", props)),
+            state: state.map(|state| format!("{:?}", state)),
+            hook_states,
+            ..Self::new(InspectNodeKind::Component, name.to_string(), children)
+        }
+    }
+
+    /// Describe an element, eg in [`VirtualDom::inspect`](crate::internals::VirtualDom::inspect).
+    pub fn element(name: &str, children: Vec<InspectNode>) -> Self {
+        Self::new(InspectNodeKind::Element, name.to_string(), children)
+    }
+
+    /// Describe a mismatch between an element and its widgets.
+    pub(crate) fn error(message: String, orphaned_widgets: Vec<InspectNode>) -> Self {
+        InspectNode {
+            value: Some(message),
+            ..Self::new(
+                InspectNodeKind::Error,
+                "Error".to_string(),
+                orphaned_widgets,
+            )
+        }
+    }
+
+    /// Describe a widget created by panoramix.
+    ///
+    /// If `children` is `None`, the children are described from the widget's `DebugState`.
+    pub fn widget(
+        widget: &dyn FlexWidget,
+        data: &DruidAppData,
+        children: Option<Vec<InspectNode>>,
+    ) -> Self {
+        let debug_state = widget.debug_state(data);
+        let children = children.unwrap_or_else(|| {
+            debug_state
+                .children
+                .iter()
+                .map(Self::from_debug_state)
+                .collect()
+        });
+
+        InspectNode {
+            widget_id: Some(widget.widget_id()),
+            layout_rect: Some(widget.layout_rect()),
+            is_hot: widget.is_hot(),
+            ..Self::from_debug_state(&debug_state)
+        }
+        .with_children(children)
+    }
+
+    fn from_debug_state(debug_state: &DebugState) -> Self {
+        let children = debug_state
+            .children
+            .iter()
+            .map(Self::from_debug_state)
+            .collect();
+
+        InspectNode {
+            value: Some(debug_state.main_value.clone()).filter(|value| !value.is_empty()),
+            values: debug_state
+                .other_values
+                .iter()
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect(),
+            ..Self::new(
+                InspectNodeKind::Widget,
+                debug_state.display_name.clone(),
+                children,
+            )
+        }
+    }
+
+    fn with_children(self, children: Vec<InspectNode>) -> Self {
+        InspectNode { children, ..self }
+    }
+
+    /// Set the key of the node, eg for items of a list.
+    pub fn with_key(self, key: &str) -> Self {
+        InspectNode {
+            key: Some(key.to_string()),
+            ..self
+        }
+    }
+
+    /// Returns the first node, in depth-first order, that matches the predicate.
+    pub fn find(&self, predicate: impl Fn(&InspectNode) -> bool + Copy) -> Option<&InspectNode> {
+        if predicate(self) {
+            return Some(self);
+        }
+        self.children.iter().find_map(|child| child.find(predicate))
+    }
+
+    /// Returns the node with the given path (see [`path`](Self::path)).
+    pub fn find_path(&self, path: &str) -> Option<&InspectNode> {
+        self.find(|node| node.path.as_deref() == Some(path))
+    }
+
+    /// Returns the first component node with the given name.
+    pub fn find_component(&self, name: &str) -> Option<&InspectNode> {
+        self.find(|node| node.kind == InspectNodeKind::Component && node.name == name)
+    }
+
+    /// Returns the path of the innermost component or element which created the widget with
+    /// the given id, or one of its ancestors.
+    ///
+    /// See [`path`](Self::path).
+    pub fn path_of(&self, widget_id: WidgetId) -> Option<&str> {
+        self.widget_path(widget_id).flatten()
+    }
+
+    /// Returns `None` if the widget isn't in the subtree, `Some(None)` if it is but no node
+    /// leading to it has a path.
+    fn widget_path(&self, widget_id: WidgetId) -> Option<Option<&str>> {
+        let path = if self.widget_id == Some(widget_id) {
+            None
+        } else {
+            self.children
+                .iter()
+                .find_map(|child| child.widget_path(widget_id))?
+        };
+        Some(path.or_else(|| self.path.as_deref()))
+    }
+
+    /// Returns the layout rect of the widget with the given id, in window coordinates.
+    ///
+    /// `self` must be the root of the tree, since layout rects are relative to the parent
+    /// widget.
+    pub fn window_rect(&self, widget_id: WidgetId) -> Option<Rect> {
+        if self.widget_id == Some(widget_id) {
+            return self.layout_rect;
+        }
+        let origin = self
+            .layout_rect
+            .map_or(Vec2::ZERO, |rect| rect.origin().to_vec2());
+        self.children
+            .iter()
+            .find_map(|child| child.window_rect(widget_id).map(|rect| rect + origin))
+    }
+
+    /// Render the subtree as indented text, one component or element per line.
+    ///
+    /// Unlike the `Debug` output, the text only shows what the user sees (the text of each
+    /// element) and the component boundaries, so it stays the same when internals change. See
+    /// [`Harness::assert_tree_text`](crate::test_harness::Harness::assert_tree_text).
+    pub fn to_text(&self) -> String {
+        if self.path.is_none() && self.kind == InspectNodeKind::Element {
+            // Root node, which only groups the nodes of the root element.
+            tree_text::nodes_to_text(&self.children)
+        } else {
+            tree_text::nodes_to_text(std::slice::from_ref(self))
+        }
+    }
+
+    /// Serialize the tree to pretty-printed JSON.
+    ///
+    /// Requires the `serde` feature.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde_crate::Serialize for InspectNode {
+    fn serialize<S: serde_crate::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde_crate::ser::SerializeStruct;
+
+        let kind = match self.kind {
+            InspectNodeKind::Component => "component",
+            InspectNodeKind::Element => "element",
+            InspectNodeKind::Widget => "widget",
+            InspectNodeKind::Error => "error",
+        };
+        // WidgetId and Rect don't implement Serialize.
+        let widget_id = self.widget_id.map(|id| format!("{:?}", id));
+        let layout_rect = self
+            .layout_rect
+            .map(|rect| [rect.x0, rect.y0, rect.x1, rect.y1]);
+
+        let mut node = serializer.serialize_struct("InspectNode", 13)?;
+        node.serialize_field("kind", kind)?;
+        node.serialize_field("name", &self.name)?;
+        node.serialize_field("key", &self.key)?;
+        node.serialize_field("path", &self.path)?;
+        node.serialize_field("props", &self.props)?;
+        node.serialize_field("state", &self.state)?;
+        node.serialize_field("hook_states", &self.hook_states)?;
+        node.serialize_field("widget_id", &widget_id)?;
+        node.serialize_field("layout_rect", &layout_rect)?;
+        node.serialize_field("is_hot", &self.is_hot)?;
+        node.serialize_field("value", &self.value)?;
+        node.serialize_field("values", &self.values)?;
+        node.serialize_field("children", &self.children)?;
+        node.end()
+    }
+}
+
+/// Returns the name of an element from the type of its virtual DOM, eg `Button` for
+/// `panoramix::elements::button::ButtonData`.
+pub(crate) fn element_name<T: ?Sized>() -> String {
+    let type_name = std::any::type_name::<T>();
+    let type_name = type_name.split('<').next().unwrap();
+    let type_name = type_name.rsplit("::").next().unwrap();
+    type_name
+        .strip_suffix("Data")
+        .unwrap_or(type_name)
+        .to_string()
+}
+
+/// Set the paths of component and element nodes, and of their descendants.
+///
+/// Widget nodes don't have a path; the nodes under them are treated as children of their
+/// parent. `name_counts` counts the siblings with each name, to number them.
+fn assign_paths(
+    nodes: &mut [InspectNode],
+    parent_path: &str,
+    name_counts: &mut HashMap<String, usize>,
+) {
+    for node in nodes {
+        if node.kind == InspectNodeKind::Widget {
+            assign_paths(&mut node.children, parent_path, name_counts);
+            continue;
+        }
+
+        let segment = match &node.key {
+            Some(key) => format!("{}[{}]", node.name, key),
+            None => {
+                let count = name_counts.entry(node.name.clone()).or_insert(0);
+                let segment = match *count {
+                    0 => node.name.clone(),
+                    count => format!("{}#{}", node.name, count),
+                };
+                *count += 1;
+                segment
+            }
+        };
+        let path = if parent_path.is_empty() {
+            segment
+        } else {
+            format!("{}/{}", parent_path, segment)
+        };
+
+        assign_paths(&mut node.children, &path, &mut HashMap::new());
+        node.path = Some(path);
+    }
+}
+
+/// Describe every widget of a sequence.
+pub(crate) fn inspect_widgets(
+    widget_seq: &impl WidgetSequence,
+    data: &DruidAppData,
+) -> Vec<InspectNode> {
+    widget_seq
+        .widgets()
+        .into_iter()
+        .map(|widget| InspectNode::widget(widget, data, None))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_env_log::test;
+
+    #[test]
+    fn names() {
+        assert_eq!(
+            element_name::<crate::elements::internals::ButtonData>(),
+            "Button"
+        );
+        assert_eq!(
+            element_name::<Option<crate::elements::internals::LabelData>>(),
+            "Option"
+        );
+    }
+
+    #[test]
+    fn find_nodes() {
+        let tree = InspectNode::root(vec![InspectNode::component(
+            "App",
+            &(),
+            None,
+            Vec::new(),
+            vec![InspectNode::component(
+                "Counter",
+                &42,
+                Some(&3),
+                Vec::new(),
+                Vec::new(),
+            )],
+        )]);
+
+        let counter = tree.find_component("Counter").unwrap();
+        assert_eq!(counter.path.as_deref(), Some("App/Counter"));
+        assert_eq!(counter.props.as_deref(), Some("42"));
+        assert_eq!(counter.state.as_deref(), Some("3"));
+        assert!(tree.find_component("Missing").is_none());
+    }
+
+    #[test]
+    fn paths() {
+        let label = || InspectNode::element("Label", Vec::new());
+        let label_widget_id = WidgetId::next();
+        let label_widget = InspectNode {
+            widget_id: Some(label_widget_id),
+            ..InspectNode::new(InspectNodeKind::Widget, "Label".to_string(), Vec::new())
+        };
+        let tree = InspectNode::root(vec![InspectNode::element(
+            "Column",
+            vec![InspectNode::new(
+                InspectNodeKind::Widget,
+                "Column".to_string(),
+                vec![
+                    label(),
+                    InspectNode::element("Label", vec![label_widget]),
+                    InspectNode::element("List", vec![label().with_key("a")]),
+                ],
+            )],
+        )]);
+
+        assert!(tree.find_path("Column/Label").is_some());
+        assert!(tree.find_path("Column/Label#1").is_some());
+        assert!(tree.find_path("Column/List/Label[a]").is_some());
+        assert!(tree.find_path("Column/Label#2").is_none());
+
+        assert_eq!(tree.path_of(label_widget_id), Some("Column/Label#1"));
+        assert_eq!(tree.path_of(WidgetId::next()), None);
+    }
+}
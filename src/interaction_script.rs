@@ -0,0 +1,475 @@
+//! Scripts of user interactions, recorded and replayed to reproduce bugs in tests.
+
+use crate::inspector::InspectNode;
+
+use druid::{Event, Modifiers, MouseButton};
+use std::collections::{BTreeMap, HashSet};
+use std::path::PathBuf;
+
+/// A sequence of user interactions with an app.
+///
+/// Scripts are recorded by the test harness (see
+/// [`Harness::start_script_recording`](crate::test_harness::Harness::start_script_recording))
+/// or by a running app (see [`RootHandler::with_script_recording`](crate::RootHandler)), and
+/// replayed with [`Harness::replay_script`](crate::test_harness::Harness::replay_script).
+///
+/// With the `serde` feature, scripts can be saved to JSON files.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate")
+)]
+pub struct InteractionScript {
+    pub steps: Vec<ScriptStep>,
+}
+
+/// A step of an [`InteractionScript`].
+///
+/// Textboxes are identified by their element path (see [`InspectNode::path`]), eg
+/// `TodoList/Column/TextBox`, since widget ids aren't the same from one run to the next.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate", tag = "step", rename_all = "snake_case")
+)]
+pub enum ScriptStep {
+    MouseMove {
+        x: f64,
+        y: f64,
+    },
+    MouseDown {
+        button: ScriptMouseButton,
+    },
+    MouseUp {
+        button: ScriptMouseButton,
+    },
+    /// Scroll with the mouse wheel, at the mouse position.
+    Wheel {
+        dx: f64,
+        dy: f64,
+    },
+    KeyDown {
+        key: String,
+        #[cfg_attr(feature = "serde", serde(default))]
+        mods: ScriptModifiers,
+    },
+    KeyUp {
+        key: String,
+        #[cfg_attr(feature = "serde", serde(default))]
+        mods: ScriptModifiers,
+    },
+    /// Press Tab to move keyboard focus to the next focusable widget.
+    FocusNext,
+    Resize {
+        width: f64,
+        height: f64,
+    },
+    TypeText {
+        path: String,
+        text: String,
+    },
+    Paste {
+        path: String,
+        text: String,
+    },
+    SelectAll {
+        path: String,
+    },
+    Backspace {
+        path: String,
+    },
+    /// Replace the root element with the n-th element passed to
+    /// [`Harness::replay_script`](crate::test_harness::Harness::replay_script).
+    UpdateRootElement {
+        index: usize,
+    },
+    /// The `Debug` output of the root widget's `DebugState`, checked when replaying.
+    Checkpoint {
+        name: String,
+        debug_state: String,
+    },
+}
+
+/// A mouse button, in an [`InteractionScript`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate", rename_all = "snake_case")
+)]
+pub enum ScriptMouseButton {
+    Left,
+    Right,
+    Middle,
+    X1,
+    X2,
+    None,
+}
+
+/// The modifier keys held during a key event, in an [`InteractionScript`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate", default)
+)]
+pub struct ScriptModifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub meta: bool,
+}
+
+/// Records the interactions of a running app, saved when the window is closed.
+///
+/// See [`RootHandler::with_script_recording`](crate::RootHandler).
+// Script recording can only be enabled with the "serde" feature.
+#[cfg_attr(not(feature = "serde"), allow(dead_code))]
+pub(crate) struct ScriptRecorder {
+    pub path: PathBuf,
+    pub script: InteractionScript,
+    /// Keys whose KeyDown was recorded as a text edit, so their KeyUp isn't recorded either.
+    edit_keys: HashSet<String>,
+}
+
+//
+// --- IMPLS
+
+impl InteractionScript {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the names of the checkpoints, in order.
+    pub fn checkpoints(&self) -> impl Iterator<Item = &str> {
+        self.steps.iter().filter_map(|step| match step {
+            ScriptStep::Checkpoint { name, .. } => Some(name.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Serialize the script to pretty-printed JSON.
+    ///
+    /// Requires the `serde` feature.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    /// Requires the `serde` feature.
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+
+    /// Write the script to a JSON file.
+    ///
+    /// Requires the `serde` feature.
+    #[cfg(feature = "serde")]
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        std::fs::write(path, self.to_json())
+    }
+
+    /// Read a script from a JSON file.
+    ///
+    /// Requires the `serde` feature.
+    #[cfg(feature = "serde")]
+    pub fn load(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        Ok(Self::from_json(&json)?)
+    }
+}
+
+impl From<MouseButton> for ScriptMouseButton {
+    fn from(button: MouseButton) -> Self {
+        match button {
+            MouseButton::Left => ScriptMouseButton::Left,
+            MouseButton::Right => ScriptMouseButton::Right,
+            MouseButton::Middle => ScriptMouseButton::Middle,
+            MouseButton::X1 => ScriptMouseButton::X1,
+            MouseButton::X2 => ScriptMouseButton::X2,
+            MouseButton::None => ScriptMouseButton::None,
+        }
+    }
+}
+
+impl From<Modifiers> for ScriptModifiers {
+    fn from(mods: Modifiers) -> Self {
+        ScriptModifiers {
+            shift: mods.shift(),
+            ctrl: mods.ctrl(),
+            alt: mods.alt(),
+            meta: mods.meta(),
+        }
+    }
+}
+
+impl From<ScriptModifiers> for Modifiers {
+    fn from(mods: ScriptModifiers) -> Self {
+        let mut modifiers = Modifiers::empty();
+        modifiers.set(Modifiers::SHIFT, mods.shift);
+        modifiers.set(Modifiers::CONTROL, mods.ctrl);
+        modifiers.set(Modifiers::ALT, mods.alt);
+        modifiers.set(Modifiers::META, mods.meta);
+        modifiers
+    }
+}
+
+impl From<ScriptMouseButton> for MouseButton {
+    fn from(button: ScriptMouseButton) -> Self {
+        match button {
+            ScriptMouseButton::Left => MouseButton::Left,
+            ScriptMouseButton::Right => MouseButton::Right,
+            ScriptMouseButton::Middle => MouseButton::Middle,
+            ScriptMouseButton::X1 => MouseButton::X1,
+            ScriptMouseButton::X2 => MouseButton::X2,
+            ScriptMouseButton::None => MouseButton::None,
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "serde"), allow(dead_code))]
+impl ScriptRecorder {
+    pub fn new(path: PathBuf) -> Self {
+        ScriptRecorder {
+            path,
+            script: InteractionScript::new(),
+            edit_keys: HashSet::new(),
+        }
+    }
+
+    /// Record a druid event received by the root widget, if it's a user interaction.
+    pub fn record_event(&mut self, event: &Event) {
+        let step = match event {
+            Event::MouseMove(mouse_event) => ScriptStep::MouseMove {
+                x: mouse_event.window_pos.x,
+                y: mouse_event.window_pos.y,
+            },
+            Event::MouseDown(mouse_event) => ScriptStep::MouseDown {
+                button: mouse_event.button.into(),
+            },
+            Event::MouseUp(mouse_event) => ScriptStep::MouseUp {
+                button: mouse_event.button.into(),
+            },
+            Event::Wheel(mouse_event) => ScriptStep::Wheel {
+                dx: mouse_event.wheel_delta.x,
+                dy: mouse_event.wheel_delta.y,
+            },
+            Event::KeyDown(key_event) => ScriptStep::KeyDown {
+                key: key_event.key.to_string(),
+                mods: key_event.mods.into(),
+            },
+            Event::KeyUp(key_event) => {
+                let key = key_event.key.to_string();
+                if self.edit_keys.remove(&key) {
+                    return;
+                }
+                ScriptStep::KeyUp {
+                    key,
+                    mods: key_event.mods.into(),
+                }
+            }
+            Event::WindowSize(size) => ScriptStep::Resize {
+                width: size.width,
+                height: size.height,
+            },
+            _ => return,
+        };
+        self.script.steps.push(step);
+    }
+
+    /// Record the changes made by `event` to the text of each textbox, given the
+    /// [`textbox_texts`] before and after the event.
+    ///
+    /// Text edits are recorded as `TypeText`, `Paste`, `SelectAll` and `Backspace` steps,
+    /// instead of the key presses which made them. Text which doesn't come from key events
+    /// (eg a paste from the menu) is recorded too.
+    pub fn record_text_edits(
+        &mut self,
+        event: &Event,
+        old_texts: &BTreeMap<String, String>,
+        new_texts: &BTreeMap<String, String>,
+    ) {
+        let mut edit_steps = Vec::new();
+        for (path, new_text) in new_texts {
+            let old_text = match old_texts.get(path) {
+                Some(old_text) if old_text != new_text => old_text,
+                _ => continue,
+            };
+            edit_steps.extend(text_edit_steps(path, old_text, new_text));
+        }
+        if edit_steps.is_empty() {
+            return;
+        }
+
+        // The KeyDown step was recorded by record_event.
+        if let Event::KeyDown(key_event) = event {
+            self.script.steps.pop();
+            self.edit_keys.insert(key_event.key.to_string());
+        }
+        self.script.steps.extend(edit_steps);
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn save(&self) {
+        match self.script.save(&self.path) {
+            Ok(()) => tracing::info!("Saved interaction script {}", self.path.display()),
+            Err(error) => tracing::error!(
+                "Cannot save interaction script {}: {}",
+                self.path.display(),
+                error
+            ),
+        }
+    }
+}
+
+/// Returns the text of each textbox of the tree, by element path.
+pub(crate) fn textbox_texts(tree: &InspectNode) -> BTreeMap<String, String> {
+    let mut texts = BTreeMap::new();
+    let mut stack = vec![tree];
+    while let Some(node) = stack.pop() {
+        if let (Some(path), "TextBox") = (&node.path, node.name.as_str()) {
+            let text = node
+                .find(|node| node.widget_id.is_some())
+                .and_then(|widget| widget.value.clone())
+                .unwrap_or_default();
+            texts.insert(path.clone(), text);
+        }
+        stack.extend(&node.children);
+    }
+    texts
+}
+
+/// Steps changing the text of a textbox from `old_text` to `new_text`, assuming the cursor is
+/// at the end of the text.
+fn text_edit_steps(path: &str, old_text: &str, new_text: &str) -> Vec<ScriptStep> {
+    let path = path.to_string();
+    if let Some(added) = new_text.strip_prefix(old_text) {
+        let text = added.to_string();
+        return if added.chars().count() == 1 {
+            vec![ScriptStep::TypeText { path, text }]
+        } else {
+            vec![ScriptStep::Paste { path, text }]
+        };
+    }
+    if let Some(removed) = old_text.strip_prefix(new_text) {
+        if removed.chars().count() == 1 {
+            return vec![ScriptStep::Backspace { path }];
+        }
+    }
+
+    let select_all = ScriptStep::SelectAll { path: path.clone() };
+    if new_text.is_empty() {
+        vec![select_all, ScriptStep::Backspace { path }]
+    } else {
+        let text = new_text.to_string();
+        vec![select_all, ScriptStep::Paste { path, text }]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use druid::{KbKey, KeyEvent, RawMods};
+    use test_env_log::test;
+
+    #[test]
+    fn record_text_edits() {
+        let texts = |text: &str| {
+            let mut texts = BTreeMap::new();
+            texts.insert("App/TextBox".to_string(), text.to_string());
+            texts
+        };
+        let key_a = KeyEvent::for_test(RawMods::None, "a");
+        let key_v = KeyEvent::for_test(RawMods::Ctrl, "v");
+        let backspace = KeyEvent::for_test(RawMods::None, KbKey::Backspace);
+        let path = || "App/TextBox".to_string();
+
+        let mut recorder = ScriptRecorder::new(PathBuf::new());
+        let mut edit = |event: Event, old_text: &str, new_text: &str| {
+            recorder.record_event(&event);
+            recorder.record_text_edits(&event, &texts(old_text), &texts(new_text));
+        };
+        edit(Event::KeyDown(key_a.clone()), "", "a");
+        edit(Event::KeyUp(key_a), "a", "a");
+        edit(Event::KeyDown(key_v.clone()), "a", "a");
+        edit(Event::KeyUp(key_v), "a", "abc");
+        edit(Event::KeyDown(backspace.clone()), "abc", "ab");
+        edit(Event::KeyUp(backspace), "ab", "ab");
+
+        assert_eq!(
+            recorder.script.steps,
+            vec![
+                ScriptStep::TypeText {
+                    path: path(),
+                    text: "a".to_string(),
+                },
+                ScriptStep::KeyDown {
+                    key: "v".to_string(),
+                    mods: ScriptModifiers {
+                        ctrl: true,
+                        ..Default::default()
+                    },
+                },
+                ScriptStep::KeyUp {
+                    key: "v".to_string(),
+                    mods: ScriptModifiers {
+                        ctrl: true,
+                        ..Default::default()
+                    },
+                },
+                ScriptStep::Paste {
+                    path: path(),
+                    text: "bc".to_string(),
+                },
+                ScriptStep::Backspace { path: path() },
+            ]
+        );
+
+        assert_eq!(
+            text_edit_steps("App/TextBox", "abc", "xyz"),
+            vec![
+                ScriptStep::SelectAll { path: path() },
+                ScriptStep::Paste {
+                    path: path(),
+                    text: "xyz".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn json_roundtrip() {
+        let script = InteractionScript {
+            steps: vec![
+                ScriptStep::MouseMove { x: 10.0, y: 20.0 },
+                ScriptStep::MouseDown {
+                    button: ScriptMouseButton::Left,
+                },
+                ScriptStep::KeyDown {
+                    key: "a".to_string(),
+                    mods: ScriptModifiers {
+                        ctrl: true,
+                        ..Default::default()
+                    },
+                },
+                ScriptStep::TypeText {
+                    path: "App/Column/TextBox".to_string(),
+                    text: "hello".to_string(),
+                },
+                ScriptStep::Checkpoint {
+                    name: "typed".to_string(),
+                    debug_state: "Label(\"hello\")".to_string(),
+                },
+            ],
+        };
+
+        let json = script.to_json();
+        assert!(json.contains("\"step\": \"mouse_move\""));
+        assert_eq!(InteractionScript::from_json(&json).unwrap(), script);
+        assert_eq!(script.checkpoints().collect::<Vec<_>>(), vec!["typed"]);
+    }
+}
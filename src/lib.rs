@@ -10,17 +10,20 @@
 //! Here is our "hello world" example:
 //!
 //! ```no_run
-//! use panoramix::elements::{Button, Label};
-//! use panoramix::{component, Column, CompCtx, Element, Metadata, NoEvent, RootHandler};
+//! use panoramix::elements::{Button, ComponentOutput, Label};
+//! use panoramix::{component, Column, CompCtx, Element, NoEvent, RootHandler};
 //!
 //! #[component]
 //! fn HelloBox(ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
 //!     let md = ctx.use_metadata::<NoEvent, ()>();
-//!     Column!(
-//!         Label::new("Hello world!"),
-//!         Button::new("Say hello").on_click(md, |_, _| {
-//!             println!("Hello world");
-//!         })
+//!     ComponentOutput::new(
+//!         md,
+//!         Column!(
+//!             Label::new("Hello world!"),
+//!             Button::new("Say hello").on_click(md, |_, _| {
+//!                 println!("Hello world");
+//!             })
+//!         ),
 //!     )
 //! }
 //!
@@ -69,12 +72,29 @@
 //! For information on how to write a component, see [these tutorials](tutorials).
 
 mod ctx;
+mod effects;
+mod element_path;
 mod element_tree;
+mod external;
+mod fuzz;
 mod glue;
+mod history;
+mod image_snapshot;
+mod inspector;
+mod interaction_script;
 mod metadata;
+mod persistence;
+mod profiler;
+mod reducer;
 mod root_handler;
+mod state_diff;
+mod store;
+mod test_queries;
+mod time_travel;
+mod tree_text;
 mod widget_sequence;
 
+pub mod screenshot;
 pub mod test_harness;
 
 pub mod elements;
@@ -85,9 +105,15 @@ pub mod flex;
 pub use panoramix_derive::component;
 
 pub use crate::ctx::CompCtx;
+pub use effects::{Effect, Effects, TimerMessage};
 pub use element_tree::{Element, ElementExt};
-pub use metadata::{Metadata, NoEvent};
+pub use external::ExternalHandle;
+pub use inspector::{InspectNode, InspectNodeKind};
+pub use metadata::{Metadata, NoEvent, StateHandle};
+pub use profiler::{ComponentProfile, ProfileReport, RenderCause, RenderCounts};
+pub use reducer::Reducer;
 pub use root_handler::{PlatformError, RootHandler, RootWidget};
+pub use time_travel::{Recording, TimeTravel, Transition, TIME_TRAVEL};
 
 /// Traits and type used internally to compute the GUI.
 ///
@@ -30,3 +30,32 @@ pub struct NoState;
 pub struct Metadata<ComponentEvent, ComponentState> {
     _marker: std::marker::PhantomData<(ComponentEvent, ComponentState)>,
 }
+
+/// Handle to a state slot, returned by [`CompCtx::use_state`](crate::CompCtx::use_state).
+///
+/// **Note:** Like [`Metadata`], this type doesn't store the state itself; it only stores the
+/// index of the slot. Pass it to [`ElementExt::on_state`](crate::ElementExt::on_state) to
+/// modify the slot in an event callback.
+#[derive(Derivative)]
+#[derivative(
+    Clone(bound = ""),
+    Copy(bound = ""),
+    Debug(bound = ""),
+    PartialEq(bound = ""),
+    Eq(bound = ""),
+    Hash(bound = "")
+)]
+pub struct StateHandle<T> {
+    pub(crate) index: usize,
+    #[derivative(Debug = "ignore")]
+    _marker: std::marker::PhantomData<T>,
+}
+
+impl<T> StateHandle<T> {
+    pub(crate) fn new(index: usize) -> Self {
+        StateHandle {
+            index,
+            _marker: Default::default(),
+        }
+    }
+}
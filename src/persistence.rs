@@ -0,0 +1,336 @@
+//! Saving the app-wide store and the local state of components to disk, and restoring them
+//! when the app starts.
+
+// Persistence can only be enabled with the "serde" feature.
+#![cfg_attr(not(feature = "serde"), allow(dead_code))]
+
+use druid::TimerToken;
+use std::any::Any;
+#[cfg(feature = "serde")]
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+type LoadFn = fn(&Path) -> std::io::Result<Box<dyn Any>>;
+type SaveFn = fn(&dyn Any, &Path) -> std::io::Result<()>;
+
+/// Serialized local states of component instances, by path in the inspector tree.
+#[cfg(feature = "serde")]
+pub(crate) type ComponentStates = std::collections::BTreeMap<String, serde_json::Value>;
+
+#[cfg(feature = "serde")]
+struct BuildStates {
+    /// States loaded from disk and not restored yet.
+    saved: ComponentStates,
+    /// States of the instances built so far.
+    states: ComponentStates,
+}
+
+#[cfg(feature = "serde")]
+thread_local! {
+    static BUILD_STATES: RefCell<Option<BuildStates>> = RefCell::new(None);
+}
+
+/// Saves the store (or the states of components) to a file when it changes.
+///
+/// Saves are debounced: after a save, the next one is delayed until `SAVE_INTERVAL` has elapsed.
+pub(crate) struct Persistence {
+    path: PathBuf,
+    load_fn: LoadFn,
+    save_fn: SaveFn,
+    has_unsaved_changes: bool,
+    last_save_time: Option<Instant>,
+    pub timer: TimerToken,
+}
+
+impl Persistence {
+    /// Minimum delay between two saves.
+    pub const SAVE_INTERVAL: Duration = Duration::from_millis(500);
+
+    #[cfg(feature = "serde")]
+    pub fn new<S>(path: PathBuf) -> Self
+    where
+        S: serde_crate::Serialize + serde_crate::de::DeserializeOwned + 'static,
+    {
+        Persistence {
+            path,
+            load_fn: load_store::<S>,
+            save_fn: save_store::<S>,
+            has_unsaved_changes: false,
+            last_save_time: None,
+            timer: TimerToken::INVALID,
+        }
+    }
+
+    /// Returns the saved store, if there is one.
+    pub fn load(&self) -> Option<Box<dyn Any>> {
+        match (self.load_fn)(&self.path) {
+            Ok(store) => {
+                debug!("Restored state from {}", self.path.display());
+                Some(store)
+            }
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => None,
+            Err(error) => {
+                warn!("Cannot restore state from {}: {}", self.path.display(), error);
+                None
+            }
+        }
+    }
+
+    pub fn mark_changed(&mut self) {
+        self.has_unsaved_changes = true;
+    }
+
+    /// Save the store if it changed and the last save is old enough.
+    ///
+    /// If a save is postponed, returns how long to wait before trying again.
+    pub fn save_if_due(&mut self, store: &dyn Any, now: Instant) -> Option<Duration> {
+        if !self.has_unsaved_changes {
+            return None;
+        }
+        if let Some(last_save_time) = self.last_save_time {
+            let elapsed = now.saturating_duration_since(last_save_time);
+            if elapsed < Self::SAVE_INTERVAL {
+                return Some(Self::SAVE_INTERVAL - elapsed);
+            }
+        }
+        self.last_save_time = Some(now);
+        self.save(store);
+        None
+    }
+
+    /// Save the store now if it changed.
+    pub fn flush(&mut self, store: &dyn Any) {
+        if self.has_unsaved_changes {
+            self.save(store);
+        }
+    }
+
+    fn save(&mut self, store: &dyn Any) {
+        self.has_unsaved_changes = false;
+        match (self.save_fn)(store, &self.path) {
+            Ok(()) => debug!("Saved state to {}", self.path.display()),
+            Err(error) => warn!("Cannot save state to {}: {}", self.path.display(), error),
+        }
+    }
+}
+
+/// Saves the local states of components to a file when they change.
+#[cfg(feature = "serde")]
+pub(crate) struct ComponentPersistence {
+    pub persistence: Persistence,
+    /// States loaded from disk, until the first build restores them.
+    saved_states: ComponentStates,
+    /// States of the last build, or the saved states before the first build.
+    states: Option<ComponentStates>,
+}
+
+#[cfg(feature = "serde")]
+impl ComponentPersistence {
+    pub fn new(path: PathBuf) -> Self {
+        ComponentPersistence {
+            persistence: Persistence::new::<ComponentStates>(path),
+            saved_states: ComponentStates::new(),
+            states: None,
+        }
+    }
+
+    /// Load the saved states, to restore them in the next build.
+    pub fn load(&mut self) {
+        if let Some(states) = self.persistence.load() {
+            let states = *states.downcast::<ComponentStates>().unwrap();
+            self.saved_states = states.clone();
+            self.states = Some(states);
+        }
+    }
+
+    /// Run `build`, restoring the saved states, and record the states of the instances built.
+    ///
+    /// If there are no saved states, the states of the first build are the default ones, and
+    /// aren't saved.
+    pub fn build<R>(&mut self, build: impl FnOnce() -> R) -> R {
+        let (output, states) = build_with_component_states(&mut self.saved_states, build);
+        // Saved states of instances that no longer exist are dropped.
+        self.saved_states.clear();
+        if self.states.is_some() && self.states.as_ref() != Some(&states) {
+            self.persistence.mark_changed();
+        }
+        self.states = Some(states);
+        output
+    }
+
+    /// See [`Persistence::save_if_due`].
+    pub fn save_if_due(&mut self, now: Instant) -> Option<Duration> {
+        let states = self.states.as_ref()?;
+        self.persistence.save_if_due(states, now)
+    }
+
+    pub fn flush(&mut self) {
+        if let Some(states) = &self.states {
+            self.persistence.flush(states);
+        }
+    }
+}
+
+/// Run `build`, restoring the local states in `saved`, and return the local states of the
+/// component instances that called [`CompCtx::use_persisted_metadata`](crate::CompCtx::use_persisted_metadata).
+///
+/// Each saved state is only restored once; the states restored by `build` are removed from
+/// `saved`.
+#[cfg(feature = "serde")]
+pub(crate) fn build_with_component_states<R>(
+    saved: &mut ComponentStates,
+    build: impl FnOnce() -> R,
+) -> (R, ComponentStates) {
+    let build_states = BuildStates {
+        saved: std::mem::take(saved),
+        states: ComponentStates::new(),
+    };
+    let prev_states = BUILD_STATES.with(|states| states.replace(Some(build_states)));
+    let output = build();
+    let build_states = BUILD_STATES
+        .with(|states| states.replace(prev_states))
+        .unwrap();
+    *saved = build_states.saved;
+    (output, build_states.states)
+}
+
+/// Record the local state of the instance at `path`, and return its saved state, if any.
+///
+/// Does nothing outside of [`build_with_component_states`].
+#[cfg(feature = "serde")]
+pub(crate) fn persist_state<S>(path: &str, state: &S) -> Option<S>
+where
+    S: serde_crate::Serialize + serde_crate::de::DeserializeOwned,
+{
+    BUILD_STATES.with(|states| {
+        let mut states = states.borrow_mut();
+        let states = states.as_mut()?;
+
+        let restored_state = states.saved.remove(path).and_then(|value| {
+            serde_json::from_value(value)
+                .map_err(|error| warn!("Cannot restore the local state of {}: {}", path, error))
+                .ok()
+        });
+        match serde_json::to_value(restored_state.as_ref().unwrap_or(state)) {
+            Ok(value) => {
+                states.states.insert(path.to_string(), value);
+            }
+            Err(error) => warn!("Cannot save the local state of {}: {}", path, error),
+        }
+        restored_state
+    })
+}
+
+#[cfg(feature = "serde")]
+fn load_store<S: serde_crate::de::DeserializeOwned + 'static>(
+    path: &Path,
+) -> std::io::Result<Box<dyn Any>> {
+    let json = std::fs::read_to_string(path)?;
+    let store: S = serde_json::from_str(&json)?;
+    Ok(Box::new(store))
+}
+
+#[cfg(feature = "serde")]
+fn save_store<S: serde_crate::Serialize + 'static>(
+    store: &dyn Any,
+    path: &Path,
+) -> std::io::Result<()> {
+    let store = store.downcast_ref::<S>().expect(&format!(
+        "internal type error: store is not of type {}",
+        std::any::type_name::<S>(),
+    ));
+    let json = serde_json::to_string_pretty(store)?;
+    std::fs::write(path, json)
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+    use test_env_log::test;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "panoramix_{}_{}.json",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn save_and_load() {
+        let path = temp_path("save_and_load");
+        let mut persistence = Persistence::new::<Vec<String>>(path.clone());
+        assert!(persistence.load().is_none());
+
+        let store = vec!["Hello".to_string(), "world".to_string()];
+        let now = Instant::now();
+
+        // Unchanged store isn't saved
+        assert_eq!(persistence.save_if_due(&store, now), None);
+        assert!(persistence.load().is_none());
+
+        persistence.mark_changed();
+        assert_eq!(persistence.save_if_due(&store, now), None);
+
+        let loaded_store = persistence.load().unwrap();
+        assert_eq!(loaded_store.downcast_ref::<Vec<String>>(), Some(&store));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn debounce_saves() {
+        let path = temp_path("debounce_saves");
+        let mut persistence = Persistence::new::<i32>(path.clone());
+        let now = Instant::now();
+
+        persistence.mark_changed();
+        persistence.save_if_due(&1, now);
+
+        // Too soon after the first save
+        persistence.mark_changed();
+        let delay = persistence.save_if_due(&2, now + Duration::from_millis(100));
+        assert_eq!(delay, Some(Duration::from_millis(400)));
+        assert_eq!(persistence.load().unwrap().downcast_ref::<i32>(), Some(&1));
+
+        persistence.flush(&2);
+        assert_eq!(persistence.load().unwrap().downcast_ref::<i32>(), Some(&2));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn restore_component_states() {
+        let mut saved = ComponentStates::new();
+        saved.insert("App/Counter".to_string(), serde_json::json!(42));
+
+        let (restored_states, states) = build_with_component_states(&mut saved, || {
+            vec![
+                persist_state("App/Counter", &0),
+                persist_state("App/Counter#1", &5),
+            ]
+        });
+        assert_eq!(restored_states, vec![Some(42), None]);
+        assert!(saved.is_empty());
+        assert_eq!(states.get("App/Counter"), Some(&serde_json::json!(42)));
+        assert_eq!(states.get("App/Counter#1"), Some(&serde_json::json!(5)));
+
+        // Outside of a build, states are neither restored nor recorded.
+        assert_eq!(persist_state("App/Counter", &0), None);
+    }
+
+    #[test]
+    fn invalid_file() {
+        let path = temp_path("invalid_file");
+        std::fs::write(&path, "not json").unwrap();
+
+        let persistence = Persistence::new::<i32>(path.clone());
+        assert!(persistence.load().is_none());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,539 @@
+//! Per-component statistics about builds and reconciliations.
+//!
+//! See [`RootWidget::with_profiler`](crate::RootWidget::with_profiler).
+
+use crate::inspector::{InspectNode, InspectNodeKind};
+use crate::state_diff::{self, StateChange};
+
+use druid::WidgetId;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{Debug, Display};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// Why a component instance was called.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RenderCause {
+    /// The instance was called for the first time.
+    Created,
+    /// The props or the state of the instance changed.
+    ///
+    /// Each change is formatted as `path: old_value -> new_value`.
+    Changed {
+        props: Vec<String>,
+        state: Vec<String>,
+    },
+    /// Neither the props nor the state changed; the instance was called because its parent
+    /// was rebuilt (or because a selected slice of the store changed).
+    ParentRebuilt,
+}
+
+/// Statistics about a single component instance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentProfile {
+    /// The name of the component.
+    pub name: &'static str,
+    /// The path of the instance in the element tree, eg `App/Column/TodoItem#2` (see
+    /// [`InspectNode::path`]).
+    pub instance: String,
+    pub build_count: usize,
+    /// Time spent in the component function and building its elements, excluding child
+    /// components.
+    pub build_time: Duration,
+    pub reconcile_count: usize,
+    /// Time spent reconciling the elements of the component, excluding child components.
+    pub reconcile_time: Duration,
+    /// Number of widgets created by the elements of the component, excluding child components.
+    pub widgets_created: usize,
+    /// Number of widgets removed by the elements of the component, excluding child components.
+    ///
+    /// When an instance is removed, its remaining widgets are counted as removed.
+    pub widgets_destroyed: usize,
+    /// Why the instance was called, for each build.
+    pub render_causes: Vec<RenderCause>,
+}
+
+/// Statistics about every component instance built while profiling.
+///
+/// Implements `Display`, to print the statistics as a table.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProfileReport {
+    /// Every profiled instance, ordered by path.
+    pub components: Vec<ComponentProfile>,
+}
+
+/// Builds and widget changes since a checkpoint, in a test.
+///
+/// See [`Harness::render_counts`](crate::test_harness::Harness::render_counts).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RenderCounts {
+    /// Number of builds of each instance built since the checkpoint, by instance path (see
+    /// [`ComponentProfile::instance`]).
+    pub builds: BTreeMap<String, usize>,
+    /// Number of widgets in the window that weren't there at the checkpoint.
+    pub widgets_created: usize,
+    /// Number of widgets that were in the window at the checkpoint, and were removed since.
+    pub widgets_destroyed: usize,
+}
+
+#[derive(Debug)]
+struct InstanceRecord {
+    profile: ComponentProfile,
+    last_props: String,
+    last_state: String,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Profiler {
+    instances: BTreeMap<Rc<str>, InstanceRecord>,
+    /// Widgets of each instance at the last call to [`Profiler::record_widgets`].
+    instance_widgets: HashMap<Rc<str>, HashSet<WidgetId>>,
+}
+
+pub(crate) type SharedProfiler = Rc<RefCell<Profiler>>;
+
+/// Number of builds of each instance, by path; counted by the test harness, with or without
+/// a profiler.
+pub(crate) type SharedBuildCounts = Rc<RefCell<BTreeMap<String, usize>>>;
+
+struct TimingFrame {
+    start: Instant,
+    child_time: Duration,
+}
+
+struct ActiveProfiler {
+    profiler: SharedProfiler,
+    timing_stack: Vec<TimingFrame>,
+}
+
+thread_local! {
+    // Like the store, the profiler is made available to components for the duration of a
+    // build or a reconcile.
+    static ACTIVE_PROFILER: RefCell<Option<ActiveProfiler>> = RefCell::new(None);
+    static ACTIVE_BUILD_COUNTS: RefCell<Option<SharedBuildCounts>> = RefCell::new(None);
+}
+
+//
+// --- IMPLS
+
+impl ProfileReport {
+    /// Returns the statistics of the given instance.
+    pub fn get(&self, instance: &str) -> Option<&ComponentProfile> {
+        self.components
+            .iter()
+            .find(|profile| profile.instance == instance)
+    }
+
+    /// Returns the statistics of every instance of the given component.
+    pub fn instances_of<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> impl Iterator<Item = &'a ComponentProfile> + 'a {
+        self.components
+            .iter()
+            .filter(move |profile| profile.name == name)
+    }
+}
+
+impl RenderCounts {
+    /// Returns the number of builds of the given instance since the checkpoint.
+    pub fn builds_of(&self, instance: &str) -> usize {
+        self.builds.get(instance).copied().unwrap_or(0)
+    }
+
+    /// Returns the paths of the instances built since the checkpoint, in path order.
+    pub fn rebuilt_instances(&self) -> Vec<&str> {
+        self.builds.keys().map(String::as_str).collect()
+    }
+}
+
+impl Display for ProfileReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let instance_width = self
+            .components
+            .iter()
+            .map(|profile| profile.instance.len())
+            .chain(std::iter::once("Instance".len()))
+            .max()
+            .unwrap();
+
+        writeln!(
+            f,
+            "{:<width$} | {:>6} | {:>12} | {:>10} | {:>14} | {:>7} | {:>9} | Last cause",
+            "Instance",
+            "Builds",
+            "Build time",
+            "Reconciles",
+            "Reconcile time",
+            "Created",
+            "Destroyed",
+            width = instance_width,
+        )?;
+        for profile in &self.components {
+            let last_cause = match profile.render_causes.last() {
+                Some(RenderCause::Created) => "created".to_string(),
+                Some(RenderCause::Changed { props, state }) => {
+                    let changes: Vec<_> = props.iter().chain(state.iter()).cloned().collect();
+                    changes.join("; ")
+                }
+                Some(RenderCause::ParentRebuilt) => "parent rebuilt".to_string(),
+                None => String::new(),
+            };
+            writeln!(
+                f,
+                "{:<width$} | {:>6} | {:>12} | {:>10} | {:>14} | {:>7} | {:>9} | {}",
+                profile.instance,
+                profile.build_count,
+                format!("{:?}", profile.build_time),
+                profile.reconcile_count,
+                format!("{:?}", profile.reconcile_time),
+                profile.widgets_created,
+                profile.widgets_destroyed,
+                last_cause,
+                width = instance_width,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Profiler {
+    pub fn report(&self) -> ProfileReport {
+        ProfileReport {
+            components: self
+                .instances
+                .values()
+                .map(|record| record.profile.clone())
+                .collect(),
+        }
+    }
+
+    /// Count the widgets created and removed by each instance since the last call.
+    ///
+    /// Called with the inspector tree of the window after each reconciliation. Widgets are
+    /// compared by id, so a widget replaced by another one counts as created and removed.
+    pub fn record_widgets(&mut self, inspection: &InspectNode) {
+        let mut widgets_by_path = HashMap::new();
+        collect_instance_widgets(inspection, None, &mut widgets_by_path);
+
+        let mut instance_widgets = HashMap::new();
+        for (path, widgets) in widgets_by_path {
+            if let Some((instance, _)) = self.instances.get_key_value(path) {
+                instance_widgets.insert(instance.clone(), widgets);
+            }
+        }
+
+        let no_widgets = HashSet::new();
+        for (instance, widgets) in &instance_widgets {
+            let prev_widgets = self.instance_widgets.get(instance).unwrap_or(&no_widgets);
+            let created = widgets.difference(prev_widgets).count();
+            if let Some(profile) = self.record_mut(instance) {
+                profile.widgets_created += created;
+            }
+        }
+        let prev_instance_widgets = std::mem::take(&mut self.instance_widgets);
+        for (instance, prev_widgets) in &prev_instance_widgets {
+            let widgets = instance_widgets.get(instance).unwrap_or(&no_widgets);
+            let destroyed = prev_widgets.difference(widgets).count();
+            if let Some(profile) = self.record_mut(instance) {
+                profile.widgets_destroyed += destroyed;
+            }
+        }
+        self.instance_widgets = instance_widgets;
+    }
+
+    fn record_mut(&mut self, instance: &str) -> Option<&mut ComponentProfile> {
+        self.instances
+            .get_mut(instance)
+            .map(|record| &mut record.profile)
+    }
+}
+
+/// Run `f` with the profiler available to components.
+pub(crate) fn with_profiler<R>(profiler: &Option<SharedProfiler>, f: impl FnOnce() -> R) -> R {
+    let profiler = match profiler {
+        Some(profiler) => profiler.clone(),
+        None => return f(),
+    };
+
+    let prev_profiler = ACTIVE_PROFILER.with(|active_profiler| {
+        active_profiler.replace(Some(ActiveProfiler {
+            profiler,
+            timing_stack: Vec::new(),
+        }))
+    });
+    let output = f();
+    ACTIVE_PROFILER.with(|active_profiler| active_profiler.replace(prev_profiler));
+    output
+}
+
+/// Run `f`, counting the builds of each component instance in `build_counts`.
+pub(crate) fn with_build_counts<R>(
+    build_counts: &Option<SharedBuildCounts>,
+    f: impl FnOnce() -> R,
+) -> R {
+    let build_counts = match build_counts {
+        Some(build_counts) => build_counts.clone(),
+        None => return f(),
+    };
+
+    let prev_build_counts = ACTIVE_BUILD_COUNTS
+        .with(|active_build_counts| active_build_counts.replace(Some(build_counts)));
+    let output = f();
+    ACTIVE_BUILD_COUNTS.with(|active_build_counts| active_build_counts.replace(prev_build_counts));
+    output
+}
+
+/// Count a build of the component instance with the given element path, if builds are
+/// counted.
+pub(crate) fn count_build(path: &str) {
+    ACTIVE_BUILD_COUNTS.with(|active_build_counts| {
+        if let Some(build_counts) = &*active_build_counts.borrow() {
+            *build_counts
+                .borrow_mut()
+                .entry(path.to_string())
+                .or_default() += 1;
+        }
+    });
+}
+
+/// Start profiling the build of the component instance with the given element path, and
+/// return the path.
+///
+/// Returns `None` if profiling is disabled.
+pub(crate) fn enter_build(
+    name: &'static str,
+    path: &Rc<str>,
+    props: &dyn Debug,
+    state: &dyn Debug,
+) -> Option<Rc<str>> {
+    ACTIVE_PROFILER.with(|active_profiler| {
+        let mut active_profiler = active_profiler.borrow_mut();
+        let active_profiler = active_profiler.as_mut()?;
+
+        let instance = path.clone();
+        let props = format!("{:#?}", props);
+        let state = format!("{:#?}", state);
+        let mut profiler = active_profiler.profiler.borrow_mut();
+        match profiler.instances.get_mut(&instance) {
+            Some(record) => {
+                let cause = render_cause(record, &props, &state);
+                record.profile.render_causes.push(cause);
+                record.last_props = props;
+                record.last_state = state;
+            }
+            None => {
+                profiler.instances.insert(
+                    instance.clone(),
+                    InstanceRecord {
+                        profile: ComponentProfile {
+                            name,
+                            instance: instance.to_string(),
+                            build_count: 0,
+                            build_time: Duration::default(),
+                            reconcile_count: 0,
+                            reconcile_time: Duration::default(),
+                            widgets_created: 0,
+                            widgets_destroyed: 0,
+                            render_causes: vec![RenderCause::Created],
+                        },
+                        last_props: props,
+                        last_state: state,
+                    },
+                );
+            }
+        }
+        drop(profiler);
+
+        active_profiler.timing_stack.push(TimingFrame {
+            start: Instant::now(),
+            child_time: Duration::default(),
+        });
+        Some(instance)
+    })
+}
+
+/// Stop profiling the build started by [`enter_build`].
+pub(crate) fn exit_build(instance: &str) {
+    ACTIVE_PROFILER.with(|active_profiler| {
+        let mut active_profiler = active_profiler.borrow_mut();
+        let active_profiler = match active_profiler.as_mut() {
+            Some(active_profiler) => active_profiler,
+            None => return,
+        };
+
+        let self_time = pop_timing(active_profiler);
+        let mut profiler = active_profiler.profiler.borrow_mut();
+        if let Some(profile) = profiler.record_mut(instance) {
+            profile.build_count += 1;
+            profile.build_time += self_time;
+        }
+    });
+}
+
+/// Start profiling the reconciliation of a component.
+///
+/// Returns false if profiling is disabled.
+pub(crate) fn enter_reconcile() -> bool {
+    ACTIVE_PROFILER.with(|active_profiler| {
+        let mut active_profiler = active_profiler.borrow_mut();
+        match active_profiler.as_mut() {
+            Some(active_profiler) => {
+                active_profiler.timing_stack.push(TimingFrame {
+                    start: Instant::now(),
+                    child_time: Duration::default(),
+                });
+                true
+            }
+            None => false,
+        }
+    })
+}
+
+/// Stop profiling the reconciliation started by [`enter_reconcile`].
+pub(crate) fn exit_reconcile(instance: &str) {
+    ACTIVE_PROFILER.with(|active_profiler| {
+        let mut active_profiler = active_profiler.borrow_mut();
+        let active_profiler = match active_profiler.as_mut() {
+            Some(active_profiler) => active_profiler,
+            None => return,
+        };
+
+        let self_time = pop_timing(active_profiler);
+        let mut profiler = active_profiler.profiler.borrow_mut();
+        if let Some(profile) = profiler.record_mut(instance) {
+            profile.reconcile_count += 1;
+            profile.reconcile_time += self_time;
+        }
+    });
+}
+
+/// Collect the widgets of each component instance of the tree, by path.
+///
+/// The widgets of child instances aren't counted for their parent.
+fn collect_instance_widgets<'a>(
+    node: &'a InspectNode,
+    instance: Option<&'a str>,
+    widgets_by_path: &mut HashMap<&'a str, HashSet<WidgetId>>,
+) {
+    let instance = match (&node.kind, &node.path) {
+        (InspectNodeKind::Component, Some(path)) => {
+            widgets_by_path.entry(path.as_str()).or_default();
+            Some(path.as_str())
+        }
+        _ => instance,
+    };
+    if let (Some(instance), Some(widget_id)) = (instance, node.widget_id) {
+        widgets_by_path.get_mut(instance).unwrap().insert(widget_id);
+    }
+    for child in &node.children {
+        collect_instance_widgets(child, instance, widgets_by_path);
+    }
+}
+
+fn pop_timing(active_profiler: &mut ActiveProfiler) -> Duration {
+    let frame = active_profiler.timing_stack.pop().unwrap();
+    let elapsed = frame.start.elapsed();
+    if let Some(parent_frame) = active_profiler.timing_stack.last_mut() {
+        parent_frame.child_time += elapsed;
+    }
+    elapsed.checked_sub(frame.child_time).unwrap_or_default()
+}
+
+fn render_cause(record: &InstanceRecord, props: &str, state: &str) -> RenderCause {
+    let format_changes = |changes: Vec<StateChange>| -> Vec<String> {
+        changes
+            .into_iter()
+            .map(|change| {
+                format!(
+                    "{}: {} -> {}",
+                    change.path,
+                    change.old_value.as_deref().unwrap_or("<none>"),
+                    change.new_value.as_deref().unwrap_or("<none>"),
+                )
+            })
+            .collect()
+    };
+
+    let props = format_changes(state_diff::diff_debug_output(
+        &record.last_props,
+        props,
+        "props",
+    ));
+    let state = format_changes(state_diff::diff_debug_output(
+        &record.last_state,
+        state,
+        "state",
+    ));
+
+    if props.is_empty() && state.is_empty() {
+        RenderCause::ParentRebuilt
+    } else {
+        RenderCause::Changed { props, state }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::element_path;
+    use test_env_log::test;
+
+    fn build(name: &'static str, props: i32, children: impl FnOnce()) {
+        let path = element_path::enter(name).unwrap();
+        let instance = enter_build(name, &path, &props, &()).unwrap();
+        children();
+        exit_build(&instance);
+        element_path::exit();
+    }
+
+    #[test]
+    fn instance_names_and_causes() {
+        let profiler = Some(SharedProfiler::default());
+
+        for count in &[0, 0, 1] {
+            with_profiler(&profiler, || {
+                element_path::with_paths(|| {
+                    build("App", 0, || {
+                        build("Counter", *count, || {});
+                        build("Counter", 0, || {});
+                    });
+                });
+            });
+        }
+
+        let report = profiler.unwrap().borrow().report();
+        let instances: Vec<_> = report
+            .components
+            .iter()
+            .map(|profile| profile.instance.as_str())
+            .collect();
+        assert_eq!(instances, vec!["App", "App/Counter", "App/Counter#1"]);
+
+        let counter = report.get("App/Counter").unwrap();
+        assert_eq!(counter.build_count, 3);
+        assert_eq!(
+            counter.render_causes,
+            vec![
+                RenderCause::Created,
+                RenderCause::ParentRebuilt,
+                RenderCause::Changed {
+                    props: vec!["props: 0 -> 1".to_string()],
+                    state: vec![],
+                },
+            ]
+        );
+        assert_eq!(report.instances_of("Counter").count(), 2);
+
+        let table = report.to_string();
+        assert!(table.contains("App/Counter#1"));
+        assert!(table.contains("props: 0 -> 1"));
+    }
+
+    #[test]
+    fn no_profiler() {
+        assert_eq!(enter_build("App", &"App".into(), &(), &()), None);
+        assert!(!enter_reconcile());
+    }
+}
@@ -0,0 +1,50 @@
+use std::fmt::Debug;
+
+/// Trait for local state types which are only modified through actions.
+///
+/// Instead of mutating the local state of a component directly in every event callback, you can
+/// implement this trait for the state type, and turn events into actions with
+/// [`ElementExt::dispatch`](crate::ElementExt::dispatch). The framework then calls
+/// [`reduce`](Self::reduce) for each action, in the order the events were processed.
+///
+/// Since `reduce` is a regular method, state transitions can be unit-tested without building
+/// any GUI.
+///
+/// ## Example
+///
+/// ```rust
+/// use panoramix::Reducer;
+///
+/// #[derive(Debug, Clone, PartialEq)]
+/// enum CounterAction {
+///     Increment,
+///     Reset,
+/// }
+///
+/// #[derive(Debug, Default, Clone, PartialEq)]
+/// struct CounterState {
+///     count: i32,
+/// }
+///
+/// impl Reducer for CounterState {
+///     type Action = CounterAction;
+///
+///     fn reduce(&mut self, action: CounterAction) {
+///         match action {
+///             CounterAction::Increment => self.count += 1,
+///             CounterAction::Reset => self.count = 0,
+///         }
+///     }
+/// }
+///
+/// let mut state = CounterState::default();
+/// state.reduce(CounterAction::Increment);
+/// assert_eq!(state.count, 1);
+/// ```
+pub trait Reducer {
+    /// The type of actions this state accepts.
+    type Action: Debug;
+
+    /// Apply an action to the state.
+    fn reduce(&mut self, action: Self::Action);
+}
@@ -1,26 +1,80 @@
+use crate::effects::{Effect, Effects};
+use crate::element_path;
 use crate::elements::{Component, ElementBox};
+use crate::external::{
+    ExternalHandle, ExternalQueue, SharedExternalQueue, PROCESS_EXTERNAL_MESSAGES,
+};
 use crate::flex;
 use crate::glue::{DruidAppData, GlobalEventCx};
+use crate::history::History;
+use crate::inspector::{InspectNode, INSPECT};
+use crate::interaction_script::{self, ScriptRecorder};
 use crate::internals::{ProcessEventCtx, ReconcileCtx, VirtualDom};
+#[cfg(feature = "serde")]
+use crate::persistence::ComponentPersistence;
+use crate::persistence::Persistence;
+use crate::profiler::{self, ProfileReport, SharedBuildCounts, SharedProfiler};
+#[cfg(feature = "png")]
+use crate::screenshot::Screenshot;
+use crate::store::{self, CloneStore, Selections};
+use crate::time_travel::{Recording, SharedRecording, TimeTravel, Transition, TIME_TRAVEL};
 use crate::widgets::FlexWidget;
 use crate::{Element, NoEvent};
 
 use crate::glue::DebugState;
 
 use druid::widget::prelude::*;
-use druid::{widget, AppLauncher, Point, Widget, WidgetPod, WindowDesc};
+use druid::{
+    commands, widget, AppLauncher, Command, HotKey, InternalLifeCycle, Point, Rect, TimerToken,
+    Widget, WidgetId, WidgetPod, WindowDesc,
+};
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fmt::Debug;
-use tracing::{debug_span, info, instrument, trace};
+#[cfg(feature = "png")]
+use std::path::Path;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use tracing::{debug, debug_span, info, instrument, trace, warn};
 
 pub use druid::PlatformError;
 
 // TODO - trait Element: 'static
 
+/// Maximum number of times events are processed for a single druid event.
+const MAX_EVENT_PASSES: usize = 1000;
+
 /// Implements [`druid::Widget`] from a component
 ///
 /// You should probably use [`RootHandler`] directly instead.
 pub struct RootWidget<RootElem: Element> {
     pub root_element: RootElem,
+    /// Called with every event emitted by the root element.
+    pub root_event_handler: Option<Box<dyn FnMut(RootElem::Event)>>,
+    pub(crate) external_queue: SharedExternalQueue,
+    pub(crate) store: Option<Box<dyn Any>>,
+    pub(crate) clone_store: Option<CloneStore>,
+    /// Slices of the store selected by each component instance during the last build.
+    pub(crate) store_selections: Selections,
+    pub(crate) persistence: Option<Persistence>,
+    #[cfg(feature = "serde")]
+    pub(crate) component_persistence: Option<ComponentPersistence>,
+    pub(crate) recording: Option<SharedRecording<RootElem::AggregateChildrenState>>,
+    /// Position in the recording shown while time-travelling, or `None` for the latest state.
+    pub(crate) time_travel_position: Option<usize>,
+    /// Snapshots of the root state and the store.
+    pub(crate) history: Option<History<(RootElem::AggregateChildrenState, Option<Box<dyn Any>>)>>,
+    pub(crate) profiler: Option<SharedProfiler>,
+    /// Builds of each component instance, counted for the test harness.
+    pub(crate) build_counts: Option<SharedBuildCounts>,
+    /// Shortcut which logs the inspector tree.
+    pub(crate) inspector_shortcut: Option<HotKey>,
+    pub(crate) script_recorder: Option<ScriptRecorder>,
+    /// Widget with keyboard focus, tracked for the test harness.
+    pub(crate) focused_widget: Option<WidgetId>,
+    /// Messages of the timers started with [`Effects::start_timer`](crate::Effects::start_timer).
+    pub(crate) timers: HashMap<TimerToken, Box<dyn Any + Send>>,
     pub root_state: RootElem::AggregateChildrenState,
     pub vdom: Option<RootElem::BuildOutput>,
     pub default_widget: WidgetPod<DruidAppData, widget::Flex<DruidAppData>>,
@@ -36,11 +90,33 @@ pub struct RootWidget<RootElem: Element> {
 }
 
 impl<LocalEvent: Clone + Debug + PartialEq + 'static> RootWidget<ElementBox<LocalEvent>> {
-    pub fn new<Comp: Component<Props = (), LocalEvent = LocalEvent>>(
+    pub fn new<Comp: Component<Props = (), LocalEvent = LocalEvent>>(root_component: Comp) -> Self {
+        Self::with_props(root_component, ())
+    }
+
+    pub fn with_props<Comp: Component<LocalEvent = LocalEvent>>(
         _root_component: Comp,
+        props: Comp::Props,
     ) -> Self {
         RootWidget {
-            root_element: Comp::new(()),
+            root_element: Comp::new(props),
+            root_event_handler: None,
+            external_queue: Default::default(),
+            store: None,
+            clone_store: None,
+            store_selections: Selections::default(),
+            persistence: None,
+            #[cfg(feature = "serde")]
+            component_persistence: None,
+            recording: None,
+            time_travel_position: None,
+            history: None,
+            profiler: None,
+            build_counts: None,
+            inspector_shortcut: None,
+            script_recorder: None,
+            focused_widget: None,
+            timers: HashMap::new(),
             root_state: Default::default(),
             vdom: None,
             default_widget: WidgetPod::new(widget::Flex::row()),
@@ -53,24 +129,210 @@ impl<RootElem: Element> RootWidget<RootElem> {
     pub fn from_element(elem: RootElem) -> Self {
         RootWidget {
             root_element: elem,
+            root_event_handler: None,
+            external_queue: Default::default(),
+            store: None,
+            clone_store: None,
+            store_selections: Selections::default(),
+            persistence: None,
+            #[cfg(feature = "serde")]
+            component_persistence: None,
+            recording: None,
+            time_travel_position: None,
+            history: None,
+            profiler: None,
+            build_counts: None,
+            inspector_shortcut: None,
+            script_recorder: None,
+            focused_widget: None,
+            timers: HashMap::new(),
             root_state: Default::default(),
             vdom: None,
             default_widget: WidgetPod::new(widget::Flex::row()),
             widget: None,
         }
     }
+
+    /// Set a callback called with every event emitted by the root element.
+    pub fn on_root_event(self, handler: impl FnMut(RootElem::Event) + 'static) -> Self {
+        RootWidget {
+            root_event_handler: Some(Box::new(handler)),
+            ..self
+        }
+    }
+
+    /// Set the app-wide store.
+    ///
+    /// See [`RootHandler::with_store`].
+    pub fn with_store<S: Clone + 'static>(self, initial_state: S) -> Self {
+        RootWidget {
+            store: Some(Box::new(initial_state)),
+            clone_store: Some(store::clone_store::<S>()),
+            ..self
+        }
+    }
+
+    /// Set the app-wide store, saved to the given file.
+    ///
+    /// See [`RootHandler::with_persistence`].
+    #[cfg(feature = "serde")]
+    pub fn with_persistence<S>(self, path: impl Into<std::path::PathBuf>, initial_state: S) -> Self
+    where
+        S: Clone + serde_crate::Serialize + serde_crate::de::DeserializeOwned + 'static,
+    {
+        RootWidget {
+            persistence: Some(Persistence::new::<S>(path.into())),
+            ..self.with_store(initial_state)
+        }
+    }
+
+    /// Save the local state of components to the given file.
+    ///
+    /// See [`RootHandler::with_component_persistence`].
+    #[cfg(feature = "serde")]
+    pub fn with_component_persistence(self, path: impl Into<std::path::PathBuf>) -> Self {
+        RootWidget {
+            component_persistence: Some(ComponentPersistence::new(path.into())),
+            ..self
+        }
+    }
+
+    /// Keep a history of the app state, to undo and redo changes.
+    ///
+    /// See [`RootHandler::with_history`].
+    pub fn with_history(self, limit: usize) -> Self {
+        RootWidget {
+            history: Some(History::new(limit)),
+            ..self
+        }
+    }
+
+    /// Keep a history of the app state, with rapid changes undone together.
+    ///
+    /// See [`RootHandler::with_coalesced_history`].
+    pub fn with_coalesced_history(self, limit: usize, interval: Duration) -> Self {
+        RootWidget {
+            history: Some(History::new(limit).with_coalescing(interval)),
+            ..self
+        }
+    }
+
+    /// Record every event and state transition, to debug the app.
+    ///
+    /// See [`RootHandler::with_recording`].
+    pub fn with_recording(self) -> Self {
+        RootWidget {
+            recording: Some(Rc::new(RefCell::new(Recording::new(Default::default())))),
+            ..self
+        }
+    }
+
+    /// Returns a copy of the session recorded so far, if recording is enabled.
+    pub fn recording(&self) -> Option<Recording<RootElem::AggregateChildrenState>> {
+        self.recording
+            .as_ref()
+            .map(|recording| recording.borrow().clone())
+    }
+
+    /// Measure how often and how long each component is rebuilt.
+    ///
+    /// See [`RootHandler::with_profiler`].
+    pub fn with_profiler(self) -> Self {
+        RootWidget {
+            profiler: Some(Default::default()),
+            ..self
+        }
+    }
+
+    /// Returns the statistics collected so far, if profiling is enabled.
+    pub fn profile_report(&self) -> Option<ProfileReport> {
+        self.profiler
+            .as_ref()
+            .map(|profiler| profiler.borrow().report())
+    }
+
+    /// Log the inspector tree when the given shortcut is pressed.
+    ///
+    /// See [`RootHandler::with_inspector_shortcut`].
+    pub fn with_inspector_shortcut(self, shortcut: HotKey) -> Self {
+        RootWidget {
+            inspector_shortcut: Some(shortcut),
+            ..self
+        }
+    }
+
+    /// Record the user's interactions, and save them to the given file when the window closes.
+    ///
+    /// See [`RootHandler::with_script_recording`].
+    #[cfg(feature = "serde")]
+    pub fn with_script_recording(self, path: impl Into<std::path::PathBuf>) -> Self {
+        RootWidget {
+            script_recorder: Some(ScriptRecorder::new(path.into())),
+            ..self
+        }
+    }
+
+    /// Describe the current component tree and widget tree.
+    ///
+    /// The returned tree merges both sides of the reconciliation: each component node has the
+    /// name, props and local state of the component, each element node has the type of the
+    /// element (and its key in a list), and each widget node has the id, the layout rect and
+    /// the `DebugState` values of the widget the element created.
+    ///
+    /// With the `serde` feature, the tree can be serialized (eg with
+    /// [`InspectNode::to_json`]) and sent to an external viewer.
+    ///
+    /// Returns an empty root node if the widget isn't initialized yet.
+    pub fn inspect(&self) -> InspectNode {
+        let (vdom, widget) = match (&self.vdom, &self.widget) {
+            (Some(vdom), Some(widget)) => (vdom, widget),
+            _ => return InspectNode::root(Vec::new()),
+        };
+
+        // Widgets only read the actions queued in app data when handling events.
+        let data = DruidAppData::default();
+        let scroll = widget.widget();
+        let widget_seq = &scroll.child().children_seq;
+        let mut root = InspectNode::root(vdom.inspect(&self.root_state, widget_seq, &data));
+        // The root widgets are laid out in the scrolled area of the window.
+        root.layout_rect = Some(Rect::from_origin_size(
+            Point::ZERO - scroll.offset(),
+            scroll.child_size(),
+        ));
+        root
+    }
+
+    /// Returns a handle used to send messages to the widget from other threads.
+    ///
+    /// See [`RootHandler::external_handle`].
+    pub fn external_handle<Message: Send + 'static>(&self) -> ExternalHandle<Message> {
+        ExternalHandle::new(self.external_queue.clone())
+    }
 }
 
 impl<RootElem: Element> RootWidget<RootElem> {
     #[instrument(level = "debug", skip(self, ctx))]
     pub fn init(&mut self, ctx: &mut EventCtx) {
-        let (new_vdom, state) =
-            debug_span!("build").in_scope(|| self.root_element.clone().build(Default::default()));
-        self.root_state = state;
+        if let Some(persistence) = &self.persistence {
+            if let Some(store) = persistence.load() {
+                self.store = Some(store);
+            }
+        }
 
-        info!("Initial aggregate app state: {:?}", self.root_state);
+        #[cfg(feature = "serde")]
+        if let Some(component_persistence) = &mut self.component_persistence {
+            component_persistence.load();
+        }
+
+        let new_vdom = self.build(Default::default(), None);
+        let profiler = self.profiler.clone();
+
+        if let Some(recording) = &self.recording {
+            *recording.borrow_mut() = Recording::new(self.root_state.clone());
+        }
 
-        let widget_seq = debug_span!("init_tree").in_scope(|| new_vdom.init_tree());
+        let widget_seq = debug_span!("init_tree")
+            .in_scope(|| profiler::with_profiler(&profiler, || new_vdom.init_tree()));
         // FIXME - Fix alignment to be consistent
         // (eg "Root(Button)" and "Root(Row(Button))" should be the same)
         let flex_widget = WidgetPod::new(
@@ -88,6 +350,12 @@ impl<RootElem: Element> RootWidget<RootElem> {
         ctx.children_changed();
         self.widget = Some(flex_widget);
         self.vdom = Some(new_vdom);
+        self.record_profiled_widgets();
+
+        // Messages may have been sent before the widget was ready to process them.
+        if ExternalQueue::has_messages(&self.external_queue) {
+            ctx.submit_command(PROCESS_EXTERNAL_MESSAGES);
+        }
 
         ctx.request_paint();
     }
@@ -106,44 +374,189 @@ impl<RootElem: Element> RootWidget<RootElem> {
         //  -> If app state is unchanged, return early.
         // - Generate new vdom from new app state.
         // - Reconcile new vdom with previous vdom.
+        // - Run side effects requested by event callbacks.
 
         let prev_root_state = self.root_state.clone();
+        let prev_store = match (&self.history, self.clone_store) {
+            (Some(_), Some(clone_store)) => self.store.as_deref().map(clone_store),
+            _ => None,
+        };
 
-        debug_span!("process_event").in_scope(|| {
+        let (effects, mut store_modified) = debug_span!("process_event").in_scope(|| {
             let prev_vdom = self.vdom.as_mut().unwrap();
             let flex_widget = self.widget.as_mut().unwrap().widget_mut();
             let mut cx = GlobalEventCx::new(data);
+            cx.store = self.store.as_deref_mut();
 
-            // We ignore the root event for now.
-            // This might change in cases where we want the user to control
-            // when RootWidget::run() is called.
             // Types are default types, because ProcessEventCtx will
             // normally be unused in root element.
             let mut ctx = ProcessEventCtx {
                 event_queue: &mut Vec::<NoEvent>::new(),
                 state: &mut (),
+                hook_states: &mut Vec::new(),
             };
-            prev_vdom.process_event(
-                &mut ctx,
-                &mut self.root_state,
-                &mut flex_widget.child_mut().children_seq,
-                &mut cx,
-            );
+
+            // We process widget events first, then each message sent from
+            // other threads, one at a time.
+            let external_messages = ExternalQueue::take_messages(&self.external_queue);
+            let passes = std::iter::once(None).chain(external_messages.into_iter().map(Some));
+            for external_message in passes {
+                cx.external_message = external_message;
+
+                // Elements which have more events to emit (eg Subscribe) request
+                // another pass. Events left after the last pass are processed on
+                // the next druid event.
+                for _ in 0..MAX_EVENT_PASSES {
+                    cx.has_pending_events = false;
+
+                    prev_vdom.process_event(
+                        &mut ctx,
+                        &mut self.root_state,
+                        &mut flex_widget.child_mut().children_seq,
+                        &mut cx,
+                    );
+
+                    // Events emitted by the root element are passed to the host application.
+                    while let Some(event) = prev_vdom.process_local_event(
+                        &mut self.root_state,
+                        &mut flex_widget.child_mut().children_seq,
+                        &mut cx,
+                    ) {
+                        trace!("Root element emitted event {:?}", event);
+                        if let Some(handler) = &mut self.root_event_handler {
+                            handler(event);
+                        }
+                    }
+
+                    if !cx.has_pending_events {
+                        break;
+                    }
+                }
+
+                if cx.external_message.take().is_some() {
+                    warn!("External message wasn't received by any ExternalMessages element");
+                }
+            }
+
+            (cx.effects, cx.store_modified)
         });
 
-        if !force_update && self.root_state == prev_root_state {
+        if let Some(history) = &mut self.history {
+            if store_modified || self.root_state != prev_root_state {
+                history.record((prev_root_state.clone(), prev_store), Instant::now());
+            }
+            for effect in effects.iter() {
+                let undo = match effect {
+                    Effect::Undo => true,
+                    Effect::Redo => false,
+                    _ => continue,
+                };
+                let mut state = (std::mem::take(&mut self.root_state), self.store.take());
+                let restored = if undo {
+                    history.undo(&mut state)
+                } else {
+                    history.redo(&mut state)
+                };
+                if !restored {
+                    debug!("Nothing to {}", if undo { "undo" } else { "redo" });
+                }
+                let (root_state, store) = state;
+                self.root_state = root_state;
+                self.store = store;
+                // Components selecting the restored slices are called again.
+                store_modified |= restored && self.store.is_some();
+            }
+        }
+
+        // Components are only rebuilt if a slice of the store they use changed.
+        let store_changed =
+            store_modified && store::selection_changed(&self.store, &self.store_selections);
+
+        if store_modified {
+            if let Some(persistence) = &mut self.persistence {
+                persistence.mark_changed();
+            }
+        }
+        self.save_state(ctx);
+
+        if !force_update && !store_changed && self.root_state == prev_root_state {
             trace!("State is unchanged. Skipping virtual DOM update.");
+            debug_span!("effects").in_scope(|| self.run_effects(ctx, effects));
             return;
         }
 
-        info!("New aggregate app state: {:?}", self.root_state);
+        // If the local state is unchanged, only the components whose slices of the store
+        // changed are called again.
+        let store_only = !force_update && self.root_state == prev_root_state;
+        self.rebuild(ctx, data, env, store_only);
+        // The build records the local states of components, to save them too.
+        self.save_state(ctx);
+
+        debug_span!("effects").in_scope(|| self.run_effects(ctx, effects));
+    }
 
-        let (new_vdom, state) = debug_span!("build").in_scope(|| {
-            self.root_element
-                .clone()
-                .build(std::mem::take(&mut self.root_state))
+    /// Build the root element, and set the new root state and store selections.
+    ///
+    /// If `prev_selections` is set, only the store changed since the last build; see
+    /// [`store::build_with_store`].
+    fn build(
+        &mut self,
+        prev_state: RootElem::AggregateChildrenState,
+        prev_selections: Option<Selections>,
+    ) -> RootElem::BuildOutput {
+        let root_element = self.root_element.clone();
+        let profiler = self.profiler.clone();
+        let build_counts = self.build_counts.clone();
+        let store = &mut self.store;
+        // Instances called again because of the store keep their local state, and the others
+        // aren't called, so the persisted states are only recorded by full builds.
+        #[cfg(feature = "serde")]
+        let component_persistence = match &mut self.component_persistence {
+            Some(component_persistence) if prev_selections.is_none() => Some(component_persistence),
+            _ => None,
+        };
+
+        let ((new_vdom, state), store_selections) = debug_span!("build").in_scope(|| {
+            store::build_with_store(store, prev_selections, || {
+                profiler::with_profiler(&profiler, || {
+                    profiler::with_build_counts(&build_counts, || {
+                        element_path::with_paths(|| {
+                            #[cfg(feature = "serde")]
+                            if let Some(component_persistence) = component_persistence {
+                                return component_persistence
+                                    .build(|| root_element.build(prev_state));
+                            }
+                            root_element.build(prev_state)
+                        })
+                    })
+                })
+            })
         });
         self.root_state = state;
+        self.store_selections = store_selections;
+        new_vdom
+    }
+
+    /// Build a new vdom from the current state, and reconcile it with the previous one.
+    ///
+    /// If `store_only` is true, only the store changed since the last build, so component
+    /// instances whose selected slices are unchanged reuse their previous element tree.
+    fn rebuild(
+        &mut self,
+        ctx: &mut EventCtx,
+        data: &mut DruidAppData,
+        env: &Env,
+        store_only: bool,
+    ) {
+        let root_state = std::mem::take(&mut self.root_state);
+        let prev_selections = std::mem::take(&mut self.store_selections);
+        let prev_selections = if store_only {
+            Some(prev_selections)
+        } else {
+            None
+        };
+        let new_vdom = self.build(root_state, prev_selections);
+        let profiler = self.profiler.clone();
 
         let flex_widget = self.widget.as_mut().unwrap().widget_mut();
         let prev_vdom = self.vdom.as_mut().unwrap();
@@ -154,24 +567,239 @@ impl<RootElem: Element> RootWidget<RootElem> {
         };
 
         debug_span!("reconcile").in_scope(|| {
-            new_vdom.reconcile(
-                &prev_vdom,
-                &mut flex_widget.child_mut().children_seq,
-                &mut reconcile_ctx,
-            );
+            profiler::with_profiler(&profiler, || {
+                new_vdom.reconcile(
+                    &prev_vdom,
+                    &mut flex_widget.child_mut().children_seq,
+                    &mut reconcile_ctx,
+                )
+            });
         });
         *prev_vdom = new_vdom;
+        self.record_profiled_widgets();
 
         ctx.request_update();
         ctx.request_paint();
     }
+
+    /// Count the widgets created and removed by each component instance, when profiling.
+    fn record_profiled_widgets(&self) {
+        if let Some(profiler) = &self.profiler {
+            let inspection = self.inspect();
+            profiler.borrow_mut().record_widgets(&inspection);
+        }
+    }
+}
+
+impl<RootElem: Element> RootWidget<RootElem> {
+    /// Show the app as it was at a recorded state.
+    #[instrument(level = "debug", skip(self, ctx, data, env))]
+    fn time_travel(
+        &mut self,
+        ctx: &mut EventCtx,
+        data: &mut DruidAppData,
+        env: &Env,
+        time_travel: TimeTravel,
+    ) {
+        let recording = match &self.recording {
+            Some(recording) => recording.clone(),
+            None => {
+                warn!("Cannot time-travel without a recording (see RootWidget::with_recording)");
+                return;
+            }
+        };
+        let recording = recording.borrow();
+
+        let latest = recording.transitions.len();
+        let position = self.time_travel_position.unwrap_or(latest);
+        let position = recording.travel(position, time_travel);
+        debug!("Going to state #{} of {}", position, latest);
+
+        self.time_travel_position = if position == latest {
+            None
+        } else {
+            Some(position)
+        };
+        self.root_state = recording.state_at(position).unwrap().clone();
+        drop(recording);
+
+        self.rebuild(ctx, data, env, false);
+    }
+
+    /// Run the side effects requested by event callbacks, once the widgets are reconciled.
+    fn run_effects(&mut self, ctx: &mut EventCtx, effects: Effects) {
+        for effect in effects.into_vec() {
+            trace!("Running effect {:?}", effect);
+            match effect {
+                Effect::Command(command) => ctx.submit_command(command),
+                Effect::Focus(path) => {
+                    let inspection = self.inspect();
+                    match find_widget(&inspection, &path) {
+                        Some(id) => ctx.set_focus(id),
+                        None => warn!("Cannot focus {:?}: no widget has this path", path),
+                    }
+                }
+                Effect::ScrollTo(path) => {
+                    let inspection = self.inspect();
+                    let rect =
+                        find_widget(&inspection, &path).and_then(|id| inspection.window_rect(id));
+                    let rect = match rect {
+                        Some(rect) => rect,
+                        None => {
+                            warn!("Cannot scroll to {:?}: no widget has this path", path);
+                            continue;
+                        }
+                    };
+                    let scroll = self.widget.as_mut().unwrap().widget_mut();
+                    // Window rects are shifted by the current scroll offset.
+                    if scroll.scroll_to(rect + scroll.offset()) {
+                        ctx.request_paint();
+                    }
+                }
+                Effect::StartTimer { delay, message } => {
+                    let token = ctx.request_timer(delay);
+                    self.timers.insert(token, message.make_message());
+                }
+                Effect::CloseWindow => {
+                    let window_id = ctx.window_id();
+                    ctx.submit_command(Command::from(commands::CLOSE_WINDOW).to(window_id));
+                }
+                // Applied before the GUI is updated.
+                Effect::Undo | Effect::Redo => {}
+            }
+        }
+    }
+
+    /// Record an event, and the transition it caused if the state changed.
+    fn record_event(&mut self, event: &Event, state_changed: bool) {
+        let recording = match &self.recording {
+            Some(recording) => recording,
+            None => return,
+        };
+        let mut recording = recording.borrow_mut();
+
+        // Changing a past state forks the session: the transitions after the past state, and
+        // the events which caused them, are dropped, and the new state is the latest one.
+        if state_changed {
+            if let Some(position) = self.time_travel_position.take() {
+                debug!("Forking the recording at state #{}", position);
+                let first_dropped_event = recording.transitions[position].event_index;
+                recording.events.truncate(first_dropped_event);
+                recording.transitions.truncate(position);
+            }
+        }
+
+        recording.events.push(event.clone());
+        if state_changed {
+            let event_index = recording.events.len() - 1;
+            trace!("Recording transition #{}", recording.transitions.len() + 1);
+            recording.transitions.push(Transition {
+                event_index,
+                state: self.root_state.clone(),
+            });
+        }
+    }
+
+    fn log_inspection(&self) {
+        let inspection = self.inspect();
+        #[cfg(feature = "serde")]
+        info!("Inspector tree:\n{}", inspection.to_json());
+        #[cfg(not(feature = "serde"))]
+        info!("Inspector tree:\n{:#?}", inspection);
+    }
+
+    fn save_state(&mut self, ctx: &mut EventCtx) {
+        if let (Some(persistence), Some(store)) = (&mut self.persistence, &self.store) {
+            if let Some(delay) = persistence.save_if_due(store.as_ref(), Instant::now()) {
+                // Any event runs the save again, including the timer.
+                if persistence.timer == TimerToken::INVALID {
+                    persistence.timer = ctx.request_timer(delay);
+                }
+            }
+        }
+        #[cfg(feature = "serde")]
+        if let Some(component_persistence) = &mut self.component_persistence {
+            if let Some(delay) = component_persistence.save_if_due(Instant::now()) {
+                let persistence = &mut component_persistence.persistence;
+                if persistence.timer == TimerToken::INVALID {
+                    persistence.timer = ctx.request_timer(delay);
+                }
+            }
+        }
+    }
+}
+
+/// Returns the first widget of the element with the given path in the inspector tree.
+fn find_widget(inspection: &InspectNode, path: &str) -> Option<WidgetId> {
+    let node = inspection.find_path(path)?;
+    node.find(|node| node.widget_id.is_some())?.widget_id
 }
 
 impl<RootElem: Element> Widget<DruidAppData> for RootWidget<RootElem> {
     fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut DruidAppData, env: &Env) {
         let mut force_update = false;
 
+        if let Event::Timer(token) = event {
+            if let Some(persistence) = &mut self.persistence {
+                if persistence.timer == *token {
+                    persistence.timer = TimerToken::INVALID;
+                }
+            }
+            #[cfg(feature = "serde")]
+            if let Some(component_persistence) = &mut self.component_persistence {
+                let persistence = &mut component_persistence.persistence;
+                if persistence.timer == *token {
+                    persistence.timer = TimerToken::INVALID;
+                }
+            }
+            // Timer messages are processed like external messages by this event.
+            if let Some(message) = self.timers.remove(token) {
+                ExternalQueue::push_message(&self.external_queue, message);
+            }
+        }
+        if let Event::WindowDisconnected = event {
+            if let (Some(persistence), Some(store)) = (&mut self.persistence, &self.store) {
+                persistence.flush(store.as_ref());
+            }
+            #[cfg(feature = "serde")]
+            if let Some(component_persistence) = &mut self.component_persistence {
+                component_persistence.flush();
+            }
+            if let Some(report) = self.profile_report() {
+                info!("Component profile:\n{}", report);
+            }
+            #[cfg(feature = "serde")]
+            if let Some(script_recorder) = &self.script_recorder {
+                script_recorder.save();
+            }
+        }
+        if let Some(script_recorder) = &mut self.script_recorder {
+            script_recorder.record_event(event);
+        }
+        // Text edits are recorded from the text of the textboxes before and after the event.
+        let old_texts = match event {
+            Event::KeyDown(_) | Event::KeyUp(_) | Event::Paste(_)
+                if self.script_recorder.is_some() =>
+            {
+                Some(interaction_script::textbox_texts(&self.inspect()))
+            }
+            _ => None,
+        };
+
+        if let Event::KeyDown(key_event) = event {
+            if let Some(shortcut) = &self.inspector_shortcut {
+                if shortcut.matches(key_event) {
+                    self.log_inspection();
+                }
+            }
+        }
+
         if let Event::Command(command) = event {
+            if let Some(inspection) = command.get(INSPECT) {
+                inspection.replace(Some(self.inspect()));
+                return;
+            }
+
             let selector = druid::Selector::new("update_root_element");
             if let Some(new_root) = command.get::<RootElem>(selector) {
                 self.root_element = new_root.clone();
@@ -184,11 +812,36 @@ impl<RootElem: Element> Widget<DruidAppData> for RootWidget<RootElem> {
             self.default_widget.event(ctx, event, data, env);
         }
 
+        if let Some(old_texts) = old_texts {
+            let new_texts = interaction_script::textbox_texts(&self.inspect());
+            if let Some(script_recorder) = &mut self.script_recorder {
+                script_recorder.record_text_edits(event, &old_texts, &new_texts);
+            }
+        }
+
         if self.vdom.is_none() {
             self.init(ctx);
+            return;
+        }
+
+        let prev_state = self.recording.as_ref().map(|_| self.root_state.clone());
+
+        let time_travel = match event {
+            Event::Command(command) => command.get(TIME_TRAVEL).copied(),
+            _ => None,
+        };
+        if let Some(time_travel) = time_travel {
+            self.time_travel(ctx, data, env, time_travel);
         } else {
             self.run(ctx, data, env, force_update);
         }
+
+        if let Some(prev_state) = prev_state {
+            // Time travel commands are recorded, so that replaying the session replays
+            // them too, but the states they lead to aren't new transitions.
+            let state_changed = time_travel.is_none() && self.root_state != prev_state;
+            self.record_event(event, state_changed);
+        }
     }
 
     fn lifecycle(
@@ -198,6 +851,10 @@ impl<RootElem: Element> Widget<DruidAppData> for RootWidget<RootElem> {
         data: &DruidAppData,
         env: &Env,
     ) {
+        if let LifeCycle::Internal(InternalLifeCycle::RouteFocusChanged { new, .. }) = event {
+            self.focused_widget = *new;
+        }
+
         if let Some(widget) = &mut self.widget {
             widget.lifecycle(ctx, event, data, env);
         } else {
@@ -266,6 +923,7 @@ impl<RootElem: Element> Widget<DruidAppData> for RootWidget<RootElem> {
 pub struct RootHandler<RootElem: Element> {
     pub root_widget: RootWidget<RootElem>,
     pub init_tracing: bool,
+    window_size: Option<Size>,
 }
 
 impl<LocalEvent: Clone + Debug + PartialEq + 'static> RootHandler<ElementBox<LocalEvent>> {
@@ -278,11 +936,204 @@ impl<LocalEvent: Clone + Debug + PartialEq + 'static> RootHandler<ElementBox<Loc
         RootHandler {
             root_widget: RootWidget::new(root_component),
             init_tracing: false,
+            window_size: None,
+        }
+    }
+
+    /// Creates the data to start the application, with the given props for the root component.
+    ///
+    /// Same as [`new`](RootHandler::new), for root components whose props aren't `()`.
+    pub fn with_props<Comp: Component<LocalEvent = LocalEvent>>(
+        root_component: Comp,
+        props: Comp::Props,
+    ) -> Self {
+        RootHandler {
+            root_widget: RootWidget::with_props(root_component, props),
+            init_tracing: false,
+            window_size: None,
         }
     }
 }
 
 impl<RootElem: Element> RootHandler<RootElem> {
+    /// Returns a handle used to send messages to the application from other threads.
+    ///
+    /// The handle can be cloned, and used before or after the application is launched.
+    /// Messages are received by [`ExternalMessages`](crate::elements::ExternalMessages)
+    /// elements in the component tree.
+    pub fn external_handle<Message: Send + 'static>(&self) -> ExternalHandle<Message> {
+        self.root_widget.external_handle()
+    }
+
+    /// Set an app-wide store, shared by all components.
+    ///
+    /// Components read slices of the store with
+    /// [`CompCtx::use_selector`](crate::CompCtx::use_selector), and modify it in event callbacks
+    /// with [`ElementExt::on_store`](crate::ElementExt::on_store). When the store is modified,
+    /// only the components whose selected slices changed are called again.
+    ///
+    /// The store is cloned to record it in the history (see [`with_history`](Self::with_history)).
+    pub fn with_store<S: Clone + 'static>(self, initial_state: S) -> Self {
+        RootHandler {
+            root_widget: self.root_widget.with_store(initial_state),
+            ..self
+        }
+    }
+
+    /// Set the app-wide store, saved to the given file.
+    ///
+    /// Same as [`with_store`](Self::with_store), except the store is restored from `path`
+    /// when the app starts, and saved to it as JSON when it changes. If the file doesn't exist
+    /// or can't be read, the store is set to `initial_state`. Saves are debounced, so that
+    /// rapid changes (eg typing) don't write to the disk every time.
+    ///
+    /// To persist the local state of components, see
+    /// [`with_component_persistence`](Self::with_component_persistence).
+    ///
+    /// Requires the `serde` feature.
+    #[cfg(feature = "serde")]
+    pub fn with_persistence<S>(self, path: impl Into<std::path::PathBuf>, initial_state: S) -> Self
+    where
+        S: Clone + serde_crate::Serialize + serde_crate::de::DeserializeOwned + 'static,
+    {
+        RootHandler {
+            root_widget: self.root_widget.with_persistence(path, initial_state),
+            ..self
+        }
+    }
+
+    /// Save the local state of components to the given file.
+    ///
+    /// Only the components which call
+    /// [`CompCtx::use_persisted_metadata`](crate::CompCtx::use_persisted_metadata) instead of
+    /// `use_metadata` are saved, as JSON, keyed by their path in the inspector tree. When the app
+    /// starts, each state is restored before the component instance is first built. Saves are
+    /// debounced like in [`with_persistence`](Self::with_persistence).
+    ///
+    /// Requires the `serde` feature.
+    #[cfg(feature = "serde")]
+    pub fn with_component_persistence(self, path: impl Into<std::path::PathBuf>) -> Self {
+        RootHandler {
+            root_widget: self.root_widget.with_component_persistence(path),
+            ..self
+        }
+    }
+
+    /// Record every event and state transition, to debug the app.
+    ///
+    /// Once recording is enabled, submitting a [`TIME_TRAVEL`](crate::TIME_TRAVEL) command
+    /// shows the app as it was at a past state; the GUI is rebuilt from that state. The
+    /// recorded session can be replayed in tests with
+    /// [`Harness::replay`](crate::test_harness::Harness::replay).
+    ///
+    /// Every event is kept in memory, so this should only be used to debug.
+    pub fn with_recording(self) -> Self {
+        RootHandler {
+            root_widget: self.root_widget.with_recording(),
+            ..self
+        }
+    }
+
+    /// Measure how often and how long each component is rebuilt.
+    ///
+    /// For each component instance, the profiler counts builds and reconciliations, measures
+    /// the time they take (excluding child components), counts the widgets created and
+    /// destroyed, and records why the component was rebuilt (which props or local state
+    /// changed, or none, if it was rebuilt because its parent was).
+    ///
+    /// Instances are identified by their element path, eg `App/Column/TodoItem#2` for the
+    /// third `TodoItem` of the column returned by `App`. The report is logged when the window closes; tests can
+    /// read it with [`Harness::profile_report`](crate::test_harness::Harness::profile_report).
+    ///
+    /// Props and states are compared through their `Debug` output, so profiling slows the app
+    /// down; it should only be used to debug.
+    pub fn with_profiler(self) -> Self {
+        RootHandler {
+            root_widget: self.root_widget.with_profiler(),
+            ..self
+        }
+    }
+
+    /// Log the inspector tree when the given shortcut is pressed.
+    ///
+    /// The tree describes the current components, elements and widgets (see
+    /// [`RootWidget::inspect`]). It's logged at the `info` level, as JSON if the `serde`
+    /// feature is enabled.
+    ///
+    /// ```no_run
+    /// # use panoramix::elements::Label;
+    /// # use panoramix::{component, CompCtx, Element, NoEvent, RootHandler};
+    /// # #[component]
+    /// # fn App(_ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
+    /// #     Label::new("Hello")
+    /// # }
+    /// use druid::{HotKey, SysMods};
+    ///
+    /// RootHandler::new(App)
+    ///     .with_inspector_shortcut(HotKey::new(SysMods::CmdShift, "I"))
+    ///     .with_tracing(true)
+    ///     .launch()
+    ///     .expect("launch failed");
+    /// ```
+    pub fn with_inspector_shortcut(self, shortcut: HotKey) -> Self {
+        RootHandler {
+            root_widget: self.root_widget.with_inspector_shortcut(shortcut),
+            ..self
+        }
+    }
+
+    /// Record the user's interactions, and save them to the given file when the window closes.
+    ///
+    /// Mouse and keyboard events are saved as an
+    /// [`InteractionScript`](crate::test_harness::InteractionScript) in JSON, which can be
+    /// replayed in a test with
+    /// [`Harness::replay_script`](crate::test_harness::Harness::replay_script) to reproduce a
+    /// bug. Key presses which edit the text of a textbox are saved as text edits of the textbox,
+    /// identified by its element path. Mouse positions are replayed as is, so the test window should have the same size
+    /// as the window of the recorded session.
+    ///
+    /// Requires the `serde` feature.
+    #[cfg(feature = "serde")]
+    pub fn with_script_recording(self, path: impl Into<std::path::PathBuf>) -> Self {
+        RootHandler {
+            root_widget: self.root_widget.with_script_recording(path),
+            ..self
+        }
+    }
+
+    /// Keep a history of the app state, to undo and redo changes.
+    ///
+    /// After each event which changes the local state of components or the app-wide store
+    /// (see [`with_store`](Self::with_store)), a snapshot of the previous state and store is
+    /// recorded, up to `limit` snapshots. Event callbacks undo and redo changes with
+    /// [`Effects::undo`](crate::Effects::undo) and [`Effects::redo`](crate::Effects::redo).
+    pub fn with_history(self, limit: usize) -> Self {
+        RootHandler {
+            root_widget: self.root_widget.with_history(limit),
+            ..self
+        }
+    }
+
+    /// Same as [`with_history`](Self::with_history), except changes made less than `interval`
+    /// after the previous change are undone together (eg typing a word in a `TextBox`).
+    pub fn with_coalesced_history(self, limit: usize, interval: Duration) -> Self {
+        RootHandler {
+            root_widget: self.root_widget.with_coalesced_history(limit, interval),
+            ..self
+        }
+    }
+
+    /// Set a callback called with every event emitted by the root component.
+    ///
+    /// This lets the host application react to app-level events (eg "save requested")
+    /// without handling them in the component tree.
+    pub fn on_root_event(self, handler: impl FnMut(RootElem::Event) + 'static) -> Self {
+        RootHandler {
+            root_widget: self.root_widget.on_root_event(handler),
+            ..self
+        }
+    }
+
     pub fn with_tracing(self, init_tracing: bool) -> Self {
         RootHandler {
             init_tracing,
@@ -290,16 +1141,83 @@ impl<RootElem: Element> RootHandler<RootElem> {
         }
     }
 
+    /// Set the size of the window.
+    pub fn with_window_size(self, window_size: Size) -> Self {
+        RootHandler {
+            window_size: Some(window_size),
+            ..self
+        }
+    }
+
+    /// Render the app in the headless test harness, and write it as a PNG file to `path`,
+    /// instead of opening a window.
+    ///
+    /// The window size defaults to [`WINDOW_SIZE`](crate::test_harness::WINDOW_SIZE). Use
+    /// [`Screenshot`] directly to interact with the app before the screenshot is taken.
+    ///
+    /// Requires the `png` feature.
+    #[cfg(feature = "png")]
+    pub fn save_screenshot(self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        if self.init_tracing {
+            crate::glue::init_tracing();
+        }
+
+        let screenshot = Screenshot::new(self.root_widget);
+        match self.window_size {
+            Some(window_size) => screenshot.with_window_size(window_size),
+            None => screenshot,
+        }
+        .save(path)
+    }
+
     /// Start the application.
+    ///
+    /// With the `png` feature, if the [`PANORAMIX_SCREENSHOT`](crate::screenshot::SCREENSHOT_VAR)
+    /// environment variable is set, a screenshot is written to the given path instead (see
+    /// [`save_screenshot`](Self::save_screenshot)). This is how the `screenshots` example
+    /// captures the other examples.
     pub fn launch(self) -> Result<(), PlatformError> {
+        #[cfg(feature = "png")]
+        {
+            if let Some((path, window_size)) = crate::screenshot::requested_screenshot() {
+                let app = match window_size {
+                    Some(window_size) => self.with_window_size(window_size),
+                    None => self,
+                };
+                app.save_screenshot(&path).unwrap_or_else(|error| {
+                    panic!(
+                        "error: cannot write screenshot {}: {}",
+                        path.display(),
+                        error
+                    )
+                });
+                return Ok(());
+            }
+        }
+
         if self.init_tracing {
             crate::glue::init_tracing();
         }
+        #[cfg(not(feature = "png"))]
+        {
+            if std::env::var_os(crate::screenshot::SCREENSHOT_VAR).is_some() {
+                warn!(
+                    "{} is ignored, since screenshots require the png feature",
+                    crate::screenshot::SCREENSHOT_VAR
+                );
+            }
+        }
 
         let widget = self.root_widget;
-        let main_window = WindowDesc::new(widget);
+        let external_queue = widget.external_queue.clone();
+        let mut main_window = WindowDesc::new(widget);
+        if let Some(window_size) = self.window_size {
+            main_window = main_window.window_size(window_size);
+        }
         let data = Default::default();
 
-        AppLauncher::with_window(main_window).launch(data)
+        let launcher = AppLauncher::with_window(main_window);
+        ExternalQueue::set_sink(&external_queue, launcher.get_external_handle());
+        launcher.launch(data)
     }
 }
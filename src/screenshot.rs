@@ -0,0 +1,213 @@
+//! Screenshots of apps rendered in the headless test harness, used for examples and docs.
+//!
+//! Writing screenshots to files requires the `png` feature. With that feature,
+//! [`RootHandler::launch`](crate::RootHandler::launch) writes a screenshot instead of opening a
+//! window if [`SCREENSHOT_VAR`] is set, so any app can be captured without changing its code:
+//!
+//! ```sh
+//! PANORAMIX_SCREENSHOT=list.png PANORAMIX_SCREENSHOT_SIZE=640x480 cargo run --features png --example list
+//! ```
+
+use crate::test_harness::{Harness, RenderedImage, WINDOW_SIZE};
+use crate::{Element, RootWidget};
+
+use druid::Size;
+#[cfg(feature = "png")]
+use std::ffi::OsString;
+#[cfg(feature = "png")]
+use std::path::{Path, PathBuf};
+#[cfg(feature = "png")]
+use tracing::info;
+
+/// Environment variable which, if set, makes [`RootHandler::launch`](crate::RootHandler::launch)
+/// write a screenshot of the app to the given path instead of opening a window.
+pub const SCREENSHOT_VAR: &str = "PANORAMIX_SCREENSHOT";
+
+/// Environment variable setting the window size of the screenshot requested with
+/// [`SCREENSHOT_VAR`], eg `640x480`.
+pub const SCREENSHOT_SIZE_VAR: &str = "PANORAMIX_SCREENSHOT_SIZE";
+
+/// Script run on the app before a screenshot is taken.
+pub type ScreenshotScript<RootElem> = Box<dyn FnMut(&mut Harness<'_, '_, RootElem>)>;
+
+/// A screenshot of an app, taken in the headless [`Harness`].
+///
+/// ```no_run
+/// # use panoramix::elements::Label;
+/// # use panoramix::screenshot::Screenshot;
+/// # use panoramix::RootWidget;
+/// let root_widget = RootWidget::from_element(Label::new("Hello"));
+///
+/// # #[cfg(feature = "png")]
+/// Screenshot::new(root_widget)
+///     .with_window_size((300.0, 200.0).into())
+///     .save("hello.png")
+///     .unwrap();
+/// ```
+pub struct Screenshot<RootElem: Element> {
+    root_widget: RootWidget<RootElem>,
+    window_size: Size,
+    script: Option<ScreenshotScript<RootElem>>,
+}
+
+//
+// --- IMPLS
+
+impl<RootElem: Element> Screenshot<RootElem> {
+    pub fn new(root_widget: RootWidget<RootElem>) -> Self {
+        Screenshot {
+            root_widget,
+            window_size: WINDOW_SIZE,
+            script: None,
+        }
+    }
+
+    /// Set the size of the window. Defaults to [`WINDOW_SIZE`].
+    pub fn with_window_size(self, window_size: Size) -> Self {
+        Screenshot {
+            window_size,
+            ..self
+        }
+    }
+
+    /// Interact with the app (eg click buttons) before taking the screenshot.
+    pub fn with_script(self, script: impl FnMut(&mut Harness<'_, '_, RootElem>) + 'static) -> Self {
+        Screenshot {
+            script: Some(Box::new(script)),
+            ..self
+        }
+    }
+
+    /// Run the script, and render the window.
+    pub fn render(self) -> RenderedImage {
+        let mut script = self.script;
+        let mut image = None;
+
+        Harness::run_test_root_widget_with_size(self.root_widget, self.window_size, |harness| {
+            if let Some(script) = &mut script {
+                script(harness);
+            }
+            image = Some(harness.render_to_image());
+        });
+        image.unwrap()
+    }
+
+    /// Run the script, and write the rendered window to a PNG file.
+    ///
+    /// Requires the `png` feature.
+    #[cfg(feature = "png")]
+    pub fn save(self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let path = path.as_ref();
+        self.render().save_png(path)?;
+        info!("Saved screenshot {}", path.display());
+        Ok(())
+    }
+}
+
+/// Write screenshots of an app for each window size.
+///
+/// `make_screenshot` is called once per window size, since the app is rebuilt for each
+/// screenshot. Screenshots are written to `dir` as `<name>.png` if there is a single size, or
+/// `<name>-<width>x<height>.png` otherwise. Returns the paths of the written files.
+///
+/// Requires the `png` feature.
+#[cfg(feature = "png")]
+pub fn save_screenshots<RootElem: Element>(
+    dir: impl AsRef<Path>,
+    name: &str,
+    window_sizes: &[Size],
+    mut make_screenshot: impl FnMut() -> Screenshot<RootElem>,
+) -> std::io::Result<Vec<PathBuf>> {
+    let dir = dir.as_ref();
+    std::fs::create_dir_all(dir)?;
+
+    let mut paths = Vec::new();
+    for window_size in window_sizes {
+        let file_name = if window_sizes.len() == 1 {
+            format!("{}.png", name)
+        } else {
+            format!("{}-{}.png", name, format_window_size(*window_size))
+        };
+        let path = dir.join(file_name);
+
+        make_screenshot()
+            .with_window_size(*window_size)
+            .save(&path)?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+/// Parse a window size, eg `640x480`.
+pub fn parse_window_size(size: &str) -> Option<Size> {
+    let (width, height) = size.split_at(size.find('x')?);
+    let width = width.trim().parse().ok()?;
+    let height = height[1..].trim().parse().ok()?;
+    Some(Size::new(width, height))
+}
+
+/// Format a window size, eg `640x480`.
+pub fn format_window_size(size: Size) -> String {
+    format!("{}x{}", size.width, size.height)
+}
+
+/// Returns the path and window size of the screenshot requested with [`SCREENSHOT_VAR`] and
+/// [`SCREENSHOT_SIZE_VAR`], if any.
+#[cfg(feature = "png")]
+pub(crate) fn requested_screenshot() -> Option<(PathBuf, Option<Size>)> {
+    parse_screenshot_request(
+        std::env::var_os(SCREENSHOT_VAR),
+        std::env::var(SCREENSHOT_SIZE_VAR).ok(),
+    )
+}
+
+#[cfg(feature = "png")]
+fn parse_screenshot_request(
+    path: Option<OsString>,
+    size: Option<String>,
+) -> Option<(PathBuf, Option<Size>)> {
+    let path = path.filter(|path| !path.is_empty())?;
+    let window_size = size.map(|size| {
+        parse_window_size(&size).unwrap_or_else(|| {
+            panic!(
+                "error: invalid {} '{}', expected eg 640x480",
+                SCREENSHOT_SIZE_VAR, size
+            )
+        })
+    });
+    Some((path.into(), window_size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_env_log::test;
+
+    #[test]
+    fn window_sizes() {
+        assert_eq!(parse_window_size("640x480"), Some(Size::new(640., 480.)));
+        assert_eq!(parse_window_size("640"), None);
+        assert_eq!(parse_window_size("axb"), None);
+        assert_eq!(format_window_size(Size::new(640., 480.)), "640x480");
+    }
+
+    #[test]
+    #[cfg(feature = "png")]
+    fn screenshot_request() {
+        let request = |path: Option<&str>, size: Option<&str>| {
+            parse_screenshot_request(path.map(OsString::from), size.map(String::from))
+        };
+
+        assert_eq!(request(None, None), None);
+        assert_eq!(request(None, Some("640x480")), None);
+        assert_eq!(request(Some(""), None), None);
+        assert_eq!(
+            request(Some("list.png"), None),
+            Some((PathBuf::from("list.png"), None))
+        );
+        assert_eq!(
+            request(Some("list.png"), Some("640x480")),
+            Some((PathBuf::from("list.png"), Some(Size::new(640., 480.))))
+        );
+    }
+}
@@ -0,0 +1,437 @@
+//! Comparison of app states, used to log what changed instead of the entire state.
+//!
+//! When the app is rebuilt, each component instance compares its new state with the one it
+//! had in the previous build. States which aren't equal are compared through their `Debug`
+//! output, parsed back into a tree. Instances are identified by their path in the inspector
+//! tree, so items of an `ElementList` are matched by key.
+
+use crate::elements::internals::AnyStateBox;
+use std::fmt::Debug;
+use std::rc::Rc;
+use tracing::level_filters::LevelFilter;
+use tracing::{debug, Level};
+
+/// A value which changed between two states.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct StateChange {
+    /// Where the value is, eg `TodoList/List/TodoItem[42].done`.
+    ///
+    /// Paths start with the path of the component instance, followed by field names and indices
+    /// in tuples and lists. State slots of [`CompCtx::use_state`](crate::CompCtx::use_state)
+    /// are named `use_state#0`, `use_state#1`, etc.
+    pub path: String,
+    /// The previous value, or `None` if it was added.
+    pub old_value: Option<String>,
+    /// The new value, or `None` if it was removed.
+    pub new_value: Option<String>,
+}
+
+/// The state of a component instance at the end of a build, recorded to log its changes.
+#[derive(Debug, Clone)]
+pub(crate) struct InstanceState<ComponentState> {
+    path: Rc<str>,
+    local_state: Option<ComponentState>,
+    hook_states: Vec<AnyStateBox>,
+}
+
+#[derive(Debug, Default)]
+struct DebugNode {
+    label: String,
+    children: Vec<DebugNode>,
+    is_container: bool,
+}
+
+/// Returns true if state changes are logged, ie if the `DEBUG` level is enabled.
+///
+/// Component instances only record their state (see [`InstanceState`]) in that case.
+pub(crate) fn is_enabled() -> bool {
+    LevelFilter::current() >= Level::DEBUG
+}
+
+impl<ComponentState: Debug + PartialEq> InstanceState<ComponentState> {
+    pub fn new(
+        path: Rc<str>,
+        local_state: Option<ComponentState>,
+        hook_states: Vec<AnyStateBox>,
+    ) -> Self {
+        InstanceState {
+            path,
+            local_state,
+            hook_states,
+        }
+    }
+}
+
+/// Log the values which changed between two states of a component instance.
+pub(crate) fn log_changes<ComponentState: Debug + PartialEq>(
+    old_state: &InstanceState<ComponentState>,
+    new_state: &InstanceState<ComponentState>,
+) {
+    for change in diff_instance(old_state, new_state) {
+        debug!(
+            path = %change.path,
+            old_value = change.old_value.as_deref().unwrap_or("<none>"),
+            new_value = change.new_value.as_deref().unwrap_or("<none>"),
+            "State changed"
+        );
+    }
+}
+
+/// Returns every value which differs between two states of a component instance.
+///
+/// Only the states which aren't equal are formatted and compared. States recorded at different
+/// paths belong to different instances, and aren't compared.
+fn diff_instance<ComponentState: Debug + PartialEq>(
+    old_state: &InstanceState<ComponentState>,
+    new_state: &InstanceState<ComponentState>,
+) -> Vec<StateChange> {
+    let mut changes = Vec::new();
+    if old_state.path != new_state.path {
+        return changes;
+    }
+    let path = &*new_state.path;
+
+    if let (Some(old_value), Some(new_value)) = (&old_state.local_state, &new_state.local_state) {
+        if old_value != new_value {
+            changes.extend(diff_debug_output(
+                &format!("{:?}", old_value),
+                &format!("{:?}", new_value),
+                path,
+            ));
+        }
+    }
+    let hook_states = old_state.hook_states.iter().zip(&new_state.hook_states);
+    for (index, (old_value, new_value)) in hook_states.enumerate() {
+        if old_value != new_value {
+            changes.extend(diff_debug_output(
+                &format!("{:?}", old_value),
+                &format!("{:?}", new_value),
+                &format!("{}.use_state#{}", path, index),
+            ));
+        }
+    }
+    changes
+}
+
+/// Returns every value which differs between two `Debug` outputs, with paths starting with `root`.
+///
+/// The outputs can be pretty-printed or not. If either output can't be parsed (eg because of a
+/// custom `Debug` impl with unbalanced brackets), the whole values are reported as changed.
+pub(crate) fn diff_debug_output(
+    old_output: &str,
+    new_output: &str,
+    root: &str,
+) -> Vec<StateChange> {
+    let (old_tree, new_tree) = match (parse_debug(old_output), parse_debug(new_output)) {
+        (Some(old_tree), Some(new_tree)) => (old_tree, new_tree),
+        _ if old_output == new_output => return Vec::new(),
+        _ => {
+            return vec![StateChange {
+                path: root.to_string(),
+                old_value: Some(old_output.to_string()),
+                new_value: Some(new_output.to_string()),
+            }]
+        }
+    };
+
+    let mut changes = Vec::new();
+    diff_nodes(&old_tree, &new_tree, root.to_string(), &mut changes);
+    changes
+}
+
+/// Parse a `Debug` output into a tree, or return `None` if its brackets or quotes aren't
+/// balanced.
+fn parse_debug(text: &str) -> Option<DebugNode> {
+    let mut stack = vec![DebugNode {
+        is_container: true,
+        ..Default::default()
+    }];
+    let mut label = String::new();
+    // Quote of the string or char literal being parsed, if any.
+    let mut quote = None;
+    let mut escaped = false;
+
+    fn push_leaf(stack: &mut Vec<DebugNode>, label: &mut String) {
+        let text = label.trim();
+        if !text.is_empty() {
+            stack.last_mut().unwrap().children.push(DebugNode {
+                label: text.to_string(),
+                children: Vec::new(),
+                is_container: false,
+            });
+        }
+        label.clear();
+    }
+
+    for c in text.chars() {
+        if let Some(quote_char) = quote {
+            label.push(c);
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                _ if c == quote_char => quote = None,
+                _ => {}
+            }
+            continue;
+        }
+
+        match c {
+            '"' | '\'' => {
+                quote = Some(c);
+                label.push(c);
+            }
+            '{' | '(' | '[' => {
+                let text = format!("{}{}", label.trim_start(), c);
+                label.clear();
+                stack.push(DebugNode {
+                    label: text,
+                    children: Vec::new(),
+                    is_container: true,
+                });
+            }
+            '}' | ')' | ']' => {
+                if stack.len() == 1 {
+                    return None;
+                }
+                push_leaf(&mut stack, &mut label);
+                let node = stack.pop().unwrap();
+                stack.last_mut().unwrap().children.push(node);
+            }
+            ',' => push_leaf(&mut stack, &mut label),
+            '\n' => label.push(' '),
+            _ => label.push(c),
+        }
+    }
+    if quote.is_some() || stack.len() > 1 {
+        return None;
+    }
+    push_leaf(&mut stack, &mut label);
+
+    let mut root = stack.pop().unwrap();
+    if root.children.len() == 1 {
+        Some(root.children.pop().unwrap())
+    } else {
+        Some(root)
+    }
+}
+
+fn diff_nodes(old: &DebugNode, new: &DebugNode, path: String, changes: &mut Vec<StateChange>) {
+    if old.label != new.label || old.is_container != new.is_container {
+        changes.push(StateChange {
+            path,
+            old_value: Some(render(old)),
+            new_value: Some(render(new)),
+        });
+        return;
+    }
+
+    let child_count = old.children.len().max(new.children.len());
+    for index in 0..child_count {
+        let old_child = old.children.get(index);
+        let new_child = new.children.get(index);
+        let child_path = format!(
+            "{}{}",
+            path,
+            segment(old_child.or(new_child).unwrap(), index)
+        );
+
+        match (old_child, new_child) {
+            (Some(old_child), Some(new_child)) => {
+                diff_nodes(old_child, new_child, child_path, changes)
+            }
+            (old_child, new_child) => changes.push(StateChange {
+                path: child_path,
+                old_value: old_child.map(render),
+                new_value: new_child.map(render),
+            }),
+        }
+    }
+}
+
+/// Returns the path segment of a child node.
+fn segment(node: &DebugNode, index: usize) -> String {
+    // Struct field
+    if let Some(colon) = node.label.find(": ") {
+        let name = &node.label[..colon];
+        if !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return format!(".{}", name);
+        }
+    }
+
+    format!(".{}", index)
+}
+
+/// Render a node on a single line.
+fn render(node: &DebugNode) -> String {
+    if !node.is_container {
+        return node.label.clone();
+    }
+
+    let children: Vec<_> = node.children.iter().map(render).collect();
+    let closing = match node.label.chars().last() {
+        Some('{') => "}",
+        Some('(') => ")",
+        Some('[') => "]",
+        _ => "",
+    };
+
+    if node.label.ends_with('{') {
+        format!("{} {} {}", node.label, children.join(", "), closing)
+    } else {
+        format!("{}{}{}", node.label, children.join(", "), closing)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_env_log::test;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct TodoItem {
+        text: String,
+        done: bool,
+    }
+
+    fn change(path: &str, old_value: Option<&str>, new_value: Option<&str>) -> StateChange {
+        StateChange {
+            path: path.to_string(),
+            old_value: old_value.map(String::from),
+            new_value: new_value.map(String::from),
+        }
+    }
+
+    fn diff_states<State: std::fmt::Debug>(
+        old_state: &State,
+        new_state: &State,
+    ) -> Vec<StateChange> {
+        diff_debug_output(
+            &format!("{:#?}", old_state),
+            &format!("{:#?}", new_state),
+            "state",
+        )
+    }
+
+    fn instance(
+        path: &str,
+        local_state: (i32, bool),
+        hook_states: &[i32],
+    ) -> InstanceState<(i32, bool)> {
+        InstanceState::new(
+            path.into(),
+            Some(local_state),
+            hook_states
+                .iter()
+                .map(|state| AnyStateBox::new(*state))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn no_change() {
+        let state = (vec![1, 2], "hello");
+        assert_eq!(diff_states(&state, &state.clone()), vec![]);
+    }
+
+    #[test]
+    fn field_change() {
+        let old_state = (
+            0,
+            TodoItem {
+                text: "Buy milk".to_string(),
+                done: false,
+            },
+        );
+        let mut new_state = old_state.clone();
+        new_state.1.done = true;
+
+        assert_eq!(
+            diff_states(&old_state, &new_state),
+            vec![change(
+                "state.1.done",
+                Some("done: false"),
+                Some("done: true")
+            )],
+        );
+    }
+
+    #[test]
+    fn list_items() {
+        let old_state = vec![1, 2];
+        let new_state = vec![1, 5, 3];
+
+        assert_eq!(
+            diff_states(&old_state, &new_state),
+            vec![
+                change("state.1", Some("2"), Some("5")),
+                change("state.2", None, Some("3")),
+            ],
+        );
+    }
+
+    #[test]
+    fn nested_value_change() {
+        let old_state = Some(vec![1]);
+        let new_state = None::<Vec<i32>>;
+
+        assert_eq!(
+            diff_states(&old_state, &new_state),
+            vec![change("state", Some("Some([1])"), Some("None"))],
+        );
+    }
+
+    #[test]
+    fn compact_output() {
+        let old_state = TodoItem {
+            text: "a, (b)".to_string(),
+            done: false,
+        };
+        let new_state = TodoItem {
+            text: "a, (c)".to_string(),
+            done: false,
+        };
+
+        assert_eq!(
+            diff_debug_output(
+                &format!("{:?}", old_state),
+                &format!("{:?}", new_state),
+                "state"
+            ),
+            vec![change(
+                "state.text",
+                Some("text: \"a, (b)\""),
+                Some("text: \"a, (c)\"")
+            )],
+        );
+    }
+
+    #[test]
+    fn unbalanced_output() {
+        assert_eq!(
+            diff_debug_output("Temperature(20°C", "Temperature(25°C", "state"),
+            vec![change(
+                "state",
+                Some("Temperature(20°C"),
+                Some("Temperature(25°C")
+            )],
+        );
+        assert_eq!(diff_debug_output("a)", "a)", "state"), vec![]);
+    }
+
+    #[test]
+    fn instance_states() {
+        let old_state = instance("App/List/Item[b]", (2, false), &[1, 2]);
+        let new_state = instance("App/List/Item[b]", (2, true), &[1, 3]);
+        assert_eq!(
+            diff_instance(&old_state, &new_state),
+            vec![
+                change("App/List/Item[b].1", Some("false"), Some("true")),
+                change("App/List/Item[b].use_state#1", Some("2"), Some("3")),
+            ],
+        );
+
+        // Another instance took the place of the first one.
+        let other_state = instance("App/List/Item[c]", (5, true), &[1, 3]);
+        assert_eq!(diff_instance(&old_state, &other_state), vec![]);
+    }
+}
@@ -0,0 +1,349 @@
+//! App-wide state, shared by all components.
+//!
+//! The store is set with [`RootHandler::with_store`](crate::RootHandler::with_store). Components
+//! read it with [`CompCtx::use_selector`](crate::CompCtx::use_selector), and modify it with
+//! [`ElementExt::on_store`](crate::ElementExt::on_store).
+
+use std::any::{type_name, Any};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Returns true if the slice a component selected changed since the component was called.
+pub(crate) type SelectorCheck = Box<dyn Fn(&dyn Any) -> bool>;
+
+/// Copies the store, so that the history can record it.
+pub(crate) type CloneStore = fn(&dyn Any) -> Box<dyn Any>;
+
+/// The slices of the store selected by each component instance during a build.
+#[derive(Default)]
+pub(crate) struct Selections {
+    /// Instances by path (see [`element_path`](crate::element_path)).
+    instances: HashMap<Rc<str>, InstanceSelection>,
+    /// Selectors of instances built without a path.
+    unnamed: Vec<SelectorCheck>,
+}
+
+struct InstanceSelection {
+    selectors: Vec<SelectorCheck>,
+    /// The props of the instance and the element tree it returned.
+    output: Box<dyn Any>,
+}
+
+struct BuildStore {
+    store: Box<dyn Any>,
+    /// Selections of the previous build, if only the store changed since then.
+    prev_selections: Option<Selections>,
+    selections: Selections,
+    /// Selectors used by the component being called.
+    selectors: Vec<SelectorCheck>,
+    /// True while building the output of an instance that was called again. Its children may
+    /// have moved to other paths, so they can't reuse the outputs recorded for those paths.
+    in_called_instance: bool,
+}
+
+thread_local! {
+    // The store is moved here for the duration of a build, since Element::build has no
+    // context parameter.
+    static BUILD_STORE: RefCell<Option<BuildStore>> = RefCell::new(None);
+}
+
+/// Run `build` with the store available to [`select`], and return the slices selected by each
+/// component instance.
+///
+/// If `prev_selections` is given, only the store changed since the build that returned them,
+/// so instances whose selected slices are unchanged reuse their previous output (see
+/// [`reuse_output`]).
+pub(crate) fn build_with_store<R>(
+    store: &mut Option<Box<dyn Any>>,
+    prev_selections: Option<Selections>,
+    build: impl FnOnce() -> R,
+) -> (R, Selections) {
+    let store_value = match store.take() {
+        Some(store_value) => store_value,
+        None => return (build(), Selections::default()),
+    };
+
+    let prev_build_store = BUILD_STORE.with(|build_store| {
+        build_store.replace(Some(BuildStore {
+            store: store_value,
+            prev_selections,
+            selections: Selections::default(),
+            selectors: Vec::new(),
+            in_called_instance: false,
+        }))
+    });
+    let output = build();
+    let build_store = BUILD_STORE
+        .with(|build_store| build_store.replace(prev_build_store))
+        .unwrap();
+
+    *store = Some(build_store.store);
+    (output, build_store.selections)
+}
+
+/// Returns the function copying a store of type `S`.
+pub(crate) fn clone_store<S: Clone + 'static>() -> CloneStore {
+    |store| Box::new(store.downcast_ref::<S>().unwrap().clone())
+}
+
+/// Returns true if any of the selected slices has a different value in the given store.
+pub(crate) fn selection_changed(store: &Option<Box<dyn Any>>, selections: &Selections) -> bool {
+    match store {
+        Some(store) => selections
+            .instances
+            .values()
+            .flat_map(|instance| &instance.selectors)
+            .chain(&selections.unnamed)
+            .any(|changed| changed(store.as_ref())),
+        None => false,
+    }
+}
+
+/// Select a slice of the store, and remember it to detect changes.
+pub(crate) fn select<S: 'static, R: Clone + PartialEq + 'static>(
+    selector: impl Fn(&S) -> R + 'static,
+) -> R {
+    BUILD_STORE.with(|build_store| {
+        let mut build_store = build_store.borrow_mut();
+        let build_store = build_store.as_mut().expect(
+            "error: 'use_selector' can only be called in apps with a store (see RootHandler::with_store)",
+        );
+        let store = build_store.store.downcast_ref::<S>().expect(&format!(
+            "error: 'use_selector' expected store of type {}",
+            type_name::<S>(),
+        ));
+
+        let value = selector(store);
+        let selected_value = value.clone();
+        build_store.selectors.push(Box::new(move |store: &dyn Any| {
+            store
+                .downcast_ref::<S>()
+                .map_or(true, |store| selector(store) != selected_value)
+        }));
+        value
+    })
+}
+
+/// Returns the output recorded by the previous build of a component instance, if only the
+/// store changed since then, the instance's selected slices are unchanged, and `is_reusable`
+/// accepts the output (eg because the props are the same).
+///
+/// Outputs are recorded by path, so they're never reused inside an instance that was called
+/// again (see [`build_called_output`]): adding or removing a sibling with the same name would
+/// give another instance the path of the recorded one.
+///
+/// The reused output is kept for the next build.
+pub(crate) fn reuse_output<Output: Clone + 'static>(
+    instance: &Rc<str>,
+    is_reusable: impl FnOnce(&Output) -> bool,
+) -> Option<Output> {
+    BUILD_STORE.with(|build_store| {
+        let mut build_store = build_store.borrow_mut();
+        let build_store = build_store.as_mut()?;
+        if build_store.in_called_instance {
+            return None;
+        }
+        let prev_instance = build_store
+            .prev_selections
+            .as_mut()?
+            .instances
+            .remove(instance)?;
+
+        let store = build_store.store.as_ref();
+        if prev_instance.selectors.iter().any(|changed| changed(store)) {
+            return None;
+        }
+        let output = prev_instance.output.downcast_ref::<Output>()?;
+        if !is_reusable(output) {
+            return None;
+        }
+
+        let output = output.clone();
+        build_store
+            .selections
+            .instances
+            .insert(instance.clone(), prev_instance);
+        Some(output)
+    })
+}
+
+/// Build the output of a component instance that was called, rather than reused.
+pub(crate) fn build_called_output<R>(build: impl FnOnce() -> R) -> R {
+    let set_in_called_instance = |value: bool| {
+        BUILD_STORE.with(|build_store| {
+            let mut build_store = build_store.borrow_mut();
+            let build_store = build_store.as_mut()?;
+            Some(std::mem::replace(
+                &mut build_store.in_called_instance,
+                value,
+            ))
+        })
+    };
+
+    let prev_value = set_in_called_instance(true);
+    let output = build();
+    if let Some(prev_value) = prev_value {
+        set_in_called_instance(prev_value);
+    }
+    output
+}
+
+/// Attach the slices selected since the last call to the given component instance, and
+/// record its output so that the next build can reuse it.
+///
+/// `output` is only called in apps with a store.
+pub(crate) fn record_output<Output: 'static>(
+    instance: Option<&Rc<str>>,
+    output: impl FnOnce() -> Output,
+) {
+    BUILD_STORE.with(|build_store| {
+        let mut build_store = build_store.borrow_mut();
+        let build_store = match build_store.as_mut() {
+            Some(build_store) => build_store,
+            None => return,
+        };
+
+        let selectors = std::mem::take(&mut build_store.selectors);
+        match instance {
+            Some(instance) => {
+                build_store.selections.instances.insert(
+                    instance.clone(),
+                    InstanceSelection {
+                        selectors,
+                        output: Box::new(output()),
+                    },
+                );
+            }
+            None => build_store.selections.unnamed.extend(selectors),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_env_log::test;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct AppState {
+        count: i32,
+        name: String,
+    }
+
+    #[test]
+    fn select_slices() {
+        let mut store: Option<Box<dyn Any>> = Some(Box::new(AppState {
+            count: 1,
+            name: "Alice".to_string(),
+        }));
+
+        let (count, selections) = build_with_store(&mut store, None, || {
+            let count = select(|s: &AppState| s.count);
+            record_output(None, || ());
+            count
+        });
+        assert_eq!(count, 1);
+        assert_eq!(selections.unnamed.len(), 1);
+        assert!(!selection_changed(&store, &selections));
+
+        let app_state = store.as_mut().unwrap().downcast_mut::<AppState>().unwrap();
+        app_state.name = "Bob".to_string();
+        assert!(!selection_changed(&store, &selections));
+
+        let app_state = store.as_mut().unwrap().downcast_mut::<AppState>().unwrap();
+        app_state.count = 2;
+        assert!(selection_changed(&store, &selections));
+    }
+
+    #[test]
+    fn no_store() {
+        let mut store = None;
+        let (value, selections) = build_with_store(&mut store, None, || 42);
+        assert_eq!(value, 42);
+        assert!(selections.instances.is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn select_without_store() {
+        select(|s: &AppState| s.count);
+    }
+
+    #[test]
+    #[should_panic]
+    fn select_wrong_type() {
+        let mut store: Option<Box<dyn Any>> = Some(Box::new(42_u32));
+        build_with_store(&mut store, None, || select(|s: &AppState| s.count));
+    }
+
+    #[test]
+    fn reuse_unchanged_instances() {
+        let mut store: Option<Box<dyn Any>> = Some(Box::new(AppState {
+            count: 1,
+            name: "Alice".to_string(),
+        }));
+        let count_label: Rc<str> = "App/CountLabel".into();
+        let name_label: Rc<str> = "App/NameLabel".into();
+
+        let build = |store: &mut Option<Box<dyn Any>>, prev_selections| {
+            build_with_store(store, prev_selections, || {
+                let count = reuse_output(&count_label, |_: &i32| true).unwrap_or_else(|| {
+                    let count = select(|s: &AppState| s.count);
+                    record_output(Some(&count_label), || count);
+                    count + 100
+                });
+                let name = reuse_output(&name_label, |_: &String| true).unwrap_or_else(|| {
+                    let name = select(|s: &AppState| s.name.clone());
+                    record_output(Some(&name_label), || name.clone());
+                    format!("new {}", name)
+                });
+                (count, name)
+            })
+        };
+
+        let (output, selections) = build(&mut store, None);
+        assert_eq!(output, (101, "new Alice".to_string()));
+
+        let app_state = store.as_mut().unwrap().downcast_mut::<AppState>().unwrap();
+        app_state.name = "Bob".to_string();
+        assert!(selection_changed(&store, &selections));
+
+        // Only the instance whose slice changed is called again.
+        let (output, selections) = build(&mut store, Some(selections));
+        assert_eq!(output, (1, "new Bob".to_string()));
+        assert!(!selection_changed(&store, &selections));
+
+        // Reused outputs are kept for the next build.
+        let (output, _) = build(&mut store, Some(selections));
+        assert_eq!(output, (1, "Bob".to_string()));
+    }
+
+    #[test]
+    fn no_reuse_in_called_instances() {
+        let mut store: Option<Box<dyn Any>> = Some(Box::new(AppState {
+            count: 1,
+            name: "Alice".to_string(),
+        }));
+        let label: Rc<str> = "App/Column/Label".into();
+
+        let build = |store: &mut Option<Box<dyn Any>>, prev_selections| {
+            build_with_store(store, prev_selections, || {
+                build_called_output(|| {
+                    reuse_output(&label, |_: &i32| true).unwrap_or_else(|| {
+                        let count = select(|s: &AppState| s.count);
+                        record_output(Some(&label), || count);
+                        count + 100
+                    })
+                })
+            })
+        };
+
+        let (output, selections) = build(&mut store, None);
+        assert_eq!(output, 101);
+
+        // The path may now belong to another instance.
+        let (output, _) = build(&mut store, Some(selections));
+        assert_eq!(output, 101);
+    }
+}
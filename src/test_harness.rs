@@ -1,15 +1,38 @@
 //! Harness used to mock a druid-and-panoramix environment on a headless target.
 
+use crate::fuzz::{self, FuzzAction, FuzzRng};
 use crate::glue::{DebugState, DruidAppData};
+use crate::inspector::{InspectNode, INSPECT};
+use crate::interaction_script::{InteractionScript, ScriptStep};
+use crate::profiler::{ProfileReport, RenderCounts, SharedBuildCounts, SharedProfiler};
+use crate::time_travel::{Recording, SharedRecording, TimeTravel, TIME_TRAVEL};
+use crate::tree_text;
 use crate::Element;
 use crate::RootWidget;
 
 use druid::tests::harness::Harness as DruidHarness;
+use druid::text::EditAction;
+use druid::widget::prelude::*;
+use druid::widget::TextBox;
 use druid::{
-    Command, Event, KeyEvent, Modifiers, MouseButton, MouseButtons, MouseEvent, Point, RawMods,
+    Command, KbKey, KeyEvent, Modifiers, MouseButton, MouseButtons, MouseEvent, Point, RawMods,
     Vec2, WidgetId, WidgetState,
 };
 use std::any::Any;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashSet};
+use std::rc::Rc;
+
+pub use crate::interaction_script::{
+    InteractionScript, ScriptModifiers, ScriptMouseButton, ScriptStep,
+};
+pub use crate::test_queries::{NodeHandle, Query, QueryResults, Role};
+
+#[cfg(feature = "png")]
+pub use crate::image_snapshot::{assert_image_snapshot, snapshot_path, UPDATE_SNAPSHOTS_VAR};
+pub use crate::image_snapshot::{ImageDiff, RenderedImage};
+
+use tracing::debug;
 
 // TODO
 // use tracing::instrument;
@@ -40,19 +63,77 @@ pub struct Harness<'a, 'b, RootElem: Element + Any> {
     pub druid_harness: &'a mut DruidHarness<'b, DruidAppData>,
     pub mouse_state: MouseEvent,
     pub _markers: std::marker::PhantomData<RootElem>,
+    pub(crate) recording: Option<SharedRecording<RootElem::AggregateChildrenState>>,
+    pub(crate) profiler: Option<SharedProfiler>,
+    root_widget: Rc<RefCell<RootWidget<RootElem>>>,
+    window_size: Size,
+    /// Interactions recorded since [`start_script_recording`](Self::start_script_recording).
+    script: Option<InteractionScript>,
+    /// Number of calls to [`update_root_element`](Self::update_root_element).
+    root_element_updates: usize,
+    /// Builds counted for [`render_counts`](Self::render_counts).
+    build_counts: SharedBuildCounts,
+    render_checkpoint: RenderCheckpoint,
+}
+
+/// Builds and widgets at the last call to
+/// [`reset_render_counts`](Harness::reset_render_counts).
+#[derive(Default)]
+struct RenderCheckpoint {
+    build_counts: BTreeMap<String, usize>,
+    widget_ids: HashSet<WidgetId>,
+}
+
+/// Size of the test window.
+pub const WINDOW_SIZE: Size = Size::new(400., 400.);
+
+/// Shares the root widget between the test window and the windows used to render it.
+struct SharedRootWidget<RootElem: Element> {
+    root_widget: Rc<RefCell<RootWidget<RootElem>>>,
+    /// If true, the widget is only laid out and painted; events go to the test window.
+    paint_only: bool,
 }
 
-impl<RootElem: 'static + Element> Harness<'_, '_, RootElem> {
+impl<'a, 'b, RootElem: 'static + Element> Harness<'a, 'b, RootElem> {
     /// Create the harness, and pass it to a callback function.
     pub fn run_test_window(
         element: RootElem,
         callback: impl FnMut(&mut Harness<'_, '_, RootElem>),
     ) {
-        let root_widget = RootWidget::from_element(element);
+        Self::run_test_root_widget(RootWidget::from_element(element), callback)
+    }
+
+    /// Same as [`run_test_window`](Self::run_test_window), but with a pre-configured root widget.
+    pub fn run_test_root_widget(
+        root_widget: RootWidget<RootElem>,
+        callback: impl FnMut(&mut Harness<'_, '_, RootElem>),
+    ) {
+        Self::run_test_root_widget_with_size(root_widget, WINDOW_SIZE, callback)
+    }
+
+    /// Same as [`run_test_root_widget`](Self::run_test_root_widget), with a test window of the
+    /// given size instead of [`WINDOW_SIZE`].
+    pub fn run_test_root_widget_with_size(
+        root_widget: RootWidget<RootElem>,
+        window_size: Size,
+        callback: impl FnMut(&mut Harness<'_, '_, RootElem>),
+    ) {
         let data: DruidAppData = Default::default();
         let mut callback = callback;
+        let recording = root_widget.recording.clone();
+        let profiler = root_widget.profiler.clone();
+        let mut root_widget = root_widget;
+        let build_counts = root_widget
+            .build_counts
+            .get_or_insert_with(Default::default)
+            .clone();
+        let root_widget = Rc::new(RefCell::new(root_widget));
+        let shared_root_widget = SharedRootWidget {
+            root_widget: root_widget.clone(),
+            paint_only: false,
+        };
 
-        DruidHarness::create_simple(data, root_widget, move |druid_harness| {
+        let harness_closure = move |druid_harness: &mut DruidHarness<DruidAppData>| {
             druid_harness.send_initial_events();
             druid_harness.just_layout();
 
@@ -71,10 +152,25 @@ impl<RootElem: 'static + Element> Harness<'_, '_, RootElem> {
                 druid_harness,
                 mouse_state,
                 _markers: Default::default(),
+                recording,
+                profiler,
+                root_widget: root_widget.clone(),
+                window_size,
+                script: None,
+                root_element_updates: 0,
+                build_counts: build_counts.clone(),
+                render_checkpoint: RenderCheckpoint::default(),
             };
 
             callback(&mut harness);
-        });
+        };
+        DruidHarness::create_with_render(
+            data,
+            shared_root_widget,
+            window_size,
+            harness_closure,
+            |_target| {},
+        );
     }
 
     /// Retrieve a copy of this widget's `WidgetState`, or die trying.
@@ -102,17 +198,527 @@ impl<RootElem: 'static + Element> Harness<'_, '_, RootElem> {
         self.druid_harness.try_get_debug_state(widget_id)
     }
 
+    /// Returns true if a widget of the window shows exactly the given text (eg a label).
+    pub fn contains_text(&self, text: &str) -> bool {
+        fn contains_text(debug_state: &DebugState, text: &str) -> bool {
+            debug_state.main_value == text
+                || debug_state
+                    .children
+                    .iter()
+                    .any(|child| contains_text(child, text))
+        }
+
+        contains_text(&self.get_root_debug_state(), text)
+    }
+
+    /// Describe the current component tree and widget tree.
+    ///
+    /// See [`RootWidget::inspect`].
+    pub fn inspect(&mut self) -> InspectNode {
+        let inspection = std::rc::Rc::new(std::cell::RefCell::new(None));
+        self.druid_harness
+            .submit_command(INSPECT.with(inspection.clone()));
+        let inspection = inspection.borrow_mut().take();
+        inspection.expect("internal error: root widget didn't handle inspect command")
+    }
+
+    /// Start a query on the whole test window.
+    ///
+    /// Queries find elements by what the user sees (their text, their role, the component
+    /// they're in), so tests don't need to know their widget ids.
+    pub fn query(&mut self) -> Query<'_, 'a, 'b, RootElem> {
+        let tree = self.inspect();
+        Query::new(self, tree)
+    }
+
+    /// Returns the innermost element showing the given text.
+    ///
+    /// See [`Query::find_by_text`].
+    pub fn find_by_text(&mut self, text: &str) -> NodeHandle<'_, 'a, 'b, RootElem> {
+        self.query().find_by_text(text)
+    }
+
+    /// Returns the component or element with the given path.
+    ///
+    /// See [`Query::find_by_path`].
+    pub fn find_by_path(&mut self, path: &str) -> NodeHandle<'_, 'a, 'b, RootElem> {
+        self.query().find_by_path(path)
+    }
+
+    /// Returns the id of the first widget created by the node with the given path.
+    ///
+    /// Paths are stable from one run to the next; see [`InspectNode::path`].
+    ///
+    /// ## Panics
+    ///
+    /// Panics if no node has this path, or if the node didn't create a widget.
+    pub fn widget_id_at(&mut self, path: &str) -> WidgetId {
+        self.find_by_path(path)
+            .widget_id()
+            .unwrap_or_else(|| panic!("error: node with path {:?} has no widget", path))
+    }
+
+    /// Returns every element with the given role.
+    ///
+    /// See [`Query::find_all_by_role`].
+    pub fn find_all_by_role(&mut self, role: Role) -> QueryResults<'_, 'a, 'b, RootElem> {
+        self.query().find_all_by_role(role)
+    }
+
+    /// Returns every instance of the component with the given name.
+    ///
+    /// See [`Query::find_component`].
+    pub fn find_component(&mut self, name: &str) -> QueryResults<'_, 'a, 'b, RootElem> {
+        self.query().find_component(name)
+    }
+
+    /// Start a query on the subtree with the given key path.
+    ///
+    /// See [`Query::within`].
+    pub fn within(&mut self, key_path: &[&str]) -> Query<'_, 'a, 'b, RootElem> {
+        self.query().within(key_path)
+    }
+
+    /// Describe the test window as indented text, one component or element per line.
+    ///
+    /// See [`InspectNode::to_text`].
+    pub fn tree_text(&mut self) -> String {
+        self.inspect().to_text()
+    }
+
+    /// Check that the test window matches the expected text, in the format of
+    /// [`tree_text`](Self::tree_text).
+    ///
+    /// The indentation common to every line of `expected`, and the blank lines around it, are
+    /// ignored, so the expected tree can be written as an indented string literal.
+    ///
+    /// ## Panics
+    ///
+    /// Panics with a line-by-line diff if the trees don't match.
+    pub fn assert_tree_text(&mut self, expected: &str) {
+        let expected = tree_text::normalize(expected);
+        let actual = self.tree_text();
+        if let Some(diff) = tree_text::diff(&expected, &actual) {
+            panic!(
+                "error: element tree doesn't match the expected text (- expected, + actual):\n{}",
+                diff
+            );
+        }
+    }
+
     /// Send a druid command through the widget tree.
     pub fn submit_command(&mut self, cmd: impl Into<Command>) {
         self.druid_harness.submit_command(cmd)
     }
 
+    /// Process messages sent with an [`ExternalHandle`](crate::ExternalHandle).
+    ///
+    /// In a running application, sending a message wakes up the application automatically.
+    /// The test harness doesn't, so tests must call this method after sending messages.
+    pub fn process_external_messages(&mut self) {
+        self.druid_harness
+            .submit_command(crate::external::PROCESS_EXTERNAL_MESSAGES)
+    }
+
+    /// Fire the timers started with [`Effects::start_timer`](crate::Effects::start_timer), in
+    /// the order they were started.
+    ///
+    /// The test harness has no clock, so timers never fire on their own; tests call this method
+    /// instead of waiting for the delay.
+    pub fn fire_timers(&mut self) {
+        let mut tokens: Vec<_> = self.root_widget.borrow().timers.keys().copied().collect();
+        tokens.sort_by_key(|token| token.into_raw());
+        for token in tokens {
+            self.druid_harness.event(Event::Timer(token));
+        }
+    }
+
+    /// Returns a copy of the session recorded so far.
+    ///
+    /// Returns `None` unless the root widget was created with
+    /// [`RootWidget::with_recording`].
+    pub fn recording(&self) -> Option<Recording<RootElem::AggregateChildrenState>> {
+        self.recording
+            .as_ref()
+            .map(|recording| recording.borrow().clone())
+    }
+
+    /// Returns the statistics collected so far by the profiler.
+    ///
+    /// Returns `None` unless the root widget was created with
+    /// [`RootWidget::with_profiler`].
+    pub fn profile_report(&self) -> Option<ProfileReport> {
+        self.profiler
+            .as_ref()
+            .map(|profiler| profiler.borrow().report())
+    }
+
+    /// Returns the builds of each component instance, and the widgets created and destroyed,
+    /// since the last call to [`reset_render_counts`](Self::reset_render_counts) (or since
+    /// the window was created).
+    ///
+    /// Used to check that an interaction doesn't rebuild more than it should; eg that
+    /// toggling an item of a list only rebuilds the item and the list, or that an identical
+    /// update doesn't create any widget.
+    pub fn render_counts(&mut self) -> RenderCounts {
+        let build_counts = self.build_counts.borrow().clone();
+        let builds = build_counts
+            .into_iter()
+            .filter_map(|(instance, count)| {
+                let prev_count = self
+                    .render_checkpoint
+                    .build_counts
+                    .get(&instance)
+                    .copied()
+                    .unwrap_or(0);
+                if count > prev_count {
+                    Some((instance, count - prev_count))
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        let widget_ids = widget_ids(&self.inspect());
+        let prev_widget_ids = &self.render_checkpoint.widget_ids;
+        RenderCounts {
+            builds,
+            widgets_created: widget_ids.difference(prev_widget_ids).count(),
+            widgets_destroyed: prev_widget_ids.difference(&widget_ids).count(),
+        }
+    }
+
+    /// Start counting builds and widget changes from the current state.
+    ///
+    /// See [`render_counts`](Self::render_counts).
+    pub fn reset_render_counts(&mut self) {
+        let build_counts = self.build_counts.borrow().clone();
+        self.render_checkpoint = RenderCheckpoint {
+            build_counts,
+            widget_ids: widget_ids(&self.inspect()),
+        };
+    }
+
+    /// Check that exactly the given component instances were built since the last call to
+    /// [`reset_render_counts`](Self::reset_render_counts).
+    ///
+    /// Instances are identified by their element path, eg `App/Column/TodoItem#2` (see
+    /// [`ComponentProfile::instance`](crate::ComponentProfile::instance)).
+    ///
+    /// ## Panics
+    ///
+    /// Panics if an instance wasn't built, or if another instance was built.
+    pub fn assert_rebuilt(&mut self, instances: &[&str]) {
+        let render_counts = self.render_counts();
+        let mut expected = instances.to_vec();
+        expected.sort_unstable();
+        let rebuilt = render_counts.rebuilt_instances();
+        if rebuilt != expected {
+            panic!(
+                "error: expected {:?} to be rebuilt, but {:?} were rebuilt",
+                expected, rebuilt
+            );
+        }
+    }
+
+    /// Show the app as it was at a recorded state.
+    ///
+    /// See [`TIME_TRAVEL`](crate::TIME_TRAVEL).
+    pub fn time_travel(&mut self, time_travel: TimeTravel) {
+        self.druid_harness
+            .submit_command(TIME_TRAVEL.with(time_travel))
+    }
+
+    /// Send every event of a recorded session to the window, in order, and check that each
+    /// replayed event leads to the recorded state.
+    ///
+    /// The root widget must record its own session (see [`RootWidget::with_recording`]), so
+    /// that its states can be compared with the recorded ones.
+    ///
+    /// ## Panic
+    ///
+    /// Panics if the root widget doesn't record its session, or if a replayed event leads to
+    /// a different state than the recorded one.
+    pub fn replay(&mut self, recording: &Recording<RootElem::AggregateChildrenState>) {
+        let own_recording = self.recording.clone().expect(
+            "error: 'replay' can only be called if the root widget records its session (see RootWidget::with_recording)",
+        );
+        let mut transitions = recording.transitions.iter().peekable();
+
+        for (event_index, event) in recording.events.iter().enumerate() {
+            self.druid_harness.event(event.clone());
+
+            let transition = match transitions.peek() {
+                Some(transition) if transition.event_index == event_index => {
+                    transitions.next().unwrap()
+                }
+                _ => continue,
+            };
+            let actual_state = own_recording.borrow().latest_state().clone();
+            assert!(
+                actual_state == transition.state,
+                "error: replayed event #{} ({:?}) didn't lead to the recorded state\nrecorded: {:?}\nactual: {:?}",
+                event_index,
+                event,
+                transition.state,
+                actual_state,
+            );
+        }
+    }
+
+    /// Start recording the interactions sent through the harness into a script.
+    ///
+    /// Mouse and keyboard events, text input and calls to
+    /// [`update_root_element`](Self::update_root_element) are recorded, as well as
+    /// [`checkpoint`](Self::checkpoint)s. Replaying the script with
+    /// [`replay_script`](Self::replay_script) reproduces the interactions, and checks that they
+    /// lead to the same checkpoints.
+    pub fn start_script_recording(&mut self) {
+        self.script = Some(InteractionScript::new());
+    }
+
+    /// Stop recording interactions, and return the recorded script.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if [`start_script_recording`](Self::start_script_recording) wasn't called.
+    pub fn stop_script_recording(&mut self) -> InteractionScript {
+        self.script
+            .take()
+            .expect("error: the harness isn't recording a script")
+    }
+
+    /// Record the current state of the window in the script, under the given name.
+    ///
+    /// Does nothing if the harness isn't recording a script.
+    pub fn checkpoint(&mut self, name: &str) {
+        if self.script.is_some() {
+            let debug_state = format!("{:#?}", self.get_root_debug_state());
+            self.record_step(ScriptStep::Checkpoint {
+                name: name.to_string(),
+                debug_state,
+            });
+        }
+    }
+
+    /// Send the interactions of a script to the window, in order.
+    ///
+    /// `UpdateRootElement` steps use the elements of `root_elements`, in order.
+    ///
+    /// ## Panic
+    ///
+    /// Panics if the state of the window at a checkpoint isn't the recorded one, or if a
+    /// textbox of the script isn't found.
+    pub fn replay_script(&mut self, script: &InteractionScript, root_elements: &[RootElem]) {
+        for step in &script.steps {
+            match step {
+                ScriptStep::MouseMove { x, y } => self.mouse_move((*x, *y)),
+                ScriptStep::MouseDown { button } => self.mouse_button_press((*button).into()),
+                ScriptStep::MouseUp { button } => self.mouse_button_release((*button).into()),
+                ScriptStep::Wheel { dx, dy } => self.scroll_by((*dx, *dy)),
+                ScriptStep::KeyDown { key, mods } => self.key_down(parse_key(key), *mods),
+                ScriptStep::KeyUp { key, mods } => self.key_up(parse_key(key), *mods),
+                ScriptStep::FocusNext => self.focus_next(),
+                ScriptStep::Resize { width, height } => self.resize((*width, *height)),
+                ScriptStep::TypeText { path, text } => {
+                    let textbox_id = self.widget_id_at(path);
+                    self.type_text(textbox_id, text);
+                }
+                ScriptStep::Paste { path, text } => {
+                    let textbox_id = self.widget_id_at(path);
+                    self.paste(textbox_id, text);
+                }
+                ScriptStep::SelectAll { path } => {
+                    let textbox_id = self.widget_id_at(path);
+                    self.select_all(textbox_id);
+                }
+                ScriptStep::Backspace { path } => {
+                    let textbox_id = self.widget_id_at(path);
+                    self.backspace(textbox_id);
+                }
+                ScriptStep::UpdateRootElement { index } => {
+                    let new_root = root_elements.get(*index).unwrap_or_else(|| {
+                        panic!(
+                            "error: script updates the root element with element #{}, but only {} were given",
+                            index,
+                            root_elements.len()
+                        )
+                    });
+                    self.update_root_element(new_root.clone());
+                }
+                ScriptStep::Checkpoint { name, debug_state } => {
+                    let actual_debug_state = format!("{:#?}", self.get_root_debug_state());
+                    assert!(
+                        actual_debug_state == *debug_state,
+                        "error: checkpoint '{}' doesn't match the recorded state\nrecorded: {}\nactual: {}",
+                        name,
+                        debug_state,
+                        actual_debug_state,
+                    );
+                    self.checkpoint(name);
+                }
+            }
+        }
+    }
+
+    fn record_step(&mut self, step: ScriptStep) {
+        if let Some(script) = &mut self.script {
+            script.steps.push(step);
+        }
+    }
+
+    /// Returns the element path of the textbox with the given id, for script steps.
+    fn textbox_path(&mut self, textbox_id: WidgetId) -> String {
+        self.inspect()
+            .path_of(textbox_id)
+            .unwrap_or_else(|| panic!("error: no textbox with id {:?}", textbox_id))
+            .to_string()
+    }
+
+    /// Interact randomly with the buttons, checkboxes and textboxes of the window.
+    ///
+    /// See [`fuzz_with_invariant`](Self::fuzz_with_invariant).
+    pub fn fuzz(&mut self, seed: u64, steps: usize) {
+        self.fuzz_with_invariant(seed, steps, |_| Ok(()))
+    }
+
+    /// Interact randomly with the buttons, checkboxes and textboxes of the window, and check
+    /// invariants after each step.
+    ///
+    /// At each step, an interactive widget is picked in the tree; buttons and checkboxes are
+    /// clicked, text is typed, pasted or erased in textboxes. The same seed always leads to the
+    /// same interactions. After each step, the harness checks that:
+    ///
+    /// - the step didn't panic,
+    /// - every widget of the virtual DOM is in the druid tree, only once, and no widget of the
+    ///   druid tree is left out of the virtual DOM,
+    /// - `invariant` returns `Ok` for the inspector tree (see [`inspect`](Self::inspect)),
+    ///   whose component nodes hold the local state of each component.
+    ///
+    /// Stops early if the window has no interactive widget.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if a check fails, with the seed and the steps which led to the failure. Running
+    /// the fuzzer with the same seed and number of steps reproduces it; if the harness is
+    /// recording a script (see [`start_script_recording`](Self::start_script_recording)),
+    /// the steps are recorded too.
+    pub fn fuzz_with_invariant(
+        &mut self,
+        seed: u64,
+        steps: usize,
+        mut invariant: impl FnMut(&InspectNode) -> Result<(), String>,
+    ) {
+        let mut rng = FuzzRng::new(seed);
+        let mut actions = Vec::new();
+
+        for step in 0..steps {
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(
+                || -> Result<bool, String> {
+                    let action = match fuzz::choose_action(&self.inspect(), &mut rng) {
+                        Some(action) => action,
+                        None => return Ok(false),
+                    };
+                    actions.push(action.clone());
+                    self.run_fuzz_action(action);
+
+                    let tree = self.inspect();
+                    let druid_harness = &mut self.druid_harness;
+                    fuzz::check_widget_sync(&tree, &mut |widget_id| {
+                        druid_harness.try_get_state(widget_id).is_some()
+                    })?;
+                    invariant(&tree)?;
+                    Ok(true)
+                },
+            ));
+
+            let error = match result {
+                Ok(Ok(true)) => continue,
+                Ok(Ok(false)) => {
+                    debug!("Fuzzing stopped at step {}: no interactive widget", step);
+                    return;
+                }
+                Ok(Err(error)) => error,
+                Err(payload) => format!("panic: {}", fuzz::panic_message(payload.as_ref())),
+            };
+            panic!(
+                "error: fuzzing with seed {} failed at step {}: {}\nsteps: {:#?}\nreproduce with `harness.fuzz({}, {})`",
+                seed,
+                step,
+                error,
+                actions,
+                seed,
+                step + 1,
+            );
+        }
+    }
+
+    fn run_fuzz_action(&mut self, action: FuzzAction) {
+        match action {
+            FuzzAction::Click(widget_id) => crate::test_queries::click_on(self, widget_id),
+            FuzzAction::TypeText(widget_id, text) => self.type_text(widget_id, &text),
+            FuzzAction::Paste(widget_id, text) => self.paste(widget_id, &text),
+            FuzzAction::SelectAll(widget_id) => self.select_all(widget_id),
+            FuzzAction::Backspace(widget_id) => self.backspace(widget_id),
+        }
+    }
+
+    /// Paint the test window to an image.
+    ///
+    /// The window is painted with a software renderer, so this doesn't need a GPU.
+    pub fn render_to_image(&mut self) -> RenderedImage {
+        let window_size = self.window_size;
+        let shared_root_widget = SharedRootWidget {
+            root_widget: self.root_widget.clone(),
+            paint_only: true,
+        };
+
+        // The druid harness only gives access to the image once it's closed, so the root
+        // widget is painted in a separate window.
+        let mut image = None;
+        DruidHarness::create_with_render(
+            DruidAppData::default(),
+            shared_root_widget,
+            window_size,
+            |druid_harness| {
+                druid_harness.send_initial_events();
+                druid_harness.just_layout();
+                druid_harness.paint();
+            },
+            |target| {
+                image = Some(RenderedImage::from_premultiplied(
+                    window_size.width as usize,
+                    window_size.height as usize,
+                    &target.into_raw(),
+                ));
+            },
+        );
+        image.unwrap()
+    }
+
+    /// Compare the test window to a reference image.
+    ///
+    /// Combines [`render_to_image`](Self::render_to_image) and [`assert_image_snapshot`], with
+    /// no pixel allowed to differ by more than `tolerance`.
+    ///
+    /// Requires the `png` feature.
+    #[cfg(feature = "png")]
+    pub fn assert_image_snapshot(&mut self, name: &str, tolerance: u8) {
+        let image = self.render_to_image();
+        assert_image_snapshot(&image, name, tolerance, 0);
+    }
+
     /// Update the entire harness with a new element, which replaces the one passed to [`Harness::run_test_window`].
     ///
     /// This is especially useful to test implementations of [`VirtualDom::reconcile`](crate::internals::VirtualDom::reconcile)
     pub fn update_root_element(&mut self, new_root: RootElem) {
         use druid::{Selector, Target};
 
+        self.record_step(ScriptStep::UpdateRootElement {
+            index: self.root_element_updates,
+        });
+        self.root_element_updates += 1;
+
         let selector = Selector::new("update_root_element");
         let command = Command::new(selector, new_root, Target::Global);
 
@@ -125,6 +731,7 @@ impl<RootElem: 'static + Element> Harness<'_, '_, RootElem> {
         self.mouse_state.pos = pos;
         self.mouse_state.window_pos = pos;
         self.mouse_state.button = MouseButton::None;
+        self.record_step(ScriptStep::MouseMove { x: pos.x, y: pos.y });
 
         self.druid_harness
             .event(Event::MouseMove(self.mouse_state.clone()));
@@ -134,6 +741,9 @@ impl<RootElem: 'static + Element> Harness<'_, '_, RootElem> {
     pub fn mouse_button_press(&mut self, button: MouseButton) {
         self.mouse_state.buttons.insert(button);
         self.mouse_state.button = button;
+        self.record_step(ScriptStep::MouseDown {
+            button: button.into(),
+        });
 
         self.druid_harness
             .event(Event::MouseDown(self.mouse_state.clone()));
@@ -143,6 +753,9 @@ impl<RootElem: 'static + Element> Harness<'_, '_, RootElem> {
     pub fn mouse_button_release(&mut self, button: MouseButton) {
         self.mouse_state.buttons.remove(button);
         self.mouse_state.button = button;
+        self.record_step(ScriptStep::MouseUp {
+            button: button.into(),
+        });
 
         self.druid_harness
             .event(Event::MouseUp(self.mouse_state.clone()));
@@ -168,11 +781,273 @@ impl<RootElem: 'static + Element> Harness<'_, '_, RootElem> {
         self.mouse_move(widget_center);
     }
 
+    /// Returns true if the mouse is over the widget with the given id, ie if druid considers
+    /// it "hot".
+    ///
+    /// ## Panics
+    ///
+    /// Panics if no widget of the window has this id.
+    pub fn hover_state(&mut self, id: WidgetId) -> bool {
+        self.inspect()
+            .find(|node| node.widget_id == Some(id))
+            .unwrap_or_else(|| panic!("error: widget {:?} isn't in the test window", id))
+            .is_hot
+    }
+
+    /// Send a Wheel event to the window, at the current mouse position.
+    ///
+    /// A positive `y` scrolls down, a positive `x` scrolls right; the content doesn't scroll
+    /// past its edges. See [`scroll_offset`](Self::scroll_offset).
+    pub fn scroll_by(&mut self, delta: impl Into<Vec2>) {
+        let delta = delta.into();
+        self.mouse_state.button = MouseButton::None;
+        self.mouse_state.wheel_delta = delta;
+        self.record_step(ScriptStep::Wheel {
+            dx: delta.x,
+            dy: delta.y,
+        });
+
+        self.druid_harness
+            .event(Event::Wheel(self.mouse_state.clone()));
+        self.mouse_state.wheel_delta = Vec2::ZERO;
+    }
+
+    /// Returns how far the content of the window is scrolled.
+    pub fn scroll_offset(&self) -> Vec2 {
+        self.root_widget
+            .borrow()
+            .widget
+            .as_ref()
+            .map_or(Vec2::ZERO, |widget| widget.widget().offset())
+    }
+
+    /// Resize the test window, and lay it out again.
+    pub fn resize(&mut self, size: impl Into<Size>) {
+        let size = size.into();
+        self.window_size = size;
+        self.record_step(ScriptStep::Resize {
+            width: size.width,
+            height: size.height,
+        });
+
+        self.druid_harness.event(Event::WindowSize(size));
+        self.druid_harness.just_layout();
+    }
+
+    /// Returns the size of the test window.
+    pub fn window_size(&self) -> Size {
+        self.window_size
+    }
+
+    /// Press Tab, which moves keyboard focus to the next focusable widget, eg from a textbox
+    /// to the next one.
+    ///
+    /// Focus moves along druid's focus chain, which only starts from a focused widget: click
+    /// on a textbox first to give it focus.
+    pub fn focus_next(&mut self) {
+        self.record_step(ScriptStep::FocusNext);
+        self.press_key(RawMods::None, KbKey::Tab);
+    }
+
+    /// Returns the id of the widget with keyboard focus, if any.
+    pub fn focused_widget(&self) -> Option<WidgetId> {
+        self.root_widget.borrow().focused_widget
+    }
+
     /// Send a KeyDown and a KeyUp event to the window.
+    ///
+    /// `key` is either a named key, eg `Backspace` or `Tab`, or a character.
     pub fn keyboard_key(&mut self, key: &str) {
-        let event = KeyEvent::for_test(RawMods::None, key);
+        let key = parse_key(key);
+        self.key_down(key.clone(), ScriptModifiers::default());
+        self.key_up(key, ScriptModifiers::default());
+    }
 
+    fn key_down(&mut self, key: KbKey, mods: ScriptModifiers) {
+        self.record_step(ScriptStep::KeyDown {
+            key: key.to_string(),
+            mods,
+        });
+        let event = KeyEvent::for_test(mods, key);
+        self.druid_harness.event(Event::KeyDown(event));
+    }
+
+    fn key_up(&mut self, key: KbKey, mods: ScriptModifiers) {
+        self.record_step(ScriptStep::KeyUp {
+            key: key.to_string(),
+            mods,
+        });
+        let event = KeyEvent::for_test(mods, key);
+        self.druid_harness.event(Event::KeyUp(event));
+    }
+
+    /// Type text in the textbox with the given id, one key per character.
+    ///
+    /// If the textbox doesn't have keyboard focus, it's clicked, and the cursor is moved to the
+    /// end of its text. Like a user typing, each character makes the textbox emit a
+    /// [`TextChanged`](crate::elements::TextChanged) event. If the text of the box is selected
+    /// (see [`select_all`](Self::select_all)), it's replaced.
+    pub fn type_text(&mut self, textbox_id: WidgetId, text: &str) {
+        if self.script.is_some() {
+            let path = self.textbox_path(textbox_id);
+            self.record_step(ScriptStep::TypeText {
+                path,
+                text: text.to_string(),
+            });
+        }
+        self.focus_textbox(textbox_id);
+        for character in text.chars() {
+            self.press_key(RawMods::None, KbKey::Character(character.to_string()));
+        }
+    }
+
+    /// Select all the text of the textbox with the given id, with Ctrl+A (Cmd+A on macOS).
+    pub fn select_all(&mut self, textbox_id: WidgetId) {
+        if self.script.is_some() {
+            let path = self.textbox_path(textbox_id);
+            self.record_step(ScriptStep::SelectAll { path });
+        }
+        self.focus_textbox(textbox_id);
+        self.press_key(cmd_mods(), KbKey::Character("a".to_string()));
+    }
+
+    /// Press Backspace in the textbox with the given id.
+    ///
+    /// This removes the character before the cursor, or the selected text.
+    pub fn backspace(&mut self, textbox_id: WidgetId) {
+        if self.script.is_some() {
+            let path = self.textbox_path(textbox_id);
+            self.record_step(ScriptStep::Backspace { path });
+        }
+        self.focus_textbox(textbox_id);
+        self.press_key(RawMods::None, KbKey::Backspace);
+    }
+
+    /// Paste text in the textbox with the given id.
+    ///
+    /// Same as [`type_text`](Self::type_text), except the textbox emits a single
+    /// [`TextChanged`](crate::elements::TextChanged) event. The text is sent to the textbox as a
+    /// druid paste edit: the harness has no clipboard, and druid textboxes ignore Ctrl+V (pastes
+    /// come from the Edit menu), so the system clipboard is never read.
+    pub fn paste(&mut self, textbox_id: WidgetId, text: &str) {
+        if self.script.is_some() {
+            let path = self.textbox_path(textbox_id);
+            self.record_step(ScriptStep::Paste {
+                path,
+                text: text.to_string(),
+            });
+        }
+        self.focus_textbox(textbox_id);
+        let edit = TextBox::PERFORM_EDIT
+            .with(EditAction::Paste(text.to_string()))
+            .to(textbox_id);
+        self.druid_harness.submit_command(edit);
+    }
+
+    /// Click on the textbox if it doesn't have keyboard focus, and move the cursor to the end.
+    fn focus_textbox(&mut self, textbox_id: WidgetId) {
+        if self.focused_widget() == Some(textbox_id) {
+            return;
+        }
+        let textbox_rect = self
+            .inspect()
+            .window_rect(textbox_id)
+            .unwrap_or_else(|| panic!("error: no textbox with id {:?}", textbox_id));
+
+        // The click is part of the text edit, not a step of its own.
+        let script = self.script.take();
+        self.mouse_move(textbox_rect.center());
+        self.mouse_button_press(MouseButton::Left);
+        self.mouse_button_release(MouseButton::Left);
+        self.script = script;
+
+        self.press_key(RawMods::None, KbKey::End);
+    }
+
+    /// Send a KeyDown and a KeyUp event to the window, without recording them.
+    fn press_key(&mut self, mods: RawMods, key: KbKey) {
+        let event = KeyEvent::for_test(mods, key);
         self.druid_harness.event(Event::KeyDown(event.clone()));
-        self.druid_harness.event(Event::KeyUp(event.clone()));
+        self.druid_harness.event(Event::KeyUp(event));
+    }
+}
+
+/// Parse a key recorded in a script, either a named key (eg `Backspace`) or a character.
+fn parse_key(key: &str) -> KbKey {
+    key.parse()
+        .unwrap_or_else(|_| KbKey::Character(key.to_string()))
+}
+
+/// Modifier of shortcuts like select all: Cmd on macOS, Ctrl elsewhere.
+fn cmd_mods() -> RawMods {
+    if cfg!(target_os = "macos") {
+        RawMods::Meta
+    } else {
+        RawMods::Ctrl
+    }
+}
+
+/// Returns the ids of every widget of the tree.
+fn widget_ids(tree: &InspectNode) -> HashSet<WidgetId> {
+    let mut ids = HashSet::new();
+    let mut stack = vec![tree];
+    while let Some(node) = stack.pop() {
+        ids.extend(node.widget_id);
+        stack.extend(&node.children);
+    }
+    ids
+}
+
+impl<RootElem: Element> Widget<DruidAppData> for SharedRootWidget<RootElem> {
+    fn event(&mut self, ctx: &mut EventCtx, event: &Event, data: &mut DruidAppData, env: &Env) {
+        if !self.paint_only {
+            self.root_widget.borrow_mut().event(ctx, event, data, env);
+        }
+    }
+
+    fn lifecycle(
+        &mut self,
+        ctx: &mut LifeCycleCtx,
+        event: &LifeCycle,
+        data: &DruidAppData,
+        env: &Env,
+    ) {
+        if !self.paint_only {
+            self.root_widget
+                .borrow_mut()
+                .lifecycle(ctx, event, data, env);
+        }
+    }
+
+    fn update(
+        &mut self,
+        ctx: &mut UpdateCtx,
+        old_data: &DruidAppData,
+        data: &DruidAppData,
+        env: &Env,
+    ) {
+        if !self.paint_only {
+            self.root_widget
+                .borrow_mut()
+                .update(ctx, old_data, data, env);
+        }
+    }
+
+    fn layout(
+        &mut self,
+        ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        data: &DruidAppData,
+        env: &Env,
+    ) -> Size {
+        self.root_widget.borrow_mut().layout(ctx, bc, data, env)
+    }
+
+    fn paint(&mut self, ctx: &mut PaintCtx, data: &DruidAppData, env: &Env) {
+        self.root_widget.borrow_mut().paint(ctx, data, env);
+    }
+
+    fn debug_state(&self, data: &DruidAppData) -> DebugState {
+        self.root_widget.borrow().debug_state(data)
     }
 }
@@ -0,0 +1,386 @@
+//! Queries used by tests to find widgets by what the user sees, instead of by id.
+
+use crate::inspector::{InspectNode, InspectNodeKind};
+use crate::test_harness::Harness;
+use crate::Element;
+
+use druid::{MouseButton, WidgetId};
+
+/// The kind of element a node was created by.
+///
+/// See [`Query::find_all_by_role`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Role {
+    Button,
+    Checkbox,
+    Clickable,
+    Column,
+    Label,
+    List,
+    Row,
+    TextBox,
+}
+
+/// A subtree of the test window, in which nodes can be searched.
+///
+/// Returned by [`Harness::query`] and [`Harness::within`].
+pub struct Query<'h, 'a, 'b, RootElem: Element> {
+    harness: &'h mut Harness<'a, 'b, RootElem>,
+    root: InspectNode,
+}
+
+/// Nodes returned by a query, eg [`Query::find_all_by_role`].
+pub struct QueryResults<'h, 'a, 'b, RootElem: Element> {
+    harness: &'h mut Harness<'a, 'b, RootElem>,
+    nodes: Vec<InspectNode>,
+    description: String,
+}
+
+/// A node found by a query, which can be interacted with.
+///
+/// The node is a snapshot of the tree at the time of the query; after interacting with it,
+/// query the tree again to check the new state.
+pub struct NodeHandle<'h, 'a, 'b, RootElem: Element> {
+    harness: &'h mut Harness<'a, 'b, RootElem>,
+    node: InspectNode,
+}
+
+//
+// --- IMPLS
+
+impl Role {
+    fn from_element_name(name: &str) -> Option<Role> {
+        match name {
+            "Button" => Some(Role::Button),
+            "Checkbox" => Some(Role::Checkbox),
+            "Clickable" => Some(Role::Clickable),
+            "Column" => Some(Role::Column),
+            "Label" => Some(Role::Label),
+            "List" => Some(Role::List),
+            "Row" => Some(Role::Row),
+            "TextBox" => Some(Role::TextBox),
+            _ => None,
+        }
+    }
+}
+
+/// Returns true if the text shown by a widget is `text`.
+///
+/// Checkboxes show their label as `[X] label`; only the label is compared.
+fn text_matches(value: &str, text: &str) -> bool {
+    let value = value
+        .strip_prefix("[X] ")
+        .or_else(|| value.strip_prefix("[ ] "))
+        .unwrap_or(value);
+    value == text
+}
+
+fn contains_text(node: &InspectNode, text: &str) -> bool {
+    node.value
+        .as_deref()
+        .map_or(false, |value| text_matches(value, text))
+        || node.children.iter().any(|child| contains_text(child, text))
+}
+
+/// Collect the innermost element nodes whose widgets show `text`.
+fn collect_by_text(node: &InspectNode, text: &str, results: &mut Vec<InspectNode>) -> bool {
+    let mut found_in_children = false;
+    for child in &node.children {
+        found_in_children |= collect_by_text(child, text, results);
+    }
+
+    if found_in_children {
+        return true;
+    }
+    if node.kind == InspectNodeKind::Element && contains_text(node, text) {
+        results.push(node.clone());
+        return true;
+    }
+    false
+}
+
+fn collect_all(
+    node: &InspectNode,
+    predicate: &dyn Fn(&InspectNode) -> bool,
+    results: &mut Vec<InspectNode>,
+) {
+    if predicate(node) {
+        results.push(node.clone());
+    }
+    for child in &node.children {
+        collect_all(child, predicate, results);
+    }
+}
+
+/// Click on the center of a widget.
+///
+/// Unlike [`Harness::mouse_click_on`], this works for widgets nested in other widgets, since
+/// the position of the widget in the window is computed from the whole tree.
+pub(crate) fn click_on<RootElem: Element>(
+    harness: &mut Harness<'_, '_, RootElem>,
+    widget_id: WidgetId,
+) {
+    let widget_rect = harness
+        .inspect()
+        .window_rect(widget_id)
+        .expect("internal error: queried widget isn't in the tree");
+
+    harness.mouse_move(widget_rect.center());
+    harness.mouse_button_press(MouseButton::Left);
+    harness.mouse_button_release(MouseButton::Left);
+}
+
+impl<'h, 'a, 'b, RootElem: Element> Query<'h, 'a, 'b, RootElem> {
+    pub(crate) fn new(harness: &'h mut Harness<'a, 'b, RootElem>, root: InspectNode) -> Self {
+        Query { harness, root }
+    }
+
+    /// Returns the innermost element showing the given text.
+    ///
+    /// Text is compared to the text of labels, buttons, checkboxes and text boxes.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if no element, or more than one element, shows the text.
+    pub fn find_by_text(self, text: &str) -> NodeHandle<'h, 'a, 'b, RootElem> {
+        self.find_all_by_text(text).single()
+    }
+
+    /// Returns every innermost element showing the given text.
+    pub fn find_all_by_text(self, text: &str) -> QueryResults<'h, 'a, 'b, RootElem> {
+        let mut nodes = Vec::new();
+        collect_by_text(&self.root, text, &mut nodes);
+        QueryResults {
+            harness: self.harness,
+            nodes,
+            description: format!("element with text {:?}", text),
+        }
+    }
+
+    /// Returns the component or element with the given path.
+    ///
+    /// See [`InspectNode::path`] for the format of paths.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if no node has this path.
+    pub fn find_by_path(self, path: &str) -> NodeHandle<'h, 'a, 'b, RootElem> {
+        let node = self
+            .root
+            .find_path(path)
+            .unwrap_or_else(|| panic!("error: no node with path {:?}", path))
+            .clone();
+        NodeHandle {
+            harness: self.harness,
+            node,
+        }
+    }
+
+    /// Returns every element with the given role, in depth-first order.
+    pub fn find_all_by_role(self, role: Role) -> QueryResults<'h, 'a, 'b, RootElem> {
+        let mut nodes = Vec::new();
+        collect_all(
+            &self.root,
+            &|node| {
+                node.kind == InspectNodeKind::Element
+                    && Role::from_element_name(&node.name) == Some(role)
+            },
+            &mut nodes,
+        );
+        QueryResults {
+            harness: self.harness,
+            nodes,
+            description: format!("element with role {:?}", role),
+        }
+    }
+
+    /// Returns every instance of the component with the given name, in depth-first order.
+    pub fn find_component(self, name: &str) -> QueryResults<'h, 'a, 'b, RootElem> {
+        let mut nodes = Vec::new();
+        collect_all(
+            &self.root,
+            &|node| node.kind == InspectNodeKind::Component && node.name == name,
+            &mut nodes,
+        );
+        QueryResults {
+            harness: self.harness,
+            nodes,
+            description: format!("component {:?}", name),
+        }
+    }
+
+    /// Restrict the query to the subtree with the given key path.
+    ///
+    /// Each key is searched, depth-first, in the subtree found for the previous key. Keys are
+    /// the keys of [`ElementList`](crate::elements::ElementList) items.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if a key isn't found.
+    pub fn within(self, key_path: &[&str]) -> Self {
+        let mut root = self.root;
+        for key in key_path {
+            root = root
+                .find(|node| node.key.as_deref() == Some(*key))
+                .unwrap_or_else(|| panic!("error: no node with key {:?} in {:?}", key, key_path))
+                .clone();
+        }
+        Query {
+            harness: self.harness,
+            root,
+        }
+    }
+}
+
+impl<'h, 'a, 'b, RootElem: Element> QueryResults<'h, 'a, 'b, RootElem> {
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// The nodes found, in depth-first order.
+    pub fn nodes(&self) -> &[InspectNode] {
+        &self.nodes
+    }
+
+    /// Returns the n-th node found, starting from 0.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if fewer than `n + 1` nodes were found.
+    pub fn nth(self, n: usize) -> NodeHandle<'h, 'a, 'b, RootElem> {
+        if n >= self.nodes.len() {
+            panic!(
+                "error: cannot get {} #{}, only {} found",
+                self.description,
+                n,
+                self.nodes.len()
+            );
+        }
+        NodeHandle {
+            harness: self.harness,
+            node: self.nodes.into_iter().nth(n).unwrap(),
+        }
+    }
+
+    /// Returns the first node found.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if no node was found.
+    pub fn first(self) -> NodeHandle<'h, 'a, 'b, RootElem> {
+        self.nth(0)
+    }
+
+    /// Returns the only node found.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if no node, or more than one node, was found.
+    pub fn single(self) -> NodeHandle<'h, 'a, 'b, RootElem> {
+        if self.nodes.len() != 1 {
+            panic!(
+                "error: expected a single {}, found {}",
+                self.description,
+                self.nodes.len()
+            );
+        }
+        self.nth(0)
+    }
+}
+
+impl<'h, 'a, 'b, RootElem: Element> NodeHandle<'h, 'a, 'b, RootElem> {
+    pub fn node(&self) -> &InspectNode {
+        &self.node
+    }
+
+    /// Returns the id of the first widget of the node, if it created a widget.
+    pub fn widget_id(&self) -> Option<WidgetId> {
+        self.node
+            .find(|node| node.widget_id.is_some())
+            .and_then(|node| node.widget_id)
+    }
+
+    /// Returns the first text shown by the node's widgets.
+    pub fn text(&self) -> Option<&str> {
+        self.node
+            .find(|node| node.value.is_some())
+            .and_then(|node| node.value.as_deref())
+    }
+
+    /// Returns true if the node is a checked checkbox.
+    pub fn is_checked(&self) -> bool {
+        self.text().map_or(false, |value| value.starts_with("[X] "))
+    }
+
+    /// Returns the `Debug` output of the local state, for component nodes.
+    pub fn state(&self) -> Option<&str> {
+        self.node.state.as_deref()
+    }
+
+    /// ## Panics
+    ///
+    /// Panics if the node doesn't show the expected text.
+    pub fn assert_text(&self, expected: &str) {
+        let text = self.text().unwrap_or_default();
+        assert!(
+            text_matches(text, expected),
+            "error: expected text {:?}, found {:?}",
+            expected,
+            text
+        );
+    }
+
+    /// ## Panics
+    ///
+    /// Panics if the node isn't a component, or if the `Debug` output of its local state isn't
+    /// `expected`.
+    pub fn assert_state(&self, expected: &str) {
+        assert_eq!(self.state(), Some(expected));
+    }
+
+    /// Restrict a new query to the subtree of this node.
+    pub fn within(self) -> Query<'h, 'a, 'b, RootElem> {
+        Query::new(self.harness, self.node)
+    }
+
+    /// Click on the center of the node's first widget.
+    pub fn click(self) {
+        let widget_id = self.expect_widget_id("click on");
+        click_on(self.harness, widget_id);
+    }
+
+    /// Type the text in the node's first widget.
+    ///
+    /// See [`Harness::type_text`].
+    pub fn type_text(self, text: &str) {
+        let widget_id = self.expect_widget_id("type in");
+        self.harness.type_text(widget_id, text);
+    }
+
+    fn expect_widget_id(&self, action: &str) -> WidgetId {
+        self.widget_id().unwrap_or_else(|| {
+            panic!(
+                "error: cannot {} {:?} node '{}', it has no widget",
+                action, self.node.kind, self.node.name
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_env_log::test;
+
+    #[test]
+    fn checkbox_text() {
+        assert!(text_matches("Hello", "Hello"));
+        assert!(text_matches("[X] Hello", "Hello"));
+        assert!(text_matches("[ ] Hello", "Hello"));
+        assert!(!text_matches("Hello world", "Hello"));
+    }
+}
@@ -0,0 +1,117 @@
+//! Recording state transitions, and going back to past states.
+
+use druid::{Event, Selector};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Command used to show the app as it was at a past state.
+///
+/// Requires the root widget to record its session (see [`RootWidget::with_recording`](crate::RootWidget::with_recording)).
+pub const TIME_TRAVEL: Selector<TimeTravel> = Selector::new("panoramix.time_travel");
+
+/// Payload of the [`TIME_TRAVEL`] command.
+///
+/// Positions are indices of states: `0` is the initial state, `n` is the state after the
+/// `n`-th transition.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeTravel {
+    /// Go to the state before the current one.
+    StepBack,
+    /// Go to the state after the current one.
+    StepForward,
+    /// Go to the given position.
+    GoTo(usize),
+    /// Go back to the latest state.
+    Resume,
+}
+
+/// A change of the app state.
+#[derive(Debug, Clone)]
+pub struct Transition<State> {
+    /// Index in [`Recording::events`] of the event which caused the transition.
+    pub event_index: usize,
+    /// The app state after the transition.
+    pub state: State,
+}
+
+/// Every event received by an app, and every state transition they caused.
+///
+/// See [`RootWidget::with_recording`](crate::RootWidget::with_recording).
+#[derive(Debug, Clone)]
+pub struct Recording<State> {
+    /// The app state once the app was initialized.
+    pub initial_state: State,
+    /// Every druid event received by the root widget after initialization, in order.
+    pub events: Vec<Event>,
+    /// Every change of the app state, in order.
+    pub transitions: Vec<Transition<State>>,
+}
+
+pub(crate) type SharedRecording<State> = Rc<RefCell<Recording<State>>>;
+
+impl<State> Recording<State> {
+    pub fn new(initial_state: State) -> Self {
+        Recording {
+            initial_state,
+            events: Vec::new(),
+            transitions: Vec::new(),
+        }
+    }
+
+    /// Returns the state at the given position, or `None` if the position is out of bounds.
+    ///
+    /// Position `0` is the initial state, position `n` is the state after the `n`-th transition.
+    pub fn state_at(&self, position: usize) -> Option<&State> {
+        if position == 0 {
+            Some(&self.initial_state)
+        } else {
+            self.transitions
+                .get(position - 1)
+                .map(|transition| &transition.state)
+        }
+    }
+
+    /// Returns the latest state.
+    pub fn latest_state(&self) -> &State {
+        self.state_at(self.transitions.len()).unwrap()
+    }
+
+    /// Returns the position a time travel command leads to, from the given position.
+    pub(crate) fn travel(&self, position: usize, time_travel: TimeTravel) -> usize {
+        let latest = self.transitions.len();
+        match time_travel {
+            TimeTravel::StepBack => position.saturating_sub(1),
+            TimeTravel::StepForward => (position + 1).min(latest),
+            TimeTravel::GoTo(position) => position.min(latest),
+            TimeTravel::Resume => latest,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_env_log::test;
+
+    #[test]
+    fn positions() {
+        let mut recording = Recording::new("a");
+        for (event_index, state) in vec!["b", "c"].into_iter().enumerate() {
+            recording.events.push(Event::WindowConnected);
+            recording.transitions.push(Transition { event_index, state });
+        }
+
+        assert_eq!(recording.state_at(0), Some(&"a"));
+        assert_eq!(recording.state_at(2), Some(&"c"));
+        assert_eq!(recording.state_at(3), None);
+        assert_eq!(recording.latest_state(), &"c");
+
+        assert_eq!(recording.travel(2, TimeTravel::StepBack), 1);
+        assert_eq!(recording.travel(0, TimeTravel::StepBack), 0);
+        assert_eq!(recording.travel(1, TimeTravel::StepForward), 2);
+        assert_eq!(recording.travel(2, TimeTravel::StepForward), 2);
+        assert_eq!(recording.travel(2, TimeTravel::GoTo(0)), 0);
+        assert_eq!(recording.travel(0, TimeTravel::GoTo(10)), 2);
+        assert_eq!(recording.travel(0, TimeTravel::Resume), 2);
+    }
+}
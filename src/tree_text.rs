@@ -0,0 +1,207 @@
+//! Compact text rendering of the element tree, used by golden tests.
+
+use crate::inspector::{InspectNode, InspectNodeKind};
+
+use std::fmt::Write;
+
+/// Number of spaces added for each level of the tree.
+const INDENT: usize = 2;
+
+/// Render nodes as indented text, one component or element per line.
+///
+/// Widget nodes aren't rendered; the nodes under them are rendered as children of the element
+/// that created the widget. Elements show the first text displayed by their widgets, eg
+/// `Label "Hello"`, components are shown as `<Name>` followed by their props and local state
+/// (unless they're `()`), and list items are prefixed with their key, eg `[42] <TodoItem>`.
+pub(crate) fn nodes_to_text(nodes: &[InspectNode]) -> String {
+    let mut text = String::new();
+    for node in nodes {
+        write_node(node, 0, &mut text);
+    }
+    text
+}
+
+fn write_node(node: &InspectNode, depth: usize, text: &mut String) {
+    if node.kind == InspectNodeKind::Widget {
+        for child in &node.children {
+            write_node(child, depth, text);
+        }
+        return;
+    }
+
+    text.push_str(&" ".repeat(depth * INDENT));
+    if let Some(key) = &node.key {
+        write!(text, "[{}] ", key).unwrap();
+    }
+    match node.kind {
+        InspectNodeKind::Component => {
+            write!(text, "<{}>", node.name).unwrap();
+            if let Some(props) = node.props.as_deref().filter(|props| *props != "()") {
+                write!(text, " props={}", props).unwrap();
+            }
+            if let Some(state) = node.state.as_deref().filter(|state| *state != "()") {
+                write!(text, " state={}", state).unwrap();
+            }
+        }
+        InspectNodeKind::Error => {
+            write!(text, "Error {:?}", node.value.as_deref().unwrap_or("")).unwrap();
+        }
+        _ => {
+            text.push_str(&node.name);
+            if let Some(value) = widget_value(node) {
+                write!(text, " {:?}", value).unwrap();
+            }
+        }
+    }
+    text.push('\n');
+
+    for child in &node.children {
+        write_node(child, depth + 1, text);
+    }
+}
+
+/// Returns the first text displayed by the widgets of an element, without looking into
+/// nested elements.
+fn widget_value(node: &InspectNode) -> Option<&str> {
+    node.children
+        .iter()
+        .filter(|child| child.kind == InspectNodeKind::Widget)
+        .find_map(|child| child.value.as_deref().or_else(|| widget_value(child)))
+}
+
+/// Remove the indentation common to every line, and the blank lines around the text.
+///
+/// Lets tests write the expected tree as an indented string literal.
+pub(crate) fn normalize(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().map(str::trim_end).collect();
+    let first = lines.iter().position(|line| !line.is_empty());
+    let last = lines.iter().rposition(|line| !line.is_empty());
+    let lines = match (first, last) {
+        (Some(first), Some(last)) => &lines[first..=last],
+        _ => return String::new(),
+    };
+
+    let indent = lines
+        .iter()
+        .filter(|line| !line.is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+
+    let mut normalized = String::new();
+    for line in lines {
+        normalized.push_str(line.get(indent..).unwrap_or(""));
+        normalized.push('\n');
+    }
+    normalized
+}
+
+/// Line-by-line diff of two texts, with `-` for lines only in `expected` and `+` for lines
+/// only in `actual`.
+///
+/// Returns `None` if the texts are equal.
+pub(crate) fn diff(expected: &str, actual: &str) -> Option<String> {
+    if expected == actual {
+        return None;
+    }
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+
+    // Length of the longest common subsequence of expected[i..] and actual[j..].
+    let mut lcs = vec![vec![0_usize; actual.len() + 1]; expected.len() + 1];
+    for i in (0..expected.len()).rev() {
+        for j in (0..actual.len()).rev() {
+            lcs[i][j] = if expected[i] == actual[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut output = String::new();
+    let (mut i, mut j) = (0, 0);
+    while i < expected.len() || j < actual.len() {
+        if i < expected.len() && j < actual.len() && expected[i] == actual[j] {
+            writeln!(output, "  {}", expected[i]).unwrap();
+            i += 1;
+            j += 1;
+        } else if i < expected.len() && (j == actual.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            writeln!(output, "- {}", expected[i]).unwrap();
+            i += 1;
+        } else {
+            writeln!(output, "+ {}", actual[j]).unwrap();
+            j += 1;
+        }
+    }
+    Some(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_env_log::test;
+
+    fn widget(value: &str, children: Vec<InspectNode>) -> InspectNode {
+        InspectNode {
+            value: Some(value.to_string()),
+            ..InspectNode::new(InspectNodeKind::Widget, "Widget".to_string(), children)
+        }
+    }
+
+    #[test]
+    fn render_tree() {
+        let tree = InspectNode::component(
+            "App",
+            &(),
+            Some(&3),
+            Vec::new(),
+            vec![InspectNode::element(
+                "Column",
+                vec![InspectNode::new(
+                    InspectNodeKind::Widget,
+                    "Flex".to_string(),
+                    vec![
+                        InspectNode::element("Label", vec![widget("Hello", Vec::new())]),
+                        InspectNode::element(
+                            "List",
+                            vec![
+                                InspectNode::element("Button", vec![widget("Go", Vec::new())])
+                                    .with_key("a"),
+                            ],
+                        ),
+                    ],
+                )],
+            )],
+        );
+
+        assert_eq!(
+            nodes_to_text(&[tree]),
+            normalize(
+                r#"
+                <App> state=3
+                  Column
+                    Label "Hello"
+                    List
+                      [a] Button "Go"
+                "#
+            )
+        );
+    }
+
+    #[test]
+    fn normalize_indentation() {
+        assert_eq!(normalize("\n    a\n      b  \n\n"), "a\n  b\n");
+        assert_eq!(normalize("  \n "), "");
+    }
+
+    #[test]
+    fn diff_lines() {
+        assert_eq!(diff("a\nb\n", "a\nb\n"), None);
+        assert_eq!(
+            diff("a\nb\nc\n", "a\nx\nc\n").unwrap(),
+            "  a\n- b\n+ x\n  c\n"
+        );
+        assert_eq!(diff("a\n", "a\nb\n").unwrap(), "  a\n+ b\n");
+    }
+}
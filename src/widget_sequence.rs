@@ -5,6 +5,7 @@ use crate::glue::DebugState;
 use crate::glue::DruidAppData;
 use druid::{
     BoxConstraints, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx, UpdateCtx,
+    WidgetId,
 };
 
 pub trait WidgetSequence {
@@ -45,6 +46,8 @@ pub trait FlexWidget {
     fn paint_rect(&self) -> Rect;
     fn set_origin(&mut self, ctx: &mut LayoutCtx, data: &DruidAppData, env: &Env, origin: Point);
     fn layout_rect(&self) -> Rect;
+    fn is_hot(&self) -> bool;
+    fn widget_id(&self) -> WidgetId;
     fn baseline_offset(&self) -> f64;
     fn paint(&mut self, ctx: &mut PaintCtx, data: &DruidAppData, env: &Env);
     fn debug_state(&self, data: &DruidAppData) -> DebugState;
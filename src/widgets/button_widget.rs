@@ -89,6 +89,14 @@ impl FlexWidget for ButtonWidget {
         self.pod.layout_rect()
     }
 
+    fn is_hot(&self) -> bool {
+        self.pod.is_hot()
+    }
+
+    fn widget_id(&self) -> WidgetId {
+        self.id
+    }
+
     fn baseline_offset(&self) -> f64 {
         self.pod.baseline_offset()
     }
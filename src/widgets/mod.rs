@@ -9,6 +9,7 @@ mod flex_widget;
 mod optional_widget;
 mod single_widget;
 mod styled_container;
+mod subscription_widget;
 mod textbox_widget;
 mod widget_list;
 mod widget_tuple;
@@ -21,6 +22,7 @@ pub use empty_sequence::EmptySequence;
 pub use flex_widget::FlexWidget;
 pub use single_widget::SingleWidget;
 pub use styled_container::Container;
+pub use subscription_widget::SubscriptionWidget;
 pub use textbox_widget::TextBoxWidget;
 pub use widget_list::WidgetList;
 pub use widget_tuple::WidgetTuple;
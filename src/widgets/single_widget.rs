@@ -6,7 +6,7 @@ use crate::widget_sequence::WidgetSequence;
 use druid::kurbo::{Point, Rect, Size};
 use druid::{
     BoxConstraints, Env, Event, EventCtx, LayoutCtx, LifeCycle, LifeCycleCtx, PaintCtx, UpdateCtx,
-    Widget, WidgetPod,
+    Widget, WidgetId, WidgetPod,
 };
 use tracing::trace;
 
@@ -91,6 +91,14 @@ impl<W: Widget<DruidAppData>> FlexWidget for SingleWidget<W> {
         self.pod.layout_rect()
     }
 
+    fn is_hot(&self) -> bool {
+        self.pod.is_hot()
+    }
+
+    fn widget_id(&self) -> WidgetId {
+        self.pod.id()
+    }
+
     fn baseline_offset(&self) -> f64 {
         self.pod.baseline_offset()
     }
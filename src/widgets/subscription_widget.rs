@@ -0,0 +1,105 @@
+use crate::elements::SubscriptionHandle;
+use crate::external::PROCESS_EXTERNAL_MESSAGES;
+use crate::glue::{DebugState, DruidAppData};
+
+use druid::widget::prelude::*;
+use druid::ExtEventSink;
+use std::sync::Arc;
+use tracing::{instrument, trace};
+
+/// An invisible widget which lets its subscription wake up the application.
+///
+/// Used by [`Subscribe`](crate::elements::Subscribe): while the widget is in the widget tree,
+/// sending a message to the subscription wakes up the application, which then emits it.
+pub struct SubscriptionWidget {
+    subscription: Arc<dyn SubscriptionHandle + Send + Sync>,
+    subscribed: bool,
+}
+
+impl SubscriptionWidget {
+    pub(crate) fn new(subscription: Arc<dyn SubscriptionHandle + Send + Sync>) -> Self {
+        SubscriptionWidget {
+            subscription,
+            subscribed: false,
+        }
+    }
+
+    /// Replace the subscription of the widget.
+    ///
+    /// Returns true if messages are already waiting in the new subscription.
+    pub(crate) fn set_subscription(
+        &mut self,
+        subscription: Arc<dyn SubscriptionHandle + Send + Sync>,
+        sink: ExtEventSink,
+    ) -> bool {
+        let mut has_messages = false;
+        if self.subscribed {
+            self.subscription.unsubscribe();
+            has_messages = subscription.subscribe(sink);
+        }
+        self.subscription = subscription;
+        has_messages
+    }
+}
+
+impl Drop for SubscriptionWidget {
+    fn drop(&mut self) {
+        if self.subscribed {
+            self.subscription.unsubscribe();
+        }
+    }
+}
+
+impl Widget<DruidAppData> for SubscriptionWidget {
+    fn event(&mut self, _ctx: &mut EventCtx, _event: &Event, _data: &mut DruidAppData, _env: &Env) {
+    }
+
+    #[instrument(
+        name = "SubscriptionWidget",
+        level = "trace",
+        skip(self, ctx, event, _data, _env)
+    )]
+    fn lifecycle(
+        &mut self,
+        ctx: &mut LifeCycleCtx,
+        event: &LifeCycle,
+        _data: &DruidAppData,
+        _env: &Env,
+    ) {
+        if let LifeCycle::WidgetAdded = event {
+            self.subscribed = true;
+            if self.subscription.subscribe(ctx.get_external_handle()) {
+                trace!("Messages were sent before subscribing");
+                ctx.submit_command(PROCESS_EXTERNAL_MESSAGES);
+            }
+        }
+    }
+
+    fn update(
+        &mut self,
+        _ctx: &mut UpdateCtx,
+        _old_data: &DruidAppData,
+        _data: &DruidAppData,
+        _env: &Env,
+    ) {
+    }
+
+    fn layout(
+        &mut self,
+        _ctx: &mut LayoutCtx,
+        bc: &BoxConstraints,
+        _data: &DruidAppData,
+        _env: &Env,
+    ) -> Size {
+        bc.min()
+    }
+
+    fn paint(&mut self, _ctx: &mut PaintCtx, _data: &DruidAppData, _env: &Env) {}
+
+    fn debug_state(&self, _data: &DruidAppData) -> DebugState {
+        DebugState {
+            display_name: self.short_type_name().to_string(),
+            ..Default::default()
+        }
+    }
+}
@@ -101,6 +101,14 @@ impl FlexWidget for TextBoxWidget {
         self.pod.layout_rect()
     }
 
+    fn is_hot(&self) -> bool {
+        self.pod.is_hot()
+    }
+
+    fn widget_id(&self) -> WidgetId {
+        self.pod.id()
+    }
+
     fn baseline_offset(&self) -> f64 {
         self.pod.baseline_offset()
     }
@@ -3,7 +3,6 @@ use panoramix::elements::{
     Toggled,
 };
 use panoramix::flex::FlexParams;
-use panoramix::internals::WidgetId;
 use panoramix::{component, Column, CompCtx, Element, ElementExt, NoEvent, Row};
 
 use either::{Left, Right};
@@ -52,10 +51,8 @@ fn TaskRow(ctx: &CompCtx, props: TaskItem) -> impl Element<Event = ItemEvent> {
     let md = ctx.use_metadata::<ItemEvent, ()>();
     let text = props.text.clone();
 
-    let checkbox = ElementBox::new(
-        Checkbox::new("", props.is_completed).with_reserved_id(WidgetId::reserved(props.id + 10)),
-    )
-    .bubble_up::<ItemEvent, _, _>(md);
+    let checkbox =
+        ElementBox::new(Checkbox::new("", props.is_completed)).bubble_up::<ItemEvent, _, _>(md);
     let row = if props.is_completed {
         Left(Row!(checkbox, Label::new(text)))
     } else {
@@ -82,7 +79,6 @@ fn AwesomeEditableList(ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEven
     // because the test harness fails to click the button, probably for hitbox reason.
     // We need to investigate why.
     let button_new_task = Button::new("New task")
-        .with_reserved_id(WidgetId::reserved(1))
         .with_flex_params(FlexParams {
             flex: Some(1.0),
             alignment: None,
@@ -115,9 +111,8 @@ fn AwesomeEditableList(ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEven
     });
     let list_view = ElementList::from_keys_elems(list_keys, list_rows);
 
-    let button_delete = Button::new("Delete completed tasks")
-        .with_reserved_id(WidgetId::reserved(2))
-        .on_click(md, |state: &mut AppState, _| {
+    let button_delete =
+        Button::new("Delete completed tasks").on_click(md, |state: &mut AppState, _| {
             state.tasks = std::mem::take(&mut state.tasks)
                 .into_iter()
                 .filter(|task| !task.is_completed)
@@ -142,12 +137,12 @@ use test_env_log::test;
 fn test_all_widgets() {
     let list = AwesomeEditableList::new(());
 
-    let button_new_task_id = WidgetId::reserved(1);
-    let button_delete_id = WidgetId::reserved(2);
-    //let checkbox_0_id = WidgetId::reserved(10);
-    //let checkbox_2_id = WidgetId::reserved(12);
-
     Harness::run_test_window(list, |harness| {
+        let button_new_task_id = harness.widget_id_at("AwesomeEditableList/Column/Row/Button");
+        let button_delete_id = harness.widget_id_at("AwesomeEditableList/Column/Button");
+        //let checkbox_0_id = harness.widget_id_at(".../List/TaskRow[0]/Row/Checkbox");
+        //let checkbox_2_id = harness.widget_id_at(".../List/TaskRow[2]/Row/Checkbox");
+
         let root_state = harness.get_root_debug_state();
         assert_debug_snapshot!(root_state);
 
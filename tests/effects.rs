@@ -0,0 +1,111 @@
+use panoramix::elements::{
+    Button, ButtonClick, ComponentOutput, ElementList, ExternalMessages, Label, TextBox,
+};
+use panoramix::{component, Column, CompCtx, Effects, Element, ElementExt, NoEvent};
+
+use std::time::Duration;
+
+#[component]
+fn Form(ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
+    let md = ctx.use_metadata::<NoEvent, ()>();
+
+    ComponentOutput::new(
+        md,
+        Column!(
+            TextBox::new("first"),
+            TextBox::new("last"),
+            Button::new("Edit last name").on_with_effects(
+                md,
+                |_, _: ButtonClick, effects: &mut Effects| {
+                    effects.request_focus("Form/Column/TextBox#1");
+                }
+            ),
+        ),
+    )
+}
+
+#[component]
+fn LongList(ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
+    let md = ctx.use_metadata::<NoEvent, ()>();
+
+    ComponentOutput::new(
+        md,
+        Column!(
+            Button::new("Go to end").on_with_effects(
+                md,
+                |_, _: ButtonClick, effects: &mut Effects| {
+                    effects.scroll_to("LongList/Column/List/Label[19]");
+                }
+            ),
+            ElementList::from_pairs(
+                (0..20).map(|i| (i.to_string(), Label::new(format!("Item {}", i))))
+            ),
+        ),
+    )
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Tick;
+
+#[component]
+fn Countdown(ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
+    let md = ctx.use_metadata::<NoEvent, u32>();
+    let ticks = ctx.get_local_state(md);
+
+    ComponentOutput::new(
+        md,
+        Column!(
+            Label::new(format!("ticks: {}", ticks)),
+            Button::new("Start").on_with_effects(md, |_, _: ButtonClick, effects: &mut Effects| {
+                effects.start_timer(Duration::from_secs(1), Tick);
+            }),
+            ExternalMessages::<Tick>::new().on(md, |ticks: &mut u32, _: Tick| *ticks += 1),
+        ),
+    )
+}
+
+use druid::Size;
+use panoramix::test_harness::Harness;
+use panoramix::RootWidget;
+use test_env_log::test;
+
+#[test]
+fn focus_effect() {
+    Harness::run_test_root_widget(RootWidget::new(Form), |harness| {
+        let last_name = harness.widget_id_at("Form/Column/TextBox#1");
+        assert_eq!(harness.focused_widget(), None);
+
+        harness.find_by_text("Edit last name").click();
+        assert_eq!(harness.focused_widget(), Some(last_name));
+    });
+}
+
+#[test]
+fn scroll_effect() {
+    let root_widget = RootWidget::new(LongList);
+    Harness::run_test_root_widget_with_size(root_widget, Size::new(200., 100.), |harness| {
+        assert_eq!(harness.scroll_offset().y, 0.);
+
+        harness.find_by_text("Go to end").click();
+        assert!(harness.scroll_offset().y > 0.);
+
+        let last_item = harness.widget_id_at("LongList/Column/List/Label[19]");
+        let rect = harness.inspect().window_rect(last_item).unwrap();
+        assert!(rect.y1 <= 100.);
+    });
+}
+
+#[test]
+fn timer_effect() {
+    Harness::run_test_root_widget(RootWidget::new(Countdown), |harness| {
+        harness.find_by_text("Start").click();
+        assert!(harness.contains_text("ticks: 0"));
+
+        harness.fire_timers();
+        assert!(harness.contains_text("ticks: 1"));
+
+        // Each timer only fires once.
+        harness.fire_timers();
+        assert!(harness.contains_text("ticks: 1"));
+    });
+}
@@ -0,0 +1,77 @@
+use panoramix::elements::{Button, ButtonClick, ComponentOutput, Label};
+use panoramix::{component, Column, CompCtx, Element, ElementExt, NoEvent};
+
+#[component]
+fn ChainedHandlers(ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
+    let md = ctx.use_metadata::<NoEvent, (u32, u32)>();
+    let state = ctx.get_local_state(md);
+
+    ComponentOutput::new(
+        md,
+        Column!(
+            Label::new(format!("first={} second={}", state.0, state.1)),
+            Button::new("Click")
+                .on(md, |state: &mut (u32, u32), _: ButtonClick| state.0 += 1)
+                .on(md, |state: &mut (u32, u32), _: ButtonClick| state.1 += 1),
+        ),
+    )
+}
+
+#[component]
+fn LeafButton(ctx: &CompCtx, _props: ()) -> impl Element<Event = ButtonClick> {
+    let md = ctx.use_metadata::<ButtonClick, u32>();
+    let count = ctx.get_local_state(md);
+
+    ComponentOutput::new(
+        md,
+        Column!(
+            Label::new(format!("leaf={}", count)),
+            Button::new("Click")
+                .on(md, |count: &mut u32, _: ButtonClick| *count += 1)
+                .bubble_up::<ButtonClick, _, _>(md),
+        ),
+    )
+}
+
+#[component]
+fn Container(ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
+    let md = ctx.use_metadata::<NoEvent, u32>();
+    let count = ctx.get_local_state(md);
+
+    ComponentOutput::new(
+        md,
+        Column!(
+            Label::new(format!("container={}", count)),
+            LeafButton::new(()).on(md, |count: &mut u32, _: ButtonClick| *count += 1),
+        ),
+    )
+}
+
+use panoramix::test_harness::Harness;
+use test_env_log::test;
+
+#[test]
+fn chained_handlers() {
+    let element = ChainedHandlers::new(());
+
+    Harness::run_test_window(element, |harness| {
+        let button_id = harness.widget_id_at("ChainedHandlers/Column/Button");
+        harness.mouse_click_on(button_id);
+        harness.mouse_click_on(button_id);
+
+        assert!(harness.contains_text("first=2 second=2"));
+    });
+}
+
+#[test]
+fn bubble_through_components() {
+    let element = Container::new(());
+
+    Harness::run_test_window(element, |harness| {
+        let button_id = harness.widget_id_at("Container/Column/LeafButton/Column/Button");
+        harness.mouse_click_on(button_id);
+
+        assert!(harness.contains_text("leaf=1"));
+        assert!(harness.contains_text("container=1"));
+    });
+}
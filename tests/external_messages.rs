@@ -0,0 +1,59 @@
+use panoramix::elements::{ComponentOutput, ExternalMessages, Label};
+use panoramix::{component, Column, CompCtx, Element, ElementExt, NoEvent, RootWidget};
+
+#[derive(Debug, Clone, PartialEq)]
+struct JobDone(u32);
+
+#[component]
+fn JobResults(ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
+    let md = ctx.use_metadata::<NoEvent, Vec<u32>>();
+    let results = ctx.get_local_state(md);
+
+    ComponentOutput::new(
+        md,
+        Column!(
+            Label::new(format!("results={:?}", results)),
+            ExternalMessages::<JobDone>::new()
+                .on(md, |results: &mut Vec<u32>, job: JobDone| results.push(job.0)),
+        ),
+    )
+}
+
+use panoramix::test_harness::Harness;
+use test_env_log::test;
+
+#[test]
+fn messages_from_other_thread() {
+    let root_widget = RootWidget::new(JobResults);
+    let handle = root_widget.external_handle::<JobDone>();
+
+    // Sent before the app starts
+    handle.send(JobDone(1)).unwrap();
+
+    Harness::run_test_root_widget(root_widget, |harness| {
+        let handle = handle.clone();
+        std::thread::spawn(move || {
+            handle.send(JobDone(2)).unwrap();
+            handle.send(JobDone(3)).unwrap();
+        })
+        .join()
+        .unwrap();
+
+        harness.process_external_messages();
+
+        assert!(harness.contains_text("results=[1, 2, 3]"));
+    });
+}
+
+#[test]
+fn unhandled_message_type() {
+    let root_widget = RootWidget::new(JobResults);
+    let handle = root_widget.external_handle::<String>();
+
+    Harness::run_test_root_widget(root_widget, |harness| {
+        handle.send("Hello".to_string()).unwrap();
+        harness.process_external_messages();
+
+        assert!(harness.contains_text("results=[]"));
+    });
+}
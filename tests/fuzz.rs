@@ -0,0 +1,121 @@
+use panoramix::elements::{
+    Button, ButtonClick, Checkbox, ComponentOutput, ElementList, Label, TextBox, TextChanged,
+    Toggled,
+};
+use panoramix::{component, Column, CompCtx, Element, ElementExt, NoEvent, Row};
+
+#[derive(Debug, Default, Clone, PartialEq)]
+struct ListState {
+    new_item: String,
+    items: Vec<String>,
+    hide_empty: bool,
+}
+
+#[component]
+fn FilteredList(ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
+    let md = ctx.use_metadata::<NoEvent, ListState>();
+    let state = ctx.get_local_state(md);
+
+    let visible_items = state
+        .items
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| !state.hide_empty || !item.trim().is_empty())
+        .map(|(index, item)| {
+            (
+                index.to_string(),
+                Row!(
+                    Label::new(item.clone()),
+                    Button::new("Remove").on(md, move |state: &mut ListState, _: ButtonClick| {
+                        state.items.remove(index);
+                    }),
+                ),
+            )
+        });
+
+    ComponentOutput::new(
+        md,
+        Column!(
+            TextBox::new(state.new_item.clone()).on(
+                md,
+                |state: &mut ListState, event: TextChanged| {
+                    state.new_item = event.new_content;
+                }
+            ),
+            Button::new("Add").on(md, |state: &mut ListState, _: ButtonClick| {
+                let item = std::mem::take(&mut state.new_item);
+                state.items.push(item);
+            }),
+            Checkbox::new("Hide empty", state.hide_empty).on(
+                md,
+                |state: &mut ListState, event: Toggled| {
+                    state.hide_empty = event.new_value;
+                }
+            ),
+            ElementList::from_pairs(visible_items),
+        ),
+    )
+}
+
+use panoramix::test_harness::Harness;
+use panoramix::{InspectNode, InspectNodeKind, RootWidget};
+use test_env_log::test;
+
+fn item_count(tree: &InspectNode) -> usize {
+    let list = tree
+        .find(|node| node.name == "List")
+        .expect("list not found");
+    list.children.len()
+}
+
+#[test]
+fn fuzz_filtered_list() {
+    Harness::run_test_root_widget(RootWidget::new(FilteredList), |harness| {
+        harness.fuzz_with_invariant(1, 200, |tree| {
+            let state = tree.find_component("FilteredList").unwrap();
+            let hide_empty = state.state.as_deref().unwrap().contains("hide_empty: true");
+            let list = tree.find(|node| node.name == "List").unwrap();
+
+            for item in &list.children {
+                let label = item
+                    .find(|node| node.name == "Label" && node.kind == InspectNodeKind::Widget)
+                    .unwrap();
+                let is_empty = label
+                    .value
+                    .as_deref()
+                    .map_or(true, |text| text.trim().is_empty());
+                if hide_empty && is_empty {
+                    return Err(format!("empty item {:?} is shown", item.key));
+                }
+            }
+            Ok(())
+        });
+    });
+}
+
+#[test]
+fn fuzz_is_deterministic() {
+    let mut states = Vec::new();
+    for _ in 0..2 {
+        Harness::run_test_root_widget(RootWidget::new(FilteredList), |harness| {
+            harness.fuzz(42, 50);
+            let tree = harness.inspect();
+            states.push(tree.find_component("FilteredList").unwrap().state.clone());
+        });
+    }
+    assert_eq!(states[0], states[1]);
+}
+
+#[test]
+#[should_panic(expected = "fuzzing with seed 3 failed")]
+fn fuzz_reports_failing_seed() {
+    Harness::run_test_root_widget(RootWidget::new(FilteredList), |harness| {
+        harness.fuzz_with_invariant(3, 200, |tree| {
+            if item_count(tree) >= 2 {
+                Err("the list has two items".to_string())
+            } else {
+                Ok(())
+            }
+        });
+    });
+}
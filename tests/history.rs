@@ -0,0 +1,117 @@
+use panoramix::elements::{Button, ButtonClick, ComponentOutput, Label};
+use panoramix::{component, Column, CompCtx, Effects, Element, ElementExt, NoEvent, RootWidget};
+
+#[component]
+fn Counter(ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
+    let md = ctx.use_metadata::<NoEvent, i32>();
+    let count = ctx.get_local_state(md);
+
+    ComponentOutput::new(
+        md,
+        Column!(
+            Label::new(format!("count={}", count)),
+            Button::new("+").on(md, |count, _: ButtonClick| *count += 1),
+            Button::new("Undo")
+                .on_with_effects(md, |_, _: ButtonClick, effects: &mut Effects| effects
+                    .undo(),),
+            Button::new("Redo")
+                .on_with_effects(md, |_, _: ButtonClick, effects: &mut Effects| effects
+                    .redo(),),
+        ),
+    )
+}
+
+#[component]
+fn StoreCounter(ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
+    let md = ctx.use_metadata::<NoEvent, ()>();
+    let count = ctx.use_selector(|count: &i32| *count);
+
+    ComponentOutput::new(
+        md,
+        Column!(
+            Label::new(format!("count={}", count)),
+            Button::new("+").on_store(|count: &mut i32, _: ButtonClick| *count += 1),
+            Button::new("Undo")
+                .on_with_effects(md, |_, _: ButtonClick, effects: &mut Effects| effects
+                    .undo(),),
+            Button::new("Redo")
+                .on_with_effects(md, |_, _: ButtonClick, effects: &mut Effects| effects
+                    .redo(),),
+        ),
+    )
+}
+
+use panoramix::internals::WidgetId;
+use panoramix::test_harness::Harness;
+use test_env_log::test;
+
+struct ButtonIds {
+    increment: WidgetId,
+    undo: WidgetId,
+    redo: WidgetId,
+}
+
+fn button_ids<RootElem: Element + 'static>(
+    harness: &mut Harness<'_, '_, RootElem>,
+    component: &str,
+) -> ButtonIds {
+    ButtonIds {
+        increment: harness.widget_id_at(&format!("{}/Column/Button", component)),
+        undo: harness.widget_id_at(&format!("{}/Column/Button#1", component)),
+        redo: harness.widget_id_at(&format!("{}/Column/Button#2", component)),
+    }
+}
+
+#[test]
+fn undo_redo() {
+    let root_widget = RootWidget::new(Counter).with_history(10);
+
+    Harness::run_test_root_widget(root_widget, |harness| {
+        let ids = button_ids(harness, "Counter");
+        harness.mouse_click_on(ids.increment);
+        harness.mouse_click_on(ids.increment);
+        harness.mouse_click_on(ids.increment);
+        assert!(harness.contains_text("count=3"));
+
+        harness.mouse_click_on(ids.undo);
+        harness.mouse_click_on(ids.undo);
+        assert!(harness.contains_text("count=1"));
+
+        harness.mouse_click_on(ids.redo);
+        assert!(harness.contains_text("count=2"));
+
+        // Nothing left to redo
+        harness.mouse_click_on(ids.redo);
+        assert!(harness.contains_text("count=2"));
+    });
+}
+
+#[test]
+fn no_history() {
+    let root_widget = RootWidget::new(Counter);
+
+    Harness::run_test_root_widget(root_widget, |harness| {
+        let ids = button_ids(harness, "Counter");
+        harness.mouse_click_on(ids.increment);
+        harness.mouse_click_on(ids.undo);
+        assert!(harness.contains_text("count=1"));
+    });
+}
+
+#[test]
+fn undo_store_changes() {
+    let root_widget = RootWidget::new(StoreCounter).with_store(0).with_history(10);
+
+    Harness::run_test_root_widget(root_widget, |harness| {
+        let ids = button_ids(harness, "StoreCounter");
+        harness.mouse_click_on(ids.increment);
+        harness.mouse_click_on(ids.increment);
+        assert!(harness.contains_text("count=2"));
+
+        harness.mouse_click_on(ids.undo);
+        assert!(harness.contains_text("count=1"));
+
+        harness.mouse_click_on(ids.redo);
+        assert!(harness.contains_text("count=2"));
+    });
+}
@@ -0,0 +1,98 @@
+use panoramix::elements::{Button, ButtonClick, ComponentOutput, ElementList, Label};
+use panoramix::{
+    component, Column, CompCtx, Element, ElementExt, InspectNodeKind, NoEvent, RootWidget,
+};
+
+#[component]
+fn Counter(ctx: &CompCtx, step: i32) -> impl Element<Event = NoEvent> {
+    let md = ctx.use_metadata::<NoEvent, i32>();
+    let count = ctx.get_local_state(md);
+
+    ComponentOutput::new(
+        md,
+        Column!(
+            Label::new(format!("count={}", count)),
+            Button::new("+").on(md, move |count, _: ButtonClick| *count += step),
+        ),
+    )
+}
+
+#[component]
+fn ItemList(_ctx: &CompCtx, items: Vec<String>) -> impl Element<Event = NoEvent> {
+    ElementList {
+        children: items
+            .iter()
+            .map(|item| (item.clone(), Label::new(item.clone())))
+            .collect(),
+    }
+}
+
+use panoramix::test_harness::Harness;
+use test_env_log::test;
+
+#[test]
+fn inspect_component() {
+    let root_widget = RootWidget::with_props(Counter, 1);
+
+    Harness::run_test_root_widget(root_widget, |harness| {
+        let button_id = harness.widget_id_at("Counter/Column/Button");
+        harness.mouse_click_on(button_id);
+
+        let tree = harness.inspect();
+        let counter = tree.find_component("Counter").unwrap();
+        assert_eq!(counter.props.as_deref(), Some("1"));
+        assert_eq!(counter.state.as_deref(), Some("1"));
+        assert_eq!(counter.path.as_deref(), Some("Counter"));
+
+        let column = &counter.children[0];
+        assert_eq!(column.kind, InspectNodeKind::Element);
+        assert_eq!(column.name, "Column");
+
+        let label = tree
+            .find(|node| node.value.as_deref() == Some("count=1"))
+            .unwrap();
+        assert_eq!(label.kind, InspectNodeKind::Widget);
+        assert!(label.layout_rect.is_some());
+
+        let button = tree.find(|node| node.widget_id == Some(button_id)).unwrap();
+        assert_eq!(button.kind, InspectNodeKind::Widget);
+    });
+}
+
+#[test]
+fn inspect_list_keys() {
+    let items = vec!["a".to_string(), "b".to_string()];
+    let root_widget = RootWidget::with_props(ItemList, items);
+
+    Harness::run_test_root_widget(root_widget, |harness| {
+        let tree = harness.inspect();
+        let list = tree
+            .find(|node| node.kind == InspectNodeKind::Element && node.name == "List")
+            .unwrap();
+
+        let keys: Vec<_> = list
+            .children
+            .iter()
+            .map(|item| item.key.as_deref())
+            .collect();
+        assert_eq!(keys, vec![Some("a"), Some("b")]);
+        assert_eq!(list.children[0].name, "Label");
+        assert_eq!(
+            list.children[1].path.as_deref(),
+            Some("ItemList/List/Label[b]")
+        );
+    });
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn inspect_to_json() {
+    let root_widget = RootWidget::with_props(Counter, 1);
+
+    Harness::run_test_root_widget(root_widget, |harness| {
+        let json = harness.inspect().to_json();
+        assert!(json.contains("\"kind\": \"component\""));
+        assert!(json.contains("\"name\": \"Counter\""));
+        assert!(json.contains("\"path\": \"Counter/Column/Button\""));
+    });
+}
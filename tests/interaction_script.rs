@@ -0,0 +1,113 @@
+use panoramix::elements::{Button, ButtonClick, ComponentOutput, Label, TextBox, TextChanged};
+use panoramix::{component, Column, CompCtx, Element, ElementExt, NoEvent};
+
+#[derive(Debug, Default, Clone, PartialEq)]
+struct AppState {
+    name: String,
+    greetings: u32,
+}
+
+#[component]
+fn Greeter(ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
+    let md = ctx.use_metadata::<NoEvent, AppState>();
+    let state = ctx.get_local_state(md);
+
+    ComponentOutput::new(
+        md,
+        Column!(
+            TextBox::new(state.name.clone()).on(md, |state: &mut AppState, event: TextChanged| {
+                state.name = event.new_content;
+            }),
+            Button::new("Greet").on(md, |state: &mut AppState, _: ButtonClick| {
+                state.greetings += 1;
+            }),
+            Label::new(format!("Hello {} (x{})", state.name, state.greetings)),
+        ),
+    )
+}
+
+use panoramix::test_harness::{Harness, InteractionScript, Role, ScriptStep};
+use panoramix::RootWidget;
+use test_env_log::test;
+
+fn record_script() -> InteractionScript {
+    let mut script = None;
+
+    Harness::run_test_root_widget(RootWidget::new(Greeter), |harness| {
+        harness.start_script_recording();
+
+        harness
+            .find_all_by_role(Role::TextBox)
+            .single()
+            .type_text("Alice");
+        harness.checkpoint("typed");
+
+        harness.find_by_text("Greet").click();
+        harness.find_by_text("Greet").click();
+        harness.checkpoint("greeted");
+
+        script = Some(harness.stop_script_recording());
+    });
+
+    script.unwrap()
+}
+
+#[test]
+fn record_and_replay() {
+    let script = record_script();
+    assert_eq!(
+        script.checkpoints().collect::<Vec<_>>(),
+        vec!["typed", "greeted"]
+    );
+    assert!(script.steps.contains(&ScriptStep::TypeText {
+        path: "Greeter/Column/TextBox".to_string(),
+        text: "Alice".to_string(),
+    }));
+
+    Harness::run_test_root_widget(RootWidget::new(Greeter), |harness| {
+        harness.replay_script(&script, &[]);
+        harness.find_by_text("Hello Alice (x2)");
+    });
+}
+
+#[test]
+#[should_panic(expected = "checkpoint 'greeted' doesn't match")]
+fn replay_detects_regression() {
+    let mut script = record_script();
+    // Simulate a change in behavior by dropping the second click.
+    let last_click = script
+        .steps
+        .iter()
+        .rposition(|step| matches!(step, ScriptStep::MouseUp { .. }))
+        .unwrap();
+    script.steps.remove(last_click);
+
+    Harness::run_test_root_widget(RootWidget::new(Greeter), |harness| {
+        harness.replay_script(&script, &[]);
+    });
+}
+
+#[test]
+fn replay_root_element_updates() {
+    let mut script = None;
+    Harness::run_test_window(Label::new("first"), |harness| {
+        harness.start_script_recording();
+        harness.update_root_element(Label::new("second"));
+        harness.checkpoint("updated");
+        script = Some(harness.stop_script_recording());
+    });
+    let script = script.unwrap();
+
+    Harness::run_test_window(Label::new("first"), |harness| {
+        harness.replay_script(&script, &[Label::new("second")]);
+    });
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn script_to_json() {
+    let script = record_script();
+    let json = script.to_json();
+    assert!(json.contains("\"step\": \"checkpoint\""));
+    assert_eq!(InteractionScript::from_json(&json).unwrap(), script);
+}
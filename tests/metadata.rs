@@ -1,24 +1,19 @@
-use panoramix::elements::{Button, ComponentOutput, EmptyElement};
-use panoramix::internals::WidgetId;
+use panoramix::elements::{Button, ButtonClick, ComponentOutput, EmptyElement, Label};
 use panoramix::Metadata;
-use panoramix::{component, CompCtx, Element, ElementExt, NoEvent};
+use panoramix::{component, Column, CompCtx, Element, ElementExt, NoEvent};
 
 #[derive(Debug, Default, Clone, PartialEq)]
 struct MyEvent;
 
 #[component]
 fn UseMetadataTwice(ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
+    let md = ctx.use_metadata::<NoEvent, i32>();
     let _md = ctx.use_metadata::<NoEvent, i32>();
-    let _md = ctx.use_metadata::<NoEvent, i32>();
-    EmptyElement::new()
+    ComponentOutput::new(md, EmptyElement::new())
 }
 
-#[component]
-fn NoComponentOutput(ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
-    let md = ctx.use_metadata::<NoEvent, i32>();
-    let _local_state = ctx.get_local_state(md);
-    EmptyElement::new()
-}
+// Note - Components that call use_metadata but don't return ComponentOutput
+// are rejected at compile time; see the doc of the #[component] macro.
 
 #[component]
 fn WrongState(ctx: &CompCtx, _props: ()) -> impl Element<Event = MyEvent> {
@@ -31,24 +26,48 @@ fn WrongState(ctx: &CompCtx, _props: ()) -> impl Element<Event = MyEvent> {
 }
 
 #[component]
-fn WrongEvent(ctx: &CompCtx, id: WidgetId) -> impl Element<Event = NoEvent> {
+fn WrongEvent(ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
     let md = ctx.use_metadata::<MyEvent, u16>();
-    let button = Button::new("Hello")
-        .with_reserved_id(id)
-        .map_event(md, |_, _event| Some(MyEvent));
+    let button = Button::new("Hello").map_event(md, |_, _event| Some(MyEvent));
     let wrong_md: Metadata<NoEvent, u16> = Default::default();
     ComponentOutput::new(wrong_md, button)
 }
 
 #[component]
-fn CorrectMetadata(ctx: &CompCtx, id: WidgetId) -> impl Element<Event = MyEvent> {
+fn CorrectMetadata(ctx: &CompCtx, _props: ()) -> impl Element<Event = MyEvent> {
     let md = ctx.use_metadata::<MyEvent, u16>();
-    let button = Button::new("Hello")
-        .with_reserved_id(id)
-        .map_event(md, |_, _event| Some(MyEvent));
+    let button = Button::new("Hello").map_event(md, |_, _event| Some(MyEvent));
     ComponentOutput::new(md, button)
 }
 
+#[component]
+fn TwoCounters(ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
+    let md = ctx.use_metadata::<NoEvent, ()>();
+    let (count_a, count_a_handle) = ctx.use_state(|| 0_i32);
+    let (count_b, count_b_handle) = ctx.use_state(|| 100_i32);
+
+    ComponentOutput::new(
+        md,
+        Column!(
+            Label::new(format!("a={} b={}", count_a, count_b)),
+            Button::new("A").on_state(count_a_handle, |count: &mut i32, _: ButtonClick| *count +=
+                1),
+            Button::new("B").on_state(count_b_handle, |count: &mut i32, _: ButtonClick| *count -=
+                1),
+        ),
+    )
+}
+
+#[component]
+fn ConditionalState(ctx: &CompCtx, use_second_state: bool) -> impl Element<Event = NoEvent> {
+    let md = ctx.use_metadata::<NoEvent, ()>();
+    let _ = ctx.use_state(|| 0_i32);
+    if use_second_state {
+        let _ = ctx.use_state(|| 0_i32);
+    }
+    ComponentOutput::new(md, EmptyElement::new())
+}
+
 use panoramix::test_harness::Harness;
 use test_env_log::test;
 
@@ -59,13 +78,6 @@ fn use_metadata_twice() {
     element.build(Default::default());
 }
 
-#[test]
-#[should_panic]
-fn no_component_output() {
-    let element = NoComponentOutput::new(());
-    element.build(Default::default());
-}
-
 #[test]
 #[should_panic]
 fn wrong_state() {
@@ -76,20 +88,45 @@ fn wrong_state() {
 #[test]
 #[should_panic]
 fn wrong_event() {
-    let button_id = WidgetId::reserved(1);
-    let element = WrongEvent::new(button_id);
+    let element = WrongEvent::new(());
 
     Harness::run_test_window(element, |harness| {
+        let button_id = harness.widget_id_at("WrongEvent/Button");
         harness.mouse_click_on(button_id);
     });
 }
 
 #[test]
 fn correct_metadata() {
-    let button_id = WidgetId::reserved(1);
-    let element = CorrectMetadata::new(button_id);
+    let element = CorrectMetadata::new(());
 
     Harness::run_test_window(element, |harness| {
+        let button_id = harness.widget_id_at("CorrectMetadata/Button");
         harness.mouse_click_on(button_id);
     });
 }
+
+#[test]
+fn use_state_slots() {
+    let element = TwoCounters::new(());
+
+    Harness::run_test_window(element, |harness| {
+        let button_a_id = harness.widget_id_at("TwoCounters/Column/Button");
+        let button_b_id = harness.widget_id_at("TwoCounters/Column/Button#1");
+        assert!(harness.contains_text("a=0 b=100"));
+
+        harness.mouse_click_on(button_a_id);
+        harness.mouse_click_on(button_a_id);
+        harness.mouse_click_on(button_b_id);
+
+        assert!(harness.contains_text("a=2 b=99"));
+    });
+}
+
+#[test]
+#[should_panic]
+#[cfg(debug_assertions)]
+fn use_state_changed_order() {
+    let (_, state) = ConditionalState::new(true).build(Default::default());
+    ConditionalState::new(false).build(state);
+}
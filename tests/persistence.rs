@@ -0,0 +1,88 @@
+#![cfg(feature = "serde")]
+
+use panoramix::elements::{Button, ButtonClick, ComponentOutput, Label};
+use panoramix::{component, Column, CompCtx, Element, ElementExt, NoEvent, RootWidget};
+
+#[component]
+fn Counter(ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
+    let count = ctx.use_selector(|count: &i32| *count);
+    Column!(
+        Label::new(format!("count={}", count)),
+        Button::new("+").on_store(|count: &mut i32, _: ButtonClick| *count += 1),
+    )
+}
+
+#[component]
+fn Toggle(ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
+    let md = ctx.use_persisted_metadata::<NoEvent, bool>();
+    let checked = ctx.get_local_state(md);
+
+    ComponentOutput::new(
+        md,
+        Column!(
+            Label::new(format!("checked={}", checked)),
+            Button::new("Toggle").on(md, |checked: &mut bool, _: ButtonClick| {
+                *checked = !*checked;
+            }),
+        ),
+    )
+}
+
+#[component]
+fn Settings(_ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
+    Column!(Toggle::new(()), Toggle::new(()))
+}
+
+use panoramix::test_harness::Harness;
+use test_env_log::test;
+
+fn temp_path(name: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("panoramix_{}_{}.json", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn restore_store() {
+    let path = temp_path("restore_store");
+
+    let root_widget = RootWidget::new(Counter).with_persistence(&path, 0);
+    Harness::run_test_root_widget(root_widget, |harness| {
+        assert!(harness.contains_text("count=0"));
+        let button_id = harness.widget_id_at("Counter/Column/Button");
+        harness.mouse_click_on(button_id);
+        assert!(harness.contains_text("count=1"));
+    });
+
+    // The first change is saved right away.
+    let root_widget = RootWidget::new(Counter).with_persistence(&path, 0);
+    Harness::run_test_root_widget(root_widget, |harness| {
+        assert!(harness.contains_text("count=1"));
+    });
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn restore_local_state() {
+    let path = temp_path("restore_local_state");
+
+    let root_widget = RootWidget::new(Settings).with_component_persistence(&path);
+    Harness::run_test_root_widget(root_widget, |harness| {
+        let second_toggle = harness.widget_id_at("Settings/Column/Toggle#1/Column/Button");
+        harness.mouse_click_on(second_toggle);
+    });
+
+    let root_widget = RootWidget::new(Settings).with_component_persistence(&path);
+    Harness::run_test_root_widget(root_widget, |harness| {
+        let inspection = harness.inspect();
+        let first_toggle = inspection.find_path("Settings/Column/Toggle").unwrap();
+        let second_toggle = inspection.find_path("Settings/Column/Toggle#1").unwrap();
+        assert_eq!(first_toggle.state.as_deref(), Some("false"));
+        assert_eq!(second_toggle.state.as_deref(), Some("true"));
+        // The restored state is used from the first call of the component.
+        assert!(harness.contains_text("checked=true"));
+    });
+
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,113 @@
+use panoramix::elements::{Button, ButtonClick, ComponentOutput, ElementList, Label};
+use panoramix::{
+    component, Column, CompCtx, Element, ElementExt, NoEvent, RenderCause, RootWidget,
+};
+
+#[component]
+fn Counter(ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
+    let md = ctx.use_metadata::<NoEvent, i32>();
+    let count = ctx.get_local_state(md);
+
+    ComponentOutput::new(
+        md,
+        Column!(
+            Label::new(format!("count={}", count)),
+            Button::new("+").on(md, |count, _: ButtonClick| *count += 1),
+        ),
+    )
+}
+
+#[component]
+fn TwoCounters(_ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
+    Column!(Counter::new(()), Counter::new(()))
+}
+
+#[component]
+fn Replacer(ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
+    let md = ctx.use_metadata::<NoEvent, u32>();
+    let generation = ctx.get_local_state(md);
+
+    ComponentOutput::new(
+        md,
+        Column!(
+            Button::new("Replace").on(md, |generation: &mut u32, _: ButtonClick| {
+                *generation += 1;
+            }),
+            // Changing the key replaces the label.
+            ElementList::from_pairs(std::iter::once((
+                generation.to_string(),
+                Label::new("Replaced label"),
+            ))),
+        ),
+    )
+}
+
+use panoramix::test_harness::Harness;
+use test_env_log::test;
+
+#[test]
+fn profile_counters() {
+    let root_widget = RootWidget::new(TwoCounters).with_profiler();
+
+    Harness::run_test_root_widget(root_widget, |harness| {
+        let report = harness.profile_report().unwrap();
+        assert_eq!(report.instances_of("Counter").count(), 2);
+
+        let first_counter = report.get("TwoCounters/Column/Counter").unwrap();
+        assert_eq!(first_counter.build_count, 1);
+        assert_eq!(first_counter.render_causes, vec![RenderCause::Created]);
+        assert!(first_counter.widgets_created > 0);
+
+        let button_id = harness.widget_id_at("TwoCounters/Column/Counter#1/Column/Button");
+        harness.mouse_click_on(button_id);
+
+        let report = harness.profile_report().unwrap();
+        let root = report.get("TwoCounters").unwrap();
+        assert_eq!(root.build_count, 2);
+        assert_eq!(root.reconcile_count, 1);
+
+        let first_counter = report.get("TwoCounters/Column/Counter").unwrap();
+        assert_eq!(first_counter.build_count, 2);
+        assert_eq!(
+            first_counter.render_causes.last(),
+            Some(&RenderCause::ParentRebuilt)
+        );
+
+        let second_counter = report.get("TwoCounters/Column/Counter#1").unwrap();
+        assert_eq!(second_counter.build_count, 2);
+        assert!(matches!(
+            second_counter.render_causes.last(),
+            Some(RenderCause::Changed { props, state }) if props.is_empty() && !state.is_empty()
+        ));
+
+        let table = report.to_string();
+        assert!(table.contains("TwoCounters/Column/Counter#1"));
+    });
+}
+
+#[test]
+fn count_replaced_widgets() {
+    let root_widget = RootWidget::new(Replacer).with_profiler();
+
+    Harness::run_test_root_widget(root_widget, |harness| {
+        let report = harness.profile_report().unwrap();
+        let widgets_created = report.get("Replacer").unwrap().widgets_created;
+        assert_eq!(report.get("Replacer").unwrap().widgets_destroyed, 0);
+
+        harness.find_by_text("Replace").click();
+
+        let report = harness.profile_report().unwrap();
+        let replacer = report.get("Replacer").unwrap();
+        assert_eq!(replacer.widgets_created, widgets_created + 1);
+        assert_eq!(replacer.widgets_destroyed, 1);
+    });
+}
+
+#[test]
+fn no_profiler() {
+    let root_widget = RootWidget::new(TwoCounters);
+
+    Harness::run_test_root_widget(root_widget, |harness| {
+        assert!(harness.profile_report().is_none());
+    });
+}
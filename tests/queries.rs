@@ -0,0 +1,103 @@
+use panoramix::elements::{Button, ButtonClick, Checkbox, ComponentOutput, ElementList, Label};
+use panoramix::{component, Column, CompCtx, Element, ElementExt, NoEvent, Row};
+
+#[component]
+fn TaskRow(ctx: &CompCtx, name: String) -> impl Element<Event = NoEvent> {
+    let md = ctx.use_metadata::<NoEvent, bool>();
+    let completed = ctx.get_local_state(md);
+
+    ComponentOutput::new(
+        md,
+        Row!(
+            Checkbox::new(name, *completed)
+                .on_toggled(md, |completed, event| *completed = event.new_value),
+            Label::new(if *completed { "done" } else { "todo" }),
+        ),
+    )
+}
+
+#[component]
+fn TaskList(ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
+    let md = ctx.use_metadata::<NoEvent, Vec<String>>();
+    let tasks = ctx.get_local_state(md);
+
+    ComponentOutput::new(
+        md,
+        Column!(
+            Label::new(format!("{} tasks", tasks.len())),
+            Button::new("Add task").on(md, |tasks: &mut Vec<String>, _: ButtonClick| {
+                tasks.push(format!("task {}", tasks.len()));
+            }),
+            ElementList::from_pairs(
+                tasks
+                    .iter()
+                    .map(|task| (task.clone(), TaskRow::new(task.clone()))),
+            ),
+        ),
+    )
+}
+
+use panoramix::test_harness::{Harness, Role};
+use panoramix::RootWidget;
+use test_env_log::test;
+
+#[test]
+fn find_by_text() {
+    Harness::run_test_root_widget(RootWidget::new(TaskList), |harness| {
+        harness.find_by_text("0 tasks").assert_text("0 tasks");
+
+        harness.find_by_text("Add task").click();
+        harness.find_by_text("Add task").click();
+
+        assert_eq!(harness.find_by_text("2 tasks").node().name, "Label");
+        assert_eq!(harness.find_all_by_role(Role::Checkbox).len(), 2);
+    });
+}
+
+#[test]
+fn find_component_and_click() {
+    Harness::run_test_root_widget(RootWidget::new(TaskList), |harness| {
+        harness.find_by_text("Add task").click();
+        harness.find_by_text("Add task").click();
+
+        harness
+            .find_component("TaskRow")
+            .nth(1)
+            .assert_state("false");
+        harness.find_by_text("task 1").click();
+
+        harness
+            .find_component("TaskRow")
+            .nth(1)
+            .assert_state("true");
+        harness
+            .find_component("TaskRow")
+            .nth(0)
+            .assert_state("false");
+        assert!(harness.find_by_text("task 1").is_checked());
+        assert_eq!(harness.query().find_all_by_text("done").len(), 1);
+    });
+}
+
+#[test]
+fn within_key_path() {
+    Harness::run_test_root_widget(RootWidget::new(TaskList), |harness| {
+        harness.find_by_text("Add task").click();
+        harness.find_by_text("Add task").click();
+
+        harness
+            .within(&["task 0"])
+            .find_all_by_role(Role::Checkbox)
+            .single()
+            .click();
+
+        let first_row = harness.find_component("TaskRow").first();
+        first_row.assert_state("true");
+        first_row.within().find_by_text("done");
+
+        assert_eq!(
+            harness.within(&["task 1"]).find_all_by_text("todo").len(),
+            1
+        );
+    });
+}
@@ -0,0 +1,86 @@
+use panoramix::elements::{Button, ButtonClick, ComponentOutput, Label};
+use panoramix::{component, Column, CompCtx, Element, ElementExt, NoEvent, Reducer};
+
+#[derive(Debug, Clone, PartialEq)]
+enum CounterAction {
+    Add(i32),
+    Reset,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+struct CounterState {
+    count: i32,
+    changes: u32,
+}
+
+impl Reducer for CounterState {
+    type Action = CounterAction;
+
+    fn reduce(&mut self, action: CounterAction) {
+        match action {
+            CounterAction::Add(value) => self.count += value,
+            CounterAction::Reset => self.count = 0,
+        }
+        self.changes += 1;
+    }
+}
+
+#[component]
+fn Counter(ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
+    let md = ctx.use_metadata::<NoEvent, CounterState>();
+    let state = ctx.get_local_state(md);
+
+    ComponentOutput::new(
+        md,
+        Column!(
+            Label::new(format!("count={} changes={}", state.count, state.changes)),
+            Button::new("+2").dispatch(md, |_: ButtonClick| CounterAction::Add(2)),
+            Button::new("Reset").dispatch(md, |_: ButtonClick| CounterAction::Reset),
+        ),
+    )
+}
+
+use panoramix::test_harness::Harness;
+use test_env_log::test;
+
+#[test]
+fn reduce_without_ui() {
+    let mut state = CounterState::default();
+    state.reduce(CounterAction::Add(3));
+    state.reduce(CounterAction::Add(-1));
+    assert_eq!(
+        state,
+        CounterState {
+            count: 2,
+            changes: 2
+        }
+    );
+
+    state.reduce(CounterAction::Reset);
+    assert_eq!(
+        state,
+        CounterState {
+            count: 0,
+            changes: 3
+        }
+    );
+}
+
+#[test]
+fn dispatch_actions() {
+    let element = Counter::new(());
+
+    Harness::run_test_window(element, |harness| {
+        let add_id = harness.widget_id_at("Counter/Column/Button");
+        let reset_id = harness.widget_id_at("Counter/Column/Button#1");
+
+        assert!(harness.contains_text("count=0 changes=0"));
+
+        harness.mouse_click_on(add_id);
+        harness.mouse_click_on(add_id);
+        assert!(harness.contains_text("count=4 changes=2"));
+
+        harness.mouse_click_on(reset_id);
+        assert!(harness.contains_text("count=0 changes=3"));
+    });
+}
@@ -0,0 +1,119 @@
+use panoramix::elements::{Button, ButtonClick, ComponentOutput, ElementList, Label};
+use panoramix::{component, Column, CompCtx, Element, ElementExt, NoEvent, RootWidget, Row};
+
+#[component]
+fn Counter(ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
+    let md = ctx.use_metadata::<NoEvent, i32>();
+    let count = ctx.get_local_state(md);
+
+    ComponentOutput::new(
+        md,
+        Column!(
+            Label::new(format!("count={}", count)),
+            Button::new("+").on(md, |count, _: ButtonClick| *count += 1),
+        ),
+    )
+}
+
+#[component]
+fn TwoCounters(_ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
+    Column!(Counter::new(()), Counter::new(()))
+}
+
+#[component]
+fn ItemList(ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
+    let md = ctx.use_metadata::<NoEvent, Vec<String>>();
+    let items = ctx.get_local_state(md);
+
+    ComponentOutput::new(
+        md,
+        Column!(
+            Button::new("Add").on(md, |items: &mut Vec<String>, _: ButtonClick| {
+                items.push(format!("item {}", items.len()));
+            }),
+            Button::new("Remove").on(md, |items: &mut Vec<String>, _: ButtonClick| {
+                items.pop();
+            }),
+            ElementList::from_pairs(items.iter().map(|item| {
+                (
+                    item.clone(),
+                    Row!(Label::new(item.clone()), Label::new("...")),
+                )
+            })),
+        ),
+    )
+}
+
+use panoramix::test_harness::Harness;
+use test_env_log::test;
+
+#[test]
+fn count_builds() {
+    Harness::run_test_root_widget(RootWidget::new(TwoCounters), |harness| {
+        // Builds are counted without profiling the app.
+        assert!(harness.profile_report().is_none());
+        let render_counts = harness.render_counts();
+        assert_eq!(render_counts.builds_of("TwoCounters"), 1);
+        assert_eq!(render_counts.builds_of("TwoCounters/Column/Counter#1"), 1);
+        assert!(render_counts.widgets_created > 0);
+
+        harness.reset_render_counts();
+        assert_eq!(harness.render_counts(), Default::default());
+
+        harness
+            .find_by_path("TwoCounters/Column/Counter#1/Column/Button")
+            .click();
+
+        // Local state changes rebuild the whole app, but don't replace any widget.
+        harness.assert_rebuilt(&[
+            "TwoCounters",
+            "TwoCounters/Column/Counter",
+            "TwoCounters/Column/Counter#1",
+        ]);
+        let render_counts = harness.render_counts();
+        assert_eq!(render_counts.widgets_created, 0);
+        assert_eq!(render_counts.widgets_destroyed, 0);
+    });
+}
+
+#[test]
+fn count_widget_changes() {
+    Harness::run_test_root_widget(RootWidget::new(ItemList), |harness| {
+        harness.reset_render_counts();
+        harness.find_by_text("Add").click();
+
+        let render_counts = harness.render_counts();
+        assert_eq!(render_counts.rebuilt_instances(), vec!["ItemList"]);
+        assert!(render_counts.widgets_created > 0);
+        assert_eq!(render_counts.widgets_destroyed, 0);
+
+        harness.reset_render_counts();
+        harness.find_by_text("Remove").click();
+
+        let render_counts = harness.render_counts();
+        assert_eq!(render_counts.widgets_created, 0);
+        assert!(render_counts.widgets_destroyed > 0);
+    });
+}
+
+#[test]
+fn identical_update() {
+    Harness::run_test_window(Label::new("Hello"), |harness| {
+        harness.reset_render_counts();
+        harness.update_root_element(Label::new("Hello"));
+
+        let render_counts = harness.render_counts();
+        assert_eq!(render_counts.widgets_created, 0);
+        assert_eq!(render_counts.widgets_destroyed, 0);
+    });
+}
+
+#[test]
+#[should_panic(expected = "error: expected [] to be rebuilt")]
+fn unexpected_rebuild() {
+    Harness::run_test_root_widget(RootWidget::new(ItemList), |harness| {
+        harness.reset_render_counts();
+        harness.find_by_text("Add").click();
+        harness.assert_rebuilt(&[]);
+    });
+}
@@ -0,0 +1,56 @@
+use panoramix::elements::{Button, ButtonClick, ComponentOutput, Label};
+use panoramix::{component, Column, CompCtx, Element, ElementExt, NoEvent, RootWidget};
+
+use std::sync::mpsc::channel;
+
+#[derive(Debug, Clone, PartialEq)]
+enum AppEvent {
+    SaveRequested,
+}
+
+#[component]
+fn Greeting(ctx: &CompCtx, name: String) -> impl Element<Event = NoEvent> {
+    Label::new(format!("Hello {}", name))
+}
+
+#[component]
+fn SaveButton(ctx: &CompCtx, _props: ()) -> impl Element<Event = AppEvent> {
+    let md = ctx.use_metadata::<AppEvent, ()>();
+    ComponentOutput::new(
+        md,
+        Column!(
+            Button::new("Save").map_event(md, |_, _: ButtonClick| Some(AppEvent::SaveRequested))
+        ),
+    )
+}
+
+use panoramix::test_harness::Harness;
+use test_env_log::test;
+
+#[test]
+fn root_props() {
+    let root_widget = RootWidget::with_props(Greeting, "Alice".to_string());
+
+    Harness::run_test_root_widget(root_widget, |harness| {
+        assert!(harness.contains_text("Hello Alice"));
+    });
+}
+
+#[test]
+fn root_events() {
+    let (sender, receiver) = channel();
+    let root_widget =
+        RootWidget::new(SaveButton).on_root_event(move |event| sender.send(event).unwrap());
+
+    Harness::run_test_root_widget(root_widget, |harness| {
+        let button_id = harness.widget_id_at("SaveButton/Column/Button");
+        harness.mouse_click_on(button_id);
+        harness.mouse_click_on(button_id);
+    });
+
+    let events: Vec<_> = receiver.try_iter().collect();
+    assert_eq!(
+        events,
+        vec![AppEvent::SaveRequested, AppEvent::SaveRequested]
+    );
+}
@@ -0,0 +1,64 @@
+use panoramix::elements::{Button, ButtonClick, ComponentOutput, Label};
+#[cfg(feature = "png")]
+use panoramix::screenshot::save_screenshots;
+use panoramix::screenshot::Screenshot;
+use panoramix::{component, Column, CompCtx, Element, ElementExt, NoEvent, RootWidget};
+
+use druid::Size;
+
+#[component]
+fn Counter(ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
+    let md = ctx.use_metadata::<NoEvent, i32>();
+    let count = ctx.get_local_state(md);
+
+    ComponentOutput::new(
+        md,
+        Column!(
+            Label::new(format!("count={}", count)),
+            Button::new("+").on(md, |count, _: ButtonClick| *count += 1),
+        ),
+    )
+}
+
+use test_env_log::test;
+
+#[test]
+fn screenshot_with_script() {
+    let image = Screenshot::new(RootWidget::new(Counter))
+        .with_window_size(Size::new(200., 100.))
+        .render();
+    assert_eq!((image.width, image.height), (200, 100));
+
+    let clicked_image = Screenshot::new(RootWidget::new(Counter))
+        .with_window_size(Size::new(200., 100.))
+        .with_script(|harness| {
+            let button_id = harness.widget_id_at("Counter/Column/Button");
+            harness.mouse_click_on(button_id);
+        })
+        .render();
+    assert!(clicked_image.compare(&image, 0).differing_pixels > 0);
+}
+
+#[test]
+#[cfg(feature = "png")]
+fn screenshots_per_window_size() {
+    let dir = std::env::temp_dir().join("panoramix_screenshots_per_window_size");
+    let window_sizes = [Size::new(200., 100.), Size::new(300., 150.)];
+
+    let paths = save_screenshots(&dir, "counter", &window_sizes, || {
+        Screenshot::new(RootWidget::new(Counter))
+    })
+    .unwrap();
+
+    assert_eq!(
+        paths,
+        vec![
+            dir.join("counter-200x100.png"),
+            dir.join("counter-300x150.png")
+        ]
+    );
+    for path in &paths {
+        assert!(path.exists());
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+}
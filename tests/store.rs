@@ -0,0 +1,80 @@
+use panoramix::elements::{Button, ButtonClick, Label};
+use panoramix::{component, Column, CompCtx, Element, ElementExt, NoEvent, RootWidget};
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct AppState {
+    count: i32,
+    last_click_time: u64,
+}
+
+#[component]
+fn CountLabel(ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
+    let count = ctx.use_selector(|state: &AppState| state.count);
+    Label::new(format!("count={}", count))
+}
+
+#[component]
+fn ClickTimeLabel(ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
+    let time = ctx.use_selector(|state: &AppState| state.last_click_time);
+    Label::new(format!("last click={}", time))
+}
+
+#[component]
+fn Controls(_ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
+    Column!(
+        CountLabel::new(()),
+        Button::new("Increment").on_store(|state: &mut AppState, _: ButtonClick| state.count += 1),
+        Button::new("Touch")
+            .on_store(|state: &mut AppState, _: ButtonClick| state.last_click_time += 1),
+    )
+}
+
+#[component]
+fn Dashboard(_ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
+    Column!(Controls::new(()), ClickTimeLabel::new(()))
+}
+
+use panoramix::test_harness::Harness;
+use test_env_log::test;
+
+#[test]
+fn select_and_modify_store() {
+    let root_widget = RootWidget::new(Controls).with_store(AppState::default());
+
+    Harness::run_test_root_widget(root_widget, |harness| {
+        let increment_id = harness.widget_id_at("Controls/Column/Button");
+        let touch_id = harness.widget_id_at("Controls/Column/Button#1");
+        assert!(harness.contains_text("count=0"));
+
+        harness.mouse_click_on(increment_id);
+        harness.mouse_click_on(increment_id);
+        assert!(harness.contains_text("count=2"));
+
+        // The selected slice doesn't change, so the app isn't rebuilt.
+        harness.reset_render_counts();
+        harness.mouse_click_on(touch_id);
+        harness.assert_rebuilt(&[]);
+        assert!(harness.contains_text("count=2"));
+    });
+}
+
+#[test]
+fn only_rebuild_selecting_components() {
+    let root_widget = RootWidget::new(Dashboard).with_store(AppState::default());
+
+    Harness::run_test_root_widget(root_widget, |harness| {
+        harness.reset_render_counts();
+        harness.find_by_text("Touch").click();
+
+        // CountLabel selects an unrelated slice, and its parents select nothing.
+        harness.assert_rebuilt(&["Dashboard/Column/ClickTimeLabel"]);
+        assert!(harness.contains_text("last click=1"));
+        assert!(harness.contains_text("count=0"));
+
+        harness.reset_render_counts();
+        harness.find_by_text("Increment").click();
+
+        harness.assert_rebuilt(&["Dashboard/Column/Controls/Column/CountLabel"]);
+        assert!(harness.contains_text("count=1"));
+    });
+}
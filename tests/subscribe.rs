@@ -0,0 +1,45 @@
+use panoramix::elements::{ComponentOutput, Label, Subscribe, Subscription};
+use panoramix::{component, Column, CompCtx, Element, ElementExt, NoEvent};
+
+#[component]
+fn DataFeed(ctx: &CompCtx, feed: Subscription<i32>) -> impl Element<Event = NoEvent> {
+    let md = ctx.use_metadata::<NoEvent, Vec<i32>>();
+    let values = ctx.get_local_state(md);
+
+    ComponentOutput::new(
+        md,
+        Column!(
+            Label::new(format!("values={:?}", values)),
+            Subscribe::new(feed).on(md, |values: &mut Vec<i32>, value: i32| values.push(value)),
+        ),
+    )
+}
+
+use panoramix::test_harness::Harness;
+use test_env_log::test;
+
+#[test]
+fn receive_messages() {
+    let (sender, subscription) = Subscription::channel();
+    let element = DataFeed::new(subscription);
+
+    Harness::run_test_window(element, |harness| {
+        assert!(harness.contains_text("values=[]"));
+
+        std::thread::spawn({
+            let sender = sender.clone();
+            move || {
+                for value in 1..=3 {
+                    sender.send(value).unwrap();
+                }
+            }
+        })
+        .join()
+        .unwrap();
+
+        // In a running application, sending a message wakes up the application.
+        harness.process_external_messages();
+
+        assert!(harness.contains_text("values=[1, 2, 3]"));
+    });
+}
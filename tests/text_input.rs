@@ -0,0 +1,83 @@
+use panoramix::elements::{Button, ButtonClick, ComponentOutput, Label, TextBox, TextChanged};
+use panoramix::{component, Column, CompCtx, Element, ElementExt, NoEvent};
+
+#[derive(Debug, Default, Clone, PartialEq)]
+struct AppState {
+    new_task: String,
+    tasks: Vec<String>,
+}
+
+#[component]
+fn TaskEditor(ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
+    let md = ctx.use_metadata::<NoEvent, AppState>();
+    let state = ctx.get_local_state(md);
+
+    ComponentOutput::new(
+        md,
+        Column!(
+            TextBox::new(state.new_task.clone()).on(
+                md,
+                |state: &mut AppState, event: TextChanged| {
+                    state.new_task = event.new_content;
+                }
+            ),
+            Button::new("Add").on(md, |state: &mut AppState, _: ButtonClick| {
+                let task = std::mem::take(&mut state.new_task);
+                state.tasks.push(task);
+            }),
+            Label::new(state.tasks.join(", ")),
+        ),
+    )
+}
+
+use panoramix::test_harness::{Harness, Role};
+use panoramix::RootWidget;
+use test_env_log::test;
+
+#[test]
+fn type_and_edit() {
+    Harness::run_test_root_widget(RootWidget::new(TaskEditor), |harness| {
+        harness
+            .find_all_by_role(Role::TextBox)
+            .single()
+            .type_text("buy milk");
+        harness
+            .find_component("TaskEditor")
+            .single()
+            .assert_state("AppState { new_task: \"buy milk\", tasks: [] }");
+
+        let textbox_id = harness
+            .find_all_by_role(Role::TextBox)
+            .single()
+            .widget_id()
+            .unwrap();
+        harness.backspace(textbox_id);
+        harness.backspace(textbox_id);
+        harness.type_text(textbox_id, "lk");
+        harness.find_by_text("Add").click();
+
+        harness.find_by_text("buy milk");
+        harness
+            .find_component("TaskEditor")
+            .single()
+            .assert_state("AppState { new_task: \"\", tasks: [\"buy milk\"] }");
+    });
+}
+
+#[test]
+fn select_all_and_paste() {
+    Harness::run_test_root_widget(RootWidget::new(TaskEditor), |harness| {
+        let textbox_id = harness
+            .find_all_by_role(Role::TextBox)
+            .single()
+            .widget_id()
+            .unwrap();
+
+        harness.paste(textbox_id, "first draft");
+        harness.select_all(textbox_id);
+        harness.paste(textbox_id, "walk the dog");
+        harness.find_by_text("Add").click();
+
+        harness.find_by_text("walk the dog");
+    });
+}
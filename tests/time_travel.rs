@@ -0,0 +1,127 @@
+use panoramix::elements::{Button, ButtonClick, ComponentOutput, Label};
+use panoramix::{component, Column, CompCtx, Element, ElementExt, NoEvent, RootWidget, TimeTravel};
+
+#[component]
+fn Counter(ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
+    let md = ctx.use_metadata::<NoEvent, i32>();
+    let count = ctx.get_local_state(md);
+
+    ComponentOutput::new(
+        md,
+        Column!(
+            Label::new(format!("count={}", count)),
+            Button::new("+").on(md, |count, _: ButtonClick| *count += 1),
+        ),
+    )
+}
+
+use panoramix::test_harness::Harness;
+use test_env_log::test;
+
+#[test]
+fn step_through_states() {
+    let root_widget = RootWidget::new(Counter).with_recording();
+
+    Harness::run_test_root_widget(root_widget, |harness| {
+        let button_id = harness.widget_id_at("Counter/Column/Button");
+        harness.mouse_click_on(button_id);
+        harness.mouse_click_on(button_id);
+        harness.mouse_click_on(button_id);
+
+        let recording = harness.recording().unwrap();
+        assert_eq!(recording.transitions.len(), 3);
+
+        harness.time_travel(TimeTravel::StepBack);
+        harness.time_travel(TimeTravel::StepBack);
+        assert!(harness.contains_text("count=1"));
+
+        harness.time_travel(TimeTravel::StepForward);
+        assert!(harness.contains_text("count=2"));
+
+        harness.time_travel(TimeTravel::GoTo(0));
+        assert!(harness.contains_text("count=0"));
+
+        harness.time_travel(TimeTravel::Resume);
+        assert!(harness.contains_text("count=3"));
+    });
+}
+
+#[test]
+fn change_past_state() {
+    let root_widget = RootWidget::new(Counter).with_recording();
+
+    Harness::run_test_root_widget(root_widget, |harness| {
+        let button_id = harness.widget_id_at("Counter/Column/Button");
+        harness.mouse_click_on(button_id);
+        harness.mouse_click_on(button_id);
+        harness.mouse_click_on(button_id);
+
+        // Changing count=2 forks the session: count=3 is dropped.
+        harness.time_travel(TimeTravel::StepBack);
+        harness.mouse_click_on(button_id);
+        assert!(harness.contains_text("count=3"));
+        assert_eq!(harness.recording().unwrap().transitions.len(), 3);
+
+        harness.time_travel(TimeTravel::StepBack);
+        assert!(harness.contains_text("count=2"));
+    });
+}
+
+#[test]
+fn replay_forked_session() {
+    let mut recording = None;
+    let root_widget = RootWidget::new(Counter).with_recording();
+    Harness::run_test_root_widget(root_widget, |harness| {
+        let button_id = harness.widget_id_at("Counter/Column/Button");
+        harness.mouse_click_on(button_id);
+        harness.mouse_click_on(button_id);
+        harness.time_travel(TimeTravel::GoTo(0));
+        harness.mouse_click_on(button_id);
+        recording = harness.recording();
+    });
+    let recording = recording.unwrap();
+
+    // The events of the dropped transitions aren't replayed.
+    let root_widget = RootWidget::new(Counter).with_recording();
+    Harness::run_test_root_widget(root_widget, |harness| {
+        harness.replay(&recording);
+        assert!(harness.contains_text("count=1"));
+    });
+}
+
+#[test]
+fn replay_session() {
+    let mut recording = None;
+    let root_widget = RootWidget::new(Counter).with_recording();
+    Harness::run_test_root_widget(root_widget, |harness| {
+        let button_id = harness.widget_id_at("Counter/Column/Button");
+        harness.mouse_click_on(button_id);
+        harness.mouse_click_on(button_id);
+        recording = harness.recording();
+    });
+    let recording = recording.unwrap();
+
+    let root_widget = RootWidget::new(Counter).with_recording();
+    Harness::run_test_root_widget(root_widget, |harness| {
+        harness.replay(&recording);
+        assert!(harness.contains_text("count=2"));
+    });
+}
+
+#[test]
+#[should_panic(expected = "records its session")]
+fn replay_without_recording() {
+    let mut recording = None;
+    let root_widget = RootWidget::new(Counter).with_recording();
+    Harness::run_test_root_widget(root_widget, |harness| {
+        let button_id = harness.widget_id_at("Counter/Column/Button");
+        harness.mouse_click_on(button_id);
+        recording = harness.recording();
+    });
+    let recording = recording.unwrap();
+
+    let root_widget = RootWidget::new(Counter);
+    Harness::run_test_root_widget(root_widget, |harness| {
+        harness.replay(&recording);
+    });
+}
@@ -0,0 +1,79 @@
+use panoramix::elements::{Button, ButtonClick, ComponentOutput, ElementList, Label};
+use panoramix::{component, Column, CompCtx, Element, ElementExt, NoEvent, Row};
+
+#[component]
+fn TaskRow(_ctx: &CompCtx, name: String) -> impl Element<Event = NoEvent> {
+    Row!(Label::new(name), Button::new("Remove"))
+}
+
+#[component]
+fn TaskList(ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
+    let md = ctx.use_metadata::<NoEvent, Vec<String>>();
+    let tasks = ctx.get_local_state(md);
+
+    ComponentOutput::new(
+        md,
+        Column!(
+            Label::new(format!("{} tasks", tasks.len())),
+            Button::new("Add task").on(md, |tasks: &mut Vec<String>, _: ButtonClick| {
+                tasks.push(format!("task {}", tasks.len()));
+            }),
+            ElementList::from_pairs(
+                tasks
+                    .iter()
+                    .map(|task| (task.clone(), TaskRow::new(task.clone()))),
+            ),
+        ),
+    )
+}
+
+use panoramix::test_harness::Harness;
+use panoramix::RootWidget;
+use test_env_log::test;
+
+#[test]
+fn render_tree_text() {
+    Harness::run_test_root_widget(RootWidget::new(TaskList), |harness| {
+        harness.assert_tree_text(
+            r#"
+            <TaskList> state=[]
+              Column
+                Label "0 tasks"
+                Button "Add task"
+                List
+            "#,
+        );
+
+        harness.find_by_text("Add task").click();
+
+        harness.assert_tree_text(
+            r#"
+            <TaskList> state=["task 0"]
+              Column
+                Label "1 tasks"
+                Button "Add task"
+                List
+                  [task 0] <TaskRow> props="task 0"
+                    Row
+                      Label "task 0"
+                      Button "Remove"
+            "#,
+        );
+    });
+}
+
+#[test]
+#[should_panic(expected = "+     Label \"0 tasks\"")]
+fn tree_text_mismatch() {
+    Harness::run_test_root_widget(RootWidget::new(TaskList), |harness| {
+        harness.assert_tree_text(
+            r#"
+            <TaskList> state=[]
+              Column
+                Label "1 tasks"
+                Button "Add task"
+                List
+            "#,
+        );
+    });
+}
@@ -0,0 +1,85 @@
+use panoramix::elements::{Button, EmptyElement, Label};
+use panoramix::flex::{BorderStyle, ContainerStyle};
+use panoramix::{Column, Element, NoEvent, Row};
+
+use druid::Color;
+
+use panoramix::test_harness::{Harness, RenderedImage};
+use test_env_log::test;
+
+const RED: [u8; 4] = [255, 0, 0, 255];
+
+fn has_pixel(image: &RenderedImage, color: [u8; 4]) -> bool {
+    image.pixels.chunks_exact(4).any(|pixel| pixel == color)
+}
+
+fn bordered_row() -> impl Element<Event = NoEvent> {
+    Row!(Label::new("Hello"), Button::new("+")).with_container_style(ContainerStyle {
+        background: None,
+        border: Some(BorderStyle {
+            width: 2.0.into(),
+            color: Color::rgb8(255, 0, 0).into(),
+        }),
+        corner_radius: 0.0.into(),
+    })
+}
+
+#[test]
+fn render_bordered_row() {
+    Harness::run_test_window(bordered_row(), |harness| {
+        let image = harness.render_to_image();
+        assert_eq!((image.width, image.height), (400, 400));
+        assert!(has_pixel(&image, RED));
+
+        // Rendering is deterministic.
+        let second_image = harness.render_to_image();
+        assert_eq!(second_image.compare(&image, 0).differing_pixels, 0);
+    });
+}
+
+#[test]
+fn render_after_update() {
+    let element = Row!(Label::new("Hello"));
+
+    Harness::run_test_window(element, |harness| {
+        let before = harness.render_to_image();
+        harness.update_root_element(Row!(Label::new("Hello world, this is a longer label")));
+        let after = harness.render_to_image();
+
+        assert!(after.compare(&before, 0).differing_pixels > 0);
+    });
+}
+
+// Reference images are in tests/snapshots/images/. To create or update them, run the tests
+// with PANORAMIX_UPDATE_SNAPSHOTS=1.
+
+#[test]
+#[cfg(feature = "png")]
+fn empty_window_snapshot() {
+    // The window background.
+    Harness::run_test_window(EmptyElement::new(), |harness| {
+        harness.assert_image_snapshot("empty_window", 2);
+    });
+}
+
+#[test]
+#[cfg(feature = "png")]
+fn bordered_row_snapshot() {
+    Harness::run_test_window(bordered_row(), |harness| {
+        harness.assert_image_snapshot("bordered_row", 2);
+    });
+}
+
+#[test]
+#[cfg(feature = "png")]
+fn text_snapshot() {
+    let element = Column!(
+        Label::new("Hello world"),
+        Label::new("A second, longer line of text"),
+        Button::new("Submit"),
+    );
+
+    Harness::run_test_window(element, |harness| {
+        harness.assert_image_snapshot("text", 2);
+    });
+}
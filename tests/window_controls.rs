@@ -0,0 +1,134 @@
+use panoramix::elements::{
+    Button, ButtonClick, ComponentOutput, ElementList, Label, TextBox, TextChanged,
+};
+use panoramix::{component, Column, CompCtx, Element, ElementExt, NoEvent};
+
+#[component]
+fn ItemList(ctx: &CompCtx, item_count: usize) -> impl Element<Event = NoEvent> {
+    let md = ctx.use_metadata::<NoEvent, String>();
+    let clicked = ctx.get_local_state(md);
+
+    ComponentOutput::new(
+        md,
+        Column!(
+            Label::new(format!("clicked {}", clicked)),
+            ElementList::from_pairs((0..item_count).map(|i| {
+                let name = format!("Item {}", i);
+                let button = Button::new(name.clone()).on(
+                    md,
+                    move |clicked: &mut String, _: ButtonClick| {
+                        *clicked = name.clone();
+                    },
+                );
+                (i.to_string(), button)
+            })),
+        ),
+    )
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+struct FormState {
+    first_name: String,
+    last_name: String,
+}
+
+#[component]
+fn Form(ctx: &CompCtx, _props: ()) -> impl Element<Event = NoEvent> {
+    let md = ctx.use_metadata::<NoEvent, FormState>();
+    let state = ctx.get_local_state(md);
+
+    ComponentOutput::new(
+        md,
+        Column!(
+            TextBox::new(state.first_name.clone()).on(
+                md,
+                |state: &mut FormState, event: TextChanged| {
+                    state.first_name = event.new_content;
+                }
+            ),
+            TextBox::new(state.last_name.clone()).on(
+                md,
+                |state: &mut FormState, event: TextChanged| {
+                    state.last_name = event.new_content;
+                }
+            ),
+            Button::new("Submit"),
+        ),
+    )
+}
+
+use druid::{Size, Vec2};
+use panoramix::test_harness::{Harness, ScriptStep};
+use panoramix::RootWidget;
+use test_env_log::test;
+
+#[test]
+fn resize_window() {
+    Harness::run_test_root_widget(RootWidget::with_props(ItemList, 5), |harness| {
+        // The list fits in the window.
+        harness.scroll_by((0., 50.));
+        assert_eq!(harness.scroll_offset(), Vec2::ZERO);
+
+        harness.start_script_recording();
+        harness.resize((400., 100.));
+        assert_eq!(harness.window_size(), Size::new(400., 100.));
+
+        harness.scroll_by((0., 50.));
+        assert_eq!(harness.scroll_offset(), Vec2::new(0., 50.));
+
+        assert_eq!(
+            harness.stop_script_recording().steps,
+            vec![
+                ScriptStep::Resize {
+                    width: 400.,
+                    height: 100.,
+                },
+                ScriptStep::Wheel { dx: 0., dy: 50. },
+            ]
+        );
+    });
+}
+
+#[test]
+fn click_in_scrolled_list() {
+    let root_widget = RootWidget::with_props(ItemList, 20);
+    Harness::run_test_root_widget_with_size(root_widget, Size::new(200., 100.), |harness| {
+        harness.scroll_by((0., 10_000.));
+        assert!(harness.scroll_offset().y > 0.);
+
+        harness.find_by_text("Item 19").click();
+        harness.find_by_text("clicked Item 19");
+    });
+}
+
+#[test]
+fn move_focus() {
+    Harness::run_test_root_widget(RootWidget::new(Form), |harness| {
+        let first_name = harness.widget_id_at("Form/Column/TextBox");
+        let last_name = harness.widget_id_at("Form/Column/TextBox#1");
+        assert_eq!(harness.focused_widget(), None);
+
+        harness.mouse_click_on(first_name);
+        assert_eq!(harness.focused_widget(), Some(first_name));
+
+        harness.focus_next();
+        assert_eq!(harness.focused_widget(), Some(last_name));
+
+        harness.focus_next();
+        assert_eq!(harness.focused_widget(), Some(first_name));
+    });
+}
+
+#[test]
+fn hover_button() {
+    Harness::run_test_root_widget(RootWidget::new(Form), |harness| {
+        let submit = harness.widget_id_at("Form/Column/Button");
+        let first_name = harness.widget_id_at("Form/Column/TextBox");
+
+        let submit_rect = harness.inspect().window_rect(submit).unwrap();
+        harness.mouse_move(submit_rect.center());
+
+        assert!(harness.hover_state(submit));
+        assert!(!harness.hover_state(first_name));
+    });
+}